@@ -33,7 +33,10 @@ use odyssey_node::{
     node::OdysseyNode,
     rpc::{EthApiExt, EthApiOverrideServer},
 };
-use odyssey_wallet::{OdysseyWallet, OdysseyWalletApiServer};
+use odyssey_wallet::{
+    balance_monitor, BalanceMonitorConfig, BudgetAlerts, DelegationCapability, OdysseyWallet,
+    OdysseyWalletAdminApiServer, OdysseyWalletApiServer, OdysseyWalletConfig,
+};
 use odyssey_walltime::{OdysseyWallTime, OdysseyWallTimeRpcApiServer};
 use reth_node_builder::{engine_tree_config::TreeConfig, EngineNodeLauncher, NodeComponents};
 use reth_optimism_cli::Cli;
@@ -56,6 +59,13 @@ fn main() {
     if let Err(err) =
         Cli::<OdysseyChainSpecParser, RollupArgs>::parse().run(|builder, rollup_args| async move {
             let wallet = sponsor()?;
+            // without a whitelist, the wallet would not sponsor any delegation
+            let delegation_whitelist =
+                wallet.as_ref().map(|_| delegation_whitelist()).transpose()?;
+            let forwards_to_sequencer = rollup_args.sequencer_http.is_some();
+            // pushes alerts on the sponsor balance to `wallet_subscribeBudget`
+            let budget_alerts = BudgetAlerts::new(Vec::new());
+            let balance_alerts = budget_alerts.clone();
             let address = wallet
                 .as_ref()
                 .map(<EthereumWallet as NetworkWallet<Ethereum>>::default_signer_address);
@@ -78,6 +88,14 @@ fn main() {
                             )
                             .await
                         });
+
+                        // the executor cancels the monitor on shutdown
+                        ctx.task_executor.spawn(balance_monitor(
+                            ctx.provider.clone(),
+                            address,
+                            BalanceMonitorConfig::default(),
+                            Some(balance_alerts),
+                        ));
                     }
 
                     Ok(())
@@ -89,16 +107,21 @@ fn main() {
                     )?;
 
                     // register odyssey wallet namespace
-                    if let Some(wallet) = wallet {
-                        ctx.modules.merge_configured(
-                            OdysseyWallet::new(
-                                ctx.provider().clone(),
-                                wallet,
-                                ctx.registry.eth_api().clone(),
-                                ctx.config().chain.chain().id(),
-                            )
-                            .into_rpc(),
+                    if let Some((wallet, delegation_whitelist)) = wallet.zip(delegation_whitelist) {
+                        let wallet = OdysseyWallet::with_config(
+                            ctx.provider().clone(),
+                            wallet,
+                            ctx.registry.eth_api().clone(),
+                            ctx.config().chain.chain().id(),
+                            OdysseyWalletConfig::default()
+                                .with_delegation_whitelist(delegation_whitelist)
+                                .with_forwards_to_sequencer(forwards_to_sequencer)
+                                .with_budget_alerts(budget_alerts),
                         )?;
+                        // the admin namespace is only served locally
+                        ctx.modules
+                            .merge_ipc(OdysseyWalletAdminApiServer::into_rpc(wallet.clone()))?;
+                        ctx.modules.merge_configured(OdysseyWalletApiServer::into_rpc(wallet))?;
                     }
 
                     let walltime = OdysseyWallTime::spawn(ctx.provider().canonical_state_stream());
@@ -146,3 +169,17 @@ fn sponsor() -> eyre::Result<Option<EthereumWallet>> {
         })
         .transpose()
 }
+
+/// Returns the whitelist of delegation contracts, a comma-separated list of addresses.
+fn delegation_whitelist() -> eyre::Result<DelegationCapability> {
+    let whitelist = std::env::var("EXP1_WHITELIST")
+        .wrap_err("EXP0001 delegation whitelist not configured.")?
+        .parse::<DelegationCapability>()
+        .wrap_err("Invalid EXP0001 delegation whitelist.")?;
+    info!(
+        target: "reth::cli",
+        addresses = whitelist.addresses.len(),
+        "EXP0001 delegation whitelist configured"
+    );
+    Ok(whitelist)
+}
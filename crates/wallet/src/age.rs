@@ -0,0 +1,91 @@
+//! Enforcement of a minimum age of delegation targets.
+//!
+//! The block in which a contract was deployed is not indexed, so the age of a target is derived
+//! from historical state instead: a target that had code `min_age_blocks` before the latest block
+//! was deployed at least that long ago. This requires a single state lookup, but assumes the code
+//! was not replaced in between, e.g. by a redeployment to the same address.
+
+use alloy_primitives::{Address, BlockNumber};
+use parking_lot::Mutex;
+use std::collections::HashSet;
+
+/// The maximum number of targets remembered as old enough.
+const MAX_CACHED_TARGETS: usize = 1_024;
+
+/// Tracks which delegation targets are old enough to be sponsored.
+///
+/// A target that is old enough stays old enough, so positive results are remembered. Targets that
+/// are too new are checked again on the next request.
+#[derive(Debug)]
+pub(crate) struct TargetAges {
+    min_age_blocks: u64,
+    old_enough: Mutex<HashSet<Address>>,
+}
+
+impl TargetAges {
+    /// Creates a new tracker for the given minimum age.
+    pub(crate) fn new(min_age_blocks: u64) -> Self {
+        Self { min_age_blocks, old_enough: Default::default() }
+    }
+
+    /// Returns whether the target was deployed at least the minimum age before the `latest`
+    /// block.
+    ///
+    /// `has_code_at` returns whether the target has code at the given block.
+    pub(crate) fn is_old_enough<E>(
+        &self,
+        target: Address,
+        latest: BlockNumber,
+        has_code_at: impl FnOnce(BlockNumber) -> Result<bool, E>,
+    ) -> Result<bool, E> {
+        if self.old_enough.lock().contains(&target) {
+            return Ok(true);
+        }
+
+        // the chain is younger than the minimum age
+        let Some(block) = latest.checked_sub(self.min_age_blocks) else {
+            return Ok(false);
+        };
+        if !has_code_at(block)? {
+            return Ok(false);
+        }
+
+        let mut old_enough = self.old_enough.lock();
+        if old_enough.len() >= MAX_CACHED_TARGETS {
+            old_enough.clear();
+        }
+        old_enough.insert(target);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+
+    #[test]
+    fn rejects_new_targets() {
+        let ages = TargetAges::new(100);
+        let deployed_at = |deployment: BlockNumber| {
+            move |block: BlockNumber| Ok::<_, Infallible>(block >= deployment)
+        };
+        let old = Address::with_last_byte(1);
+        let new = Address::with_last_byte(2);
+
+        assert_eq!(ages.is_old_enough(old, 1_000, deployed_at(900)), Ok(true));
+        assert_eq!(ages.is_old_enough(new, 1_000, deployed_at(901)), Ok(false));
+
+        // old targets are remembered
+        assert_eq!(
+            ages.is_old_enough(old, 1_000, |_| -> Result<bool, Infallible> { unreachable!() }),
+            Ok(true)
+        );
+
+        // new targets mature
+        assert_eq!(ages.is_old_enough(new, 1_001, deployed_at(901)), Ok(true));
+
+        // nothing is old enough on a young chain
+        assert_eq!(ages.is_old_enough(Address::ZERO, 99, deployed_at(0)), Ok(false));
+    }
+}
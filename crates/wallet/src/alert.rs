@@ -0,0 +1,155 @@
+//! Push alerts on the funds available for sponsorship.
+//!
+//! Alerts are broadcast to the subscribers of `wallet_subscribeBudget` whenever the sponsorship
+//! budget or the balance of the signer crosses a threshold, so operator tooling does not have to
+//! poll. Broadcasting never waits for subscribers: a subscriber that falls more than
+//! [`MAX_PENDING_BUDGET_ALERTS`] alerts behind misses the oldest ones.
+
+use crate::{
+    balance::{crossing, Crossing},
+    BalanceAlertLevel, BalanceThreshold,
+};
+use alloy_primitives::U256;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// The maximum number of alerts buffered for a subscriber.
+pub const MAX_PENDING_BUDGET_ALERTS: usize = 64;
+
+/// The funds an alert is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BudgetAlertSource {
+    /// The remaining [sponsorship budget](crate::OdysseyWalletConfig::sponsorship_budget).
+    SponsorshipBudget,
+    /// The balance of the signer, as checked by the [`balance_monitor`](crate::balance_monitor).
+    SignerBalance,
+}
+
+/// An alert on a threshold crossing, pushed to the subscribers of `wallet_subscribeBudget`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetAlert {
+    /// The funds that crossed the threshold.
+    pub source: BudgetAlertSource,
+    /// The severity of the threshold.
+    pub level: BalanceAlertLevel,
+    /// The threshold in wei.
+    pub threshold: U256,
+    /// The remaining funds in wei.
+    pub remaining: U256,
+    /// Whether the funds recovered to or above the threshold, rather than dropped below it.
+    pub recovered: bool,
+}
+
+/// The channel budget alerts are broadcast on.
+///
+/// Clones share the same channel, so the same instance can be passed to the
+/// [`OdysseyWalletConfig`](crate::OdysseyWalletConfig::with_budget_alerts) and the
+/// [`balance_monitor`](crate::balance_monitor).
+#[derive(Debug, Clone)]
+pub struct BudgetAlerts {
+    inner: Arc<BudgetAlertsInner>,
+}
+
+#[derive(Debug)]
+struct BudgetAlertsInner {
+    /// The thresholds of the sponsorship budget.
+    thresholds: Vec<BalanceThreshold>,
+    sender: broadcast::Sender<BudgetAlert>,
+    /// The sponsorship budget as of the last observation.
+    previous_budget: Mutex<Option<U256>>,
+}
+
+impl BudgetAlerts {
+    /// Creates a new channel, alerting when the sponsorship budget crosses any of the
+    /// `thresholds`.
+    pub fn new(thresholds: Vec<BalanceThreshold>) -> Self {
+        let (sender, _) = broadcast::channel(MAX_PENDING_BUDGET_ALERTS);
+        Self {
+            inner: Arc::new(BudgetAlertsInner {
+                thresholds,
+                sender,
+                previous_budget: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Subscribes to the alerts broadcast from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<BudgetAlert> {
+        self.inner.sender.subscribe()
+    }
+
+    /// Alerts on the thresholds the sponsorship budget crossed since the last observation.
+    pub(crate) fn observe_budget(&self, remaining: U256) {
+        let previous = self.inner.previous_budget.lock().replace(remaining);
+        for threshold in &self.inner.thresholds {
+            self.alert_on_crossing(
+                BudgetAlertSource::SponsorshipBudget,
+                threshold,
+                crossing(previous, remaining, threshold.balance),
+                remaining,
+            );
+        }
+    }
+
+    /// Alerts on a crossing of the threshold, if any.
+    pub(crate) fn alert_on_crossing(
+        &self,
+        source: BudgetAlertSource,
+        threshold: &BalanceThreshold,
+        crossing: Option<Crossing>,
+        remaining: U256,
+    ) {
+        let Some(crossing) = crossing else {
+            return;
+        };
+        // sending only fails if there are no subscribers
+        let _ = self.inner.sender.send(BudgetAlert {
+            source,
+            level: threshold.level,
+            threshold: threshold.balance,
+            remaining,
+            recovered: crossing == Crossing::Above,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alerts_on_crossings() {
+        let alerts = BudgetAlerts::new(vec![
+            BalanceThreshold::warn(U256::from(500)),
+            BalanceThreshold::critical(U256::from(100)),
+        ]);
+        let mut receiver = alerts.subscribe();
+
+        alerts.observe_budget(U256::from(1_000));
+        alerts.observe_budget(U256::from(50));
+        alerts.observe_budget(U256::from(60));
+        alerts.observe_budget(U256::from(200));
+
+        let alert = |level, threshold: u64, remaining: u64, recovered| BudgetAlert {
+            source: BudgetAlertSource::SponsorshipBudget,
+            level,
+            threshold: U256::from(threshold),
+            remaining: U256::from(remaining),
+            recovered,
+        };
+        assert_eq!(receiver.try_recv().unwrap(), alert(BalanceAlertLevel::Warn, 500, 50, false));
+        assert_eq!(
+            receiver.try_recv().unwrap(),
+            alert(BalanceAlertLevel::Critical, 100, 50, false)
+        );
+        assert_eq!(
+            receiver.try_recv().unwrap(),
+            alert(BalanceAlertLevel::Critical, 100, 200, true)
+        );
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -0,0 +1,201 @@
+//! An in-memory audit log of the recent requests to sponsor transactions.
+//!
+//! This lets support staff inspect recent activity over `wallet_auditLog` without access to the
+//! logs of the node. Entries only summarize requests: calldata, signatures, attestation tokens
+//! and correlation ids are never recorded. Only the [`MAX_AUDIT_ENTRIES`] most recent entries are
+//! kept.
+
+use crate::OdysseyWalletError;
+use alloy_primitives::{hex, Address, TxHash};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, fmt};
+
+/// The maximum number of entries kept, the oldest are forgotten first.
+pub const MAX_AUDIT_ENTRIES: usize = 1_024;
+
+/// The maximum number of entries returned in a page.
+pub const MAX_AUDIT_PAGE_SIZE: usize = 100;
+
+/// The key operators authenticate with, e.g. to read the audit log.
+///
+/// The key is redacted from the debug output, so it never ends up in logs.
+#[derive(Clone, PartialEq, Eq)]
+pub struct OperatorApiKey(String);
+
+impl OperatorApiKey {
+    /// Creates a new key.
+    pub fn new(key: impl Into<String>) -> Self {
+        Self(key.into())
+    }
+
+    /// Ensures the provided key matches, in time independent of where they differ.
+    pub(crate) fn authorize(&self, provided: &str) -> Result<(), OdysseyWalletError> {
+        let (expected, provided) = (self.0.as_bytes(), provided.as_bytes());
+        let difference = expected.iter().zip(provided).fold(0, |acc, (a, b)| acc | (a ^ b));
+        if expected.len() != provided.len() || difference != 0 {
+            return Err(OdysseyWalletError::Unauthorized);
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for OperatorApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("OperatorApiKey(<redacted>)")
+    }
+}
+
+/// The summary of a request to sponsor a transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    /// When the request was decided, in unix seconds.
+    pub timestamp: u64,
+    /// The account the transaction is sent to, if any.
+    pub to: Option<Address>,
+    /// The size of the calldata in bytes.
+    pub calldata_bytes: usize,
+    /// Whether the transaction was sponsored.
+    pub accepted: bool,
+    /// The hash of the sponsored transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<TxHash>,
+    /// The code of the error the request was rejected with, see `wallet_errorCatalog`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<i32>,
+}
+
+/// A page of the audit log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLogPage {
+    /// The entries, most recent first.
+    pub entries: Vec<AuditEntry>,
+    /// The cursor of the next page of older entries, if there are any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// The recent requests to sponsor transactions.
+#[derive(Debug, Default)]
+pub(crate) struct AuditLog {
+    inner: Mutex<AuditEntries>,
+}
+
+#[derive(Debug, Default)]
+struct AuditEntries {
+    /// The entries by their sequence number, oldest first.
+    entries: VecDeque<(u64, AuditEntry)>,
+    /// Increases with every recorded entry.
+    sequence: u64,
+}
+
+impl AuditLog {
+    /// Records a request.
+    pub(crate) fn record(&self, entry: AuditEntry) {
+        let mut inner = self.inner.lock();
+        inner.sequence += 1;
+        let sequence = inner.sequence;
+        inner.entries.push_back((sequence, entry));
+        if inner.entries.len() > MAX_AUDIT_ENTRIES {
+            inner.entries.pop_front();
+        }
+    }
+
+    /// Returns up to `limit` entries older than the cursor, or the most recent entries if no
+    /// cursor is given.
+    ///
+    /// The cursor is opaque to clients. It stays valid as entries are recorded, but older
+    /// entries may have been forgotten in the meantime.
+    pub(crate) fn page(
+        &self,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<AuditLogPage, OdysseyWalletError> {
+        let before = cursor.map(decode_cursor).transpose()?.unwrap_or(u64::MAX);
+        let limit = limit.clamp(1, MAX_AUDIT_PAGE_SIZE);
+
+        let inner = self.inner.lock();
+        let mut older = inner.entries.iter().rev().skip_while(|(sequence, _)| *sequence >= before);
+        let page: Vec<_> = older.by_ref().take(limit).collect();
+        let next_cursor = match (page.last(), older.next()) {
+            (Some((last, _)), Some(_)) => Some(hex::encode(last.to_be_bytes())),
+            _ => None,
+        };
+        Ok(AuditLogPage {
+            entries: page.into_iter().map(|(_, entry)| entry.clone()).collect(),
+            next_cursor,
+        })
+    }
+}
+
+/// Decodes the sequence number of a cursor.
+fn decode_cursor(cursor: &str) -> Result<u64, OdysseyWalletError> {
+    let bytes = hex::decode(cursor).map_err(|_| OdysseyWalletError::InvalidAuditCursor)?;
+    let bytes = bytes.try_into().map_err(|_| OdysseyWalletError::InvalidAuditCursor)?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: u64) -> AuditEntry {
+        AuditEntry {
+            timestamp,
+            to: Some(Address::with_last_byte(1)),
+            calldata_bytes: 4,
+            accepted: timestamp % 2 == 0,
+            hash: None,
+            error_code: None,
+        }
+    }
+
+    #[test]
+    fn paginates_most_recent_first() {
+        let log = AuditLog::default();
+        for timestamp in 1..=5 {
+            log.record(entry(timestamp));
+        }
+
+        let first = log.page(None, 2).unwrap();
+        assert_eq!(first.entries, [entry(5), entry(4)]);
+        let second = log.page(first.next_cursor.as_deref(), 2).unwrap();
+        assert_eq!(second.entries, [entry(3), entry(2)]);
+
+        // entries recorded in the meantime do not shift the pages
+        log.record(entry(6));
+        let last = log.page(second.next_cursor.as_deref(), 2).unwrap();
+        assert_eq!(last, AuditLogPage { entries: vec![entry(1)], next_cursor: None });
+
+        assert_eq!(log.page(Some("not a cursor"), 2), Err(OdysseyWalletError::InvalidAuditCursor));
+    }
+
+    #[test]
+    fn forgets_oldest_entries() {
+        let log = AuditLog::default();
+        for timestamp in 0..=MAX_AUDIT_ENTRIES as u64 {
+            log.record(entry(timestamp));
+        }
+
+        let mut page = log.page(None, MAX_AUDIT_PAGE_SIZE).unwrap();
+        let mut entries = page.entries.len();
+        while let Some(cursor) = page.next_cursor {
+            page = log.page(Some(&cursor), MAX_AUDIT_PAGE_SIZE).unwrap();
+            entries += page.entries.len();
+        }
+        assert_eq!(entries, MAX_AUDIT_ENTRIES);
+        assert_eq!(page.entries.last(), Some(&entry(1)));
+    }
+
+    #[test]
+    fn authorizes_operators() {
+        let key = OperatorApiKey::new("secret");
+        assert_eq!(key.authorize("secret"), Ok(()));
+        assert_eq!(key.authorize("secreT"), Err(OdysseyWalletError::Unauthorized));
+        assert_eq!(key.authorize("secret2"), Err(OdysseyWalletError::Unauthorized));
+        assert_eq!(key.authorize(""), Err(OdysseyWalletError::Unauthorized));
+        assert!(!format!("{key:?}").contains("secret"));
+    }
+}
@@ -0,0 +1,98 @@
+//! Detection of contradictory EIP-7702 authorizations within a single transaction.
+//!
+//! Authorizations are applied in order, so if an authority delegates to several targets in the
+//! same authorization list, only its last delegation is effective, and the earlier ones only waste
+//! gas.
+
+use crate::OdysseyWalletError;
+use alloy_eips::eip7702::SignedAuthorization;
+use alloy_primitives::Address;
+use std::collections::HashMap;
+use tracing::warn;
+
+/// How authorizations of the same authority to different targets are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContradictoryAuthorizations {
+    /// The request is rejected with [`OdysseyWalletError::ContradictoryAuthorizations`].
+    Reject,
+    /// The earlier authorizations of the authority are removed, and a warning is logged.
+    ///
+    /// The remaining authorization must be valid on its own, e.g. its nonce must be the current
+    /// nonce of the authority, or it is skipped on execution.
+    Prune,
+}
+
+/// Handles authorizations of the same authority to different targets according to `handling`.
+pub(crate) fn resolve_contradictory_authorizations(
+    authorizations: &mut Vec<SignedAuthorization>,
+    handling: ContradictoryAuthorizations,
+) -> Result<(), OdysseyWalletError> {
+    // authorizations with an invalid signature are skipped on execution
+    let delegations: Vec<_> = authorizations
+        .iter()
+        .map(|authorization| (authorization.recover_authority().ok(), authorization.address))
+        .collect();
+    let superseded = superseded(&delegations);
+    let Some(authority) = superseded.first().and_then(|index| delegations[*index].0) else {
+        return Ok(());
+    };
+
+    match handling {
+        ContradictoryAuthorizations::Reject => {
+            Err(OdysseyWalletError::ContradictoryAuthorizations { authority })
+        }
+        ContradictoryAuthorizations::Prune => {
+            warn!(target: "rpc::wallet", ?superseded, "Pruning contradictory authorizations");
+            *authorizations = std::mem::take(authorizations)
+                .into_iter()
+                .enumerate()
+                .filter(|(index, _)| !superseded.contains(index))
+                .map(|(_, authorization)| authorization)
+                .collect();
+            Ok(())
+        }
+    }
+}
+
+/// Returns the indices of the delegations, given as pairs of authority and target, that are
+/// superseded by a later delegation of the same authority to another target.
+///
+/// Delegations without an authority are never superseded.
+fn superseded(delegations: &[(Option<Address>, Address)]) -> Vec<usize> {
+    // the effective target of each authority is the target of its last delegation
+    let effective: HashMap<Address, Address> = delegations
+        .iter()
+        .filter_map(|(authority, target)| Some(((*authority)?, *target)))
+        .collect();
+    delegations
+        .iter()
+        .enumerate()
+        .filter(|(_, (authority, target))| {
+            authority.is_some_and(|authority| effective[&authority] != *target)
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_contradictory_authorizations() {
+        let (alice, bob) = (Some(Address::with_last_byte(0xa)), Some(Address::with_last_byte(0xb)));
+        let (first, second) = (Address::with_last_byte(1), Address::with_last_byte(2));
+
+        // alice delegates to the first target, then to the second
+        assert_eq!(superseded(&[(alice, first), (bob, first), (alice, second)]), vec![0]);
+        assert_eq!(
+            superseded(&[(alice, first), (alice, second), (alice, first), (bob, second)]),
+            vec![1]
+        );
+
+        // repeated delegations to the same target are not contradictory
+        assert!(superseded(&[(alice, first), (bob, second), (alice, first)]).is_empty());
+        // nor are delegations without an authority
+        assert!(superseded(&[(None, first), (None, second)]).is_empty());
+    }
+}
@@ -0,0 +1,186 @@
+//! Monitoring of the sponsor's balance.
+
+use crate::alert::{BudgetAlertSource, BudgetAlerts};
+use alloy_primitives::{Address, U256};
+use metrics::Counter;
+use metrics_derive::Metrics;
+use reth_storage_api::{StateProvider, StateProviderFactory};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// The severity of a [`BalanceThreshold`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BalanceAlertLevel {
+    /// The balance is getting low, and should be topped up soon.
+    Warn,
+    /// The balance is critically low, and sponsorship is about to stop.
+    Critical,
+}
+
+/// A balance below which an alert is emitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BalanceThreshold {
+    /// The balance in wei.
+    pub balance: U256,
+    /// The severity of the alert emitted once the balance drops below the threshold.
+    pub level: BalanceAlertLevel,
+}
+
+impl BalanceThreshold {
+    /// Creates a new [`BalanceAlertLevel::Warn`] threshold.
+    pub const fn warn(balance: U256) -> Self {
+        Self { balance, level: BalanceAlertLevel::Warn }
+    }
+
+    /// Creates a new [`BalanceAlertLevel::Critical`] threshold.
+    pub const fn critical(balance: U256) -> Self {
+        Self { balance, level: BalanceAlertLevel::Critical }
+    }
+}
+
+/// Settings for the [`balance_monitor`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceMonitorConfig {
+    /// The thresholds to alert on.
+    pub thresholds: Vec<BalanceThreshold>,
+    /// How often the balance is checked.
+    pub interval: Duration,
+}
+
+impl Default for BalanceMonitorConfig {
+    fn default() -> Self {
+        const ETHER: u128 = 1_000_000_000_000_000_000;
+        Self {
+            thresholds: vec![
+                BalanceThreshold::warn(U256::from(ETHER)),
+                BalanceThreshold::critical(U256::from(ETHER / 10)),
+            ],
+            interval: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Periodically checks the balance of the sponsor, and alerts if it drops below any of the
+/// configured thresholds.
+///
+/// An alert is emitted once per crossing: a `warn` or `error` event is logged and the threshold's
+/// counter is incremented. Recovery above a threshold is logged as well. Both are also pushed to
+/// the subscribers of the `alerts`, if set.
+///
+/// The monitor runs until the future is dropped, so it should be spawned on an executor that
+/// cancels it on shutdown.
+pub async fn balance_monitor<P>(
+    provider: P,
+    address: Address,
+    config: BalanceMonitorConfig,
+    alerts: Option<BudgetAlerts>,
+) where
+    P: StateProviderFactory,
+{
+    let metrics = BalanceMonitorMetrics::default();
+    let mut interval_timer = tokio::time::interval(config.interval);
+    let mut previous = None;
+
+    loop {
+        interval_timer.tick().await;
+
+        let balance = match provider.latest().and_then(|state| state.account_balance(address)) {
+            Ok(balance) => balance.unwrap_or_default(),
+            Err(err) => {
+                warn!(target: "rpc::wallet", ?err, "Error fetching sponsor balance");
+                continue;
+            }
+        };
+
+        for threshold in &config.thresholds {
+            let crossing = crossing(previous, balance, threshold.balance);
+            if let Some(alerts) = &alerts {
+                alerts.alert_on_crossing(
+                    BudgetAlertSource::SignerBalance,
+                    threshold,
+                    crossing,
+                    balance,
+                );
+            }
+            match crossing {
+                Some(Crossing::Below) => match threshold.level {
+                    BalanceAlertLevel::Warn => {
+                        metrics.warn_threshold_crossed.increment(1);
+                        warn!(target: "rpc::wallet", %address, %balance, threshold = %threshold.balance, "Sponsor balance is low");
+                    }
+                    BalanceAlertLevel::Critical => {
+                        metrics.critical_threshold_crossed.increment(1);
+                        error!(target: "rpc::wallet", %address, %balance, threshold = %threshold.balance, "Sponsor balance is critically low");
+                    }
+                },
+                Some(Crossing::Above) => {
+                    info!(target: "rpc::wallet", %address, %balance, threshold = %threshold.balance, "Sponsor balance recovered");
+                }
+                None => (),
+            }
+        }
+
+        previous = Some(balance);
+    }
+}
+
+/// The direction in which a balance crossed a threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Crossing {
+    /// The balance dropped below the threshold.
+    Below,
+    /// The balance recovered to or above the threshold.
+    Above,
+}
+
+/// Determines whether the balance crossed the threshold since the previous check.
+///
+/// If there is no previous check, a balance below the threshold counts as a crossing.
+pub(crate) fn crossing(previous: Option<U256>, current: U256, threshold: U256) -> Option<Crossing> {
+    let was_below = previous.is_some_and(|previous| previous < threshold);
+    match (was_below, current < threshold) {
+        (false, true) => Some(Crossing::Below),
+        (true, false) => Some(Crossing::Above),
+        _ => None,
+    }
+}
+
+/// Metrics for the sponsor balance monitor.
+#[derive(Metrics)]
+#[metrics(scope = "wallet.balance")]
+struct BalanceMonitorMetrics {
+    /// Number of times the balance dropped below a warning threshold
+    warn_threshold_crossed: Counter,
+    /// Number of times the balance dropped below a critical threshold
+    critical_threshold_crossed: Counter,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_crossings() {
+        let threshold = U256::from(100);
+
+        // first check
+        assert_eq!(crossing(None, U256::from(99), threshold), Some(Crossing::Below));
+        assert_eq!(crossing(None, U256::from(100), threshold), None);
+
+        // stays above or below
+        assert_eq!(crossing(Some(U256::from(200)), U256::from(150), threshold), None);
+        assert_eq!(crossing(Some(U256::from(50)), U256::from(40), threshold), None);
+
+        // crosses
+        assert_eq!(
+            crossing(Some(U256::from(100)), U256::from(99), threshold),
+            Some(Crossing::Below)
+        );
+        assert_eq!(
+            crossing(Some(U256::from(99)), U256::from(100), threshold),
+            Some(Crossing::Above)
+        );
+    }
+}
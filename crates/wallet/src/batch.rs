@@ -0,0 +1,139 @@
+//! Batched submission of sponsored transactions.
+
+use crate::OdysseyWalletError;
+use alloy_primitives::{Bytes, TxHash};
+use jsonrpsee::core::RpcResult;
+use std::{future::Future, time::Duration};
+use tokio::{
+    sync::{
+        mpsc::{self, error::TrySendError},
+        oneshot,
+    },
+    time::Instant,
+};
+
+/// The maximum number of transactions waiting to be submitted.
+///
+/// Once reached, e.g. because submissions stall, further transactions are rejected.
+const MAX_QUEUED_SUBMISSIONS: usize = 1_024;
+
+/// A signed transaction waiting to be submitted.
+#[derive(Debug)]
+struct PendingSubmission {
+    /// The nonce of the transaction.
+    nonce: u64,
+    /// The EIP-2718 encoded transaction.
+    tx: Bytes,
+    /// Where to send the result of the submission.
+    result: oneshot::Sender<RpcResult<TxHash>>,
+}
+
+/// Collects signed transactions over a short window and submits them as a group.
+///
+/// The transactions of a batch are submitted in nonce order.
+#[derive(Debug)]
+pub(crate) struct SubmissionBatcher {
+    to_batcher: mpsc::Sender<PendingSubmission>,
+}
+
+impl SubmissionBatcher {
+    /// Spawns the batching task, which submits each transaction using `submit`.
+    pub(crate) fn spawn<F, Fut>(window: Duration, submit: F) -> Self
+    where
+        F: Fn(Bytes) -> Fut + Send + 'static,
+        Fut: Future<Output = RpcResult<TxHash>> + Send + 'static,
+    {
+        let (to_batcher, from_wallet) = mpsc::channel(MAX_QUEUED_SUBMISSIONS);
+        tokio::task::spawn(run(window, from_wallet, submit));
+        Self { to_batcher }
+    }
+
+    /// Enqueues a transaction with the given nonce for submission.
+    ///
+    /// The returned receiver resolves to the result of the submission once the batch containing
+    /// the transaction has been flushed. Fails if too many transactions are queued.
+    pub(crate) fn enqueue(
+        &self,
+        nonce: u64,
+        tx: Bytes,
+    ) -> Result<oneshot::Receiver<RpcResult<TxHash>>, OdysseyWalletError> {
+        let (result, rx) = oneshot::channel();
+        match self.to_batcher.try_send(PendingSubmission { nonce, tx, result }) {
+            Ok(()) => Ok(rx),
+            Err(TrySendError::Full(_)) => Err(OdysseyWalletError::Overloaded),
+            Err(TrySendError::Closed(_)) => Err(OdysseyWalletError::InternalError),
+        }
+    }
+}
+
+/// Runs the batching loop until all senders are dropped.
+async fn run<F, Fut>(
+    window: Duration,
+    mut from_wallet: mpsc::Receiver<PendingSubmission>,
+    submit: F,
+) where
+    F: Fn(Bytes) -> Fut,
+    Fut: Future<Output = RpcResult<TxHash>>,
+{
+    while let Some(first) = from_wallet.recv().await {
+        // the window starts with the first transaction of the batch
+        let deadline = Instant::now() + window;
+        let mut batch = vec![first];
+        while let Ok(Some(next)) = tokio::time::timeout_at(deadline, from_wallet.recv()).await {
+            batch.push(next);
+        }
+
+        // a transaction is only accepted by the pool once the gap to the signer's nonce is filled
+        batch.sort_by_key(|submission| submission.nonce);
+        for PendingSubmission { tx, result, .. } in batch {
+            let _ = result.send(submit(tx).await);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::keccak256;
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn submits_in_order() {
+        let submitted = Arc::new(Mutex::new(Vec::new()));
+        let batcher = SubmissionBatcher::spawn(Duration::from_millis(50), {
+            let submitted = submitted.clone();
+            move |tx: Bytes| {
+                submitted.lock().unwrap().push(tx.clone());
+                async move { Ok(keccak256(&tx)) }
+            }
+        });
+
+        let txs: Vec<Bytes> = (0u8..5).map(|nonce| vec![nonce].into()).collect();
+        // enqueued out of nonce order
+        let results: Vec<_> = [1, 0, 4, 2, 3]
+            .into_iter()
+            .map(|nonce| (nonce, batcher.enqueue(nonce as u64, txs[nonce].clone()).unwrap()))
+            .collect();
+
+        // nothing is submitted until the window elapsed
+        assert!(submitted.lock().unwrap().is_empty());
+
+        for (nonce, result) in results {
+            assert_eq!(result.await.unwrap().unwrap(), keccak256(&txs[nonce]));
+        }
+        assert_eq!(*submitted.lock().unwrap(), txs);
+    }
+
+    #[tokio::test]
+    async fn rejects_once_queue_is_full() {
+        // the submission never completes, so the queue is not drained
+        let batcher = SubmissionBatcher::spawn(Duration::ZERO, |_| std::future::pending());
+        let _stalled = batcher.enqueue(0, Bytes::new()).unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        for nonce in 1..=MAX_QUEUED_SUBMISSIONS as u64 {
+            batcher.enqueue(nonce, Bytes::new()).unwrap();
+        }
+        assert_eq!(batcher.enqueue(0, Bytes::new()).err(), Some(OdysseyWalletError::Overloaded));
+    }
+}
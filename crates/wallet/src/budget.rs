@@ -0,0 +1,274 @@
+//! Budgeting of the funds spent on sponsorship.
+
+use alloy_primitives::U256;
+use parking_lot::Mutex;
+use std::time::{Duration, Instant};
+
+/// Settings for the [`SponsorshipBudget`].
+///
+/// The budget is a token bucket: it starts full, each sponsored transaction consumes its maximum
+/// cost, i.e. its gas limit at its max fee per gas plus its value, and it continuously refills at a
+/// fixed rate up to its capacity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SponsorshipBudgetConfig {
+    /// The maximum budget in wei.
+    pub capacity: U256,
+    /// The amount of wei the budget refills by per second.
+    pub refill_per_second: U256,
+}
+
+/// The state of the budget.
+#[derive(Debug)]
+struct BudgetState {
+    /// The remaining budget in wei.
+    level: U256,
+    /// When the budget was last refilled.
+    last_refill: Instant,
+}
+
+/// A continuously refilling budget for sponsored transactions.
+#[derive(Debug)]
+pub(crate) struct SponsorshipBudget {
+    config: SponsorshipBudgetConfig,
+    state: Mutex<BudgetState>,
+}
+
+impl SponsorshipBudget {
+    /// Creates a new, full budget.
+    pub(crate) const fn new(config: SponsorshipBudgetConfig, now: Instant) -> Self {
+        Self { config, state: Mutex::new(BudgetState { level: config.capacity, last_refill: now }) }
+    }
+
+    /// Consumes `cost` from the budget, returning `false` if the budget does not cover it.
+    pub(crate) fn try_consume(&self, cost: U256, now: Instant) -> bool {
+        let mut state = self.state.lock();
+        self.refill(&mut state, now);
+        if state.level < cost {
+            return false;
+        }
+        state.level -= cost;
+        true
+    }
+
+    /// Returns `cost` to the budget, e.g. if the transaction was not sponsored after all.
+    pub(crate) fn refund(&self, cost: U256, now: Instant) {
+        let mut state = self.state.lock();
+        self.refill(&mut state, now);
+        state.level = state.level.saturating_add(cost).min(self.config.capacity);
+    }
+
+    /// Returns the remaining budget.
+    pub(crate) fn level(&self, now: Instant) -> U256 {
+        let mut state = self.state.lock();
+        self.refill(&mut state, now);
+        state.level
+    }
+
+    /// Restores the budget to `level` as of `at`, e.g. from a snapshot taken before a restart.
+    pub(crate) fn restore(&self, level: U256, at: Instant) {
+        let mut state = self.state.lock();
+        state.level = level.min(self.config.capacity);
+        state.last_refill = at;
+    }
+
+    /// Refills the budget for the time elapsed since the last refill.
+    fn refill(&self, state: &mut BudgetState, now: Instant) {
+        let elapsed = now.saturating_duration_since(state.last_refill);
+        let refill = self.config.refill_per_second.saturating_mul(U256::from(elapsed.as_nanos()))
+            / U256::from(1_000_000_000u64);
+        state.level = state.level.saturating_add(refill).min(self.config.capacity);
+        state.last_refill = now.max(state.last_refill);
+    }
+}
+
+/// Settings for the [`SpendingCap`].
+///
+/// Unlike the [`SponsorshipBudgetConfig`], the cap does not refill gradually: once the spending
+/// within the current window reaches the limit, all sponsorship stops until the window ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpendingCapConfig {
+    /// The maximum amount of wei spent within a window.
+    pub limit: U256,
+    /// The length of a window, e.g. 24 hours.
+    pub window: Duration,
+}
+
+/// The configured spending cap, and the amount left within the current window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpendingCapStatus {
+    /// The maximum amount of wei spent within a window.
+    pub limit: U256,
+    /// The amount of wei left within the current window.
+    pub remaining: U256,
+}
+
+/// The state of the spending cap.
+#[derive(Debug)]
+struct SpendingState {
+    /// The amount of wei spent within the current window.
+    spent: U256,
+    /// When the current window started.
+    window_start: Instant,
+}
+
+/// A hard cap on the funds spent on sponsorship within fixed windows.
+#[derive(Debug)]
+pub(crate) struct SpendingCap {
+    config: SpendingCapConfig,
+    state: Mutex<SpendingState>,
+}
+
+impl SpendingCap {
+    /// Creates a new cap, whose first window starts at `now`.
+    pub(crate) const fn new(config: SpendingCapConfig, now: Instant) -> Self {
+        Self { config, state: Mutex::new(SpendingState { spent: U256::ZERO, window_start: now }) }
+    }
+
+    /// Spends `cost`, returning `false` if it exceeds the amount left within the current window.
+    pub(crate) fn try_spend(&self, cost: U256, now: Instant) -> bool {
+        let mut state = self.state.lock();
+        self.roll_over(&mut state, now);
+        let spent = state.spent.saturating_add(cost);
+        if spent > self.config.limit {
+            return false;
+        }
+        state.spent = spent;
+        true
+    }
+
+    /// Returns `cost` spent within the current window, e.g. if the transaction was not
+    /// sponsored after all.
+    pub(crate) fn refund(&self, cost: U256, now: Instant) {
+        let mut state = self.state.lock();
+        self.roll_over(&mut state, now);
+        state.spent = state.spent.saturating_sub(cost);
+    }
+
+    /// Returns the amount spent within the current window, and when the window started.
+    pub(crate) fn spending(&self, now: Instant) -> (U256, Instant) {
+        let mut state = self.state.lock();
+        self.roll_over(&mut state, now);
+        (state.spent, state.window_start)
+    }
+
+    /// Restores the amount spent within the window that started at `window_start`, e.g. from a
+    /// snapshot taken before a restart.
+    pub(crate) fn restore(&self, spent: U256, window_start: Instant) {
+        let mut state = self.state.lock();
+        state.spent = spent.min(self.config.limit);
+        state.window_start = window_start;
+    }
+
+    /// Returns the configured limit and the amount left within the current window.
+    pub(crate) fn status(&self, now: Instant) -> SpendingCapStatus {
+        let mut state = self.state.lock();
+        self.roll_over(&mut state, now);
+        SpendingCapStatus { limit: self.config.limit, remaining: self.config.limit - state.spent }
+    }
+
+    /// Starts a new window if the current one has ended.
+    fn roll_over(&self, state: &mut SpendingState, now: Instant) {
+        let elapsed = now.saturating_duration_since(state.window_start);
+        if elapsed < self.config.window {
+            return;
+        }
+        // windows are aligned to the start of the first window, an empty window always ends
+        let windows = elapsed.as_nanos().checked_div(self.config.window.as_nanos()).unwrap_or(0);
+        state.window_start += Duration::from_nanos(
+            (windows * self.config.window.as_nanos()).try_into().unwrap_or(u64::MAX),
+        );
+        state.spent = U256::ZERO;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn consumes_and_refills() {
+        let now = Instant::now();
+        let budget = SponsorshipBudget::new(
+            SponsorshipBudgetConfig {
+                capacity: U256::from(1_000),
+                refill_per_second: U256::from(100),
+            },
+            now,
+        );
+
+        assert!(budget.try_consume(U256::from(600), now));
+        assert!(budget.try_consume(U256::from(400), now));
+        assert!(!budget.try_consume(U256::from(1), now));
+        assert_eq!(budget.level(now), U256::ZERO);
+
+        // refills continuously
+        let now = now + Duration::from_millis(2_500);
+        assert_eq!(budget.level(now), U256::from(250));
+        assert!(!budget.try_consume(U256::from(300), now));
+        assert!(budget.try_consume(U256::from(200), now));
+        assert_eq!(budget.level(now), U256::from(50));
+
+        // up to the capacity
+        let now = now + Duration::from_secs(60);
+        assert_eq!(budget.level(now), U256::from(1_000));
+
+        // refunds are capped as well
+        assert!(budget.try_consume(U256::from(300), now));
+        budget.refund(U256::from(200), now);
+        assert_eq!(budget.level(now), U256::from(900));
+        budget.refund(U256::from(200), now);
+        assert_eq!(budget.level(now), U256::from(1_000));
+    }
+
+    #[test]
+    fn stops_spending_until_window_ends() {
+        let start = Instant::now();
+        let cap = SpendingCap::new(
+            SpendingCapConfig { limit: U256::from(1_000), window: Duration::from_secs(100) },
+            start,
+        );
+
+        assert!(cap.try_spend(U256::from(600), start));
+        assert!(!cap.try_spend(U256::from(500), start));
+        assert!(cap.try_spend(U256::from(400), start));
+        assert_eq!(
+            cap.status(start),
+            SpendingCapStatus { limit: U256::from(1_000), remaining: U256::ZERO }
+        );
+
+        // does not refill within the window
+        let now = start + Duration::from_secs(99);
+        assert!(!cap.try_spend(U256::from(1), now));
+        cap.refund(U256::from(100), now);
+        assert_eq!(cap.status(now).remaining, U256::from(100));
+
+        // resets once the window ends
+        let now = start + Duration::from_secs(250);
+        assert_eq!(cap.status(now).remaining, U256::from(1_000));
+        assert!(cap.try_spend(U256::from(1_000), now));
+        // the next window starts where the previous one ended, not when it was first used
+        assert_eq!(cap.spending(now), (U256::from(1_000), start + Duration::from_secs(200)));
+        assert_eq!(cap.status(start + Duration::from_secs(300)).remaining, U256::from(1_000));
+
+        // the spending is kept after a restore
+        let restored = SpendingCap::new(cap.config, now);
+        restored.restore(U256::from(700), start + Duration::from_secs(200));
+        assert_eq!(restored.status(now).remaining, U256::from(300));
+        assert_eq!(restored.status(start + Duration::from_secs(300)).remaining, U256::from(1_000));
+    }
+
+    #[test]
+    fn empty_window_does_not_panic() {
+        let start = Instant::now();
+        let cap = SpendingCap::new(
+            SpendingCapConfig { limit: U256::from(1_000), window: Duration::ZERO },
+            start,
+        );
+
+        // every window has ended, so only the cost of a single transaction is capped
+        assert!(cap.try_spend(U256::from(1_000), start));
+        assert!(cap.try_spend(U256::from(1_000), start + Duration::from_secs(1)));
+        assert!(!cap.try_spend(U256::from(1_001), start));
+    }
+}
@@ -0,0 +1,271 @@
+//! Bundles of sponsored transactions submitted by `wallet_sendCalls`.
+//!
+//! See also [EIP-5792][eip-5792].
+//!
+//! [eip-5792]: https://eips.ethereum.org/EIPS/eip-5792
+
+use crate::{Call, OdysseyWalletError};
+use alloy_network::ReceiptResponse;
+use alloy_primitives::{keccak256, Address, BlockHash, BlockNumber, ChainId, TxHash, B256, U64};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// The maximum number of bundles whose transactions are tracked, the oldest are forgotten first.
+pub const MAX_TRACKED_BUNDLES: usize = 10_000;
+
+/// The parameters of `wallet_sendCalls`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendCallsParams {
+    /// The version of the request format.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The chain the calls are sent on.
+    pub chain_id: U64,
+    /// The delegated account the calls are made against. If set, every call must be sent to it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<Address>,
+    /// The calls, in the order they are submitted.
+    pub calls: Vec<Call>,
+    /// The attestation token of the client, checked for each call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attestation: Option<String>,
+}
+
+impl SendCallsParams {
+    /// Validates the parameters against the chain of the wallet.
+    pub(crate) fn validate(&self, chain_id: ChainId) -> Result<(), OdysseyWalletError> {
+        if self.chain_id.to::<ChainId>() != chain_id {
+            return Err(OdysseyWalletError::ChainIdMismatch { chain_id: self.chain_id.to() });
+        }
+        if self.calls.is_empty() {
+            return Err(OdysseyWalletError::InvalidTransactionRequest);
+        }
+        if self.from.is_some_and(|from| self.calls.iter().any(|call| call.to != from)) {
+            return Err(OdysseyWalletError::IllegalDestination);
+        }
+        Ok(())
+    }
+}
+
+/// The status of a bundle or transaction, as reported by `wallet_getCallsStatus`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallsStatus {
+    /// The status of the bundle as a whole.
+    pub status: CallStatus,
+    /// The receipts of the included transactions, in the order of the bundle.
+    pub receipts: Vec<CallReceipt>,
+}
+
+impl CallsStatus {
+    /// Returns the status of a bundle, given the state of each of its transactions.
+    ///
+    /// The bundle is pending as long as any transaction is pending. Once none is pending, it
+    /// failed if any transaction reverted or was dropped, and is included otherwise.
+    pub(crate) fn of(transactions: Vec<TransactionState>) -> Self {
+        let mut status = CallStatus::Included;
+        let mut receipts = Vec::with_capacity(transactions.len());
+        for transaction in transactions {
+            match transaction {
+                TransactionState::Included(receipt) => {
+                    if !receipt.success && status == CallStatus::Included {
+                        status = CallStatus::Failed;
+                    }
+                    receipts.push(receipt);
+                }
+                TransactionState::Pending => status = CallStatus::Pending,
+                TransactionState::Unknown if status == CallStatus::Included => {
+                    status = CallStatus::Failed;
+                }
+                TransactionState::Unknown => {}
+            }
+        }
+        Self { status, receipts }
+    }
+}
+
+/// The status of a bundle or transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CallStatus {
+    /// Not all transactions are included yet.
+    Pending,
+    /// All transactions are included, and none reverted.
+    Included,
+    /// A transaction reverted, or was dropped before it was included.
+    Failed,
+}
+
+/// The receipt of an included transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallReceipt {
+    /// The hash of the transaction.
+    pub transaction_hash: TxHash,
+    /// The hash of the block that included the transaction.
+    pub block_hash: Option<BlockHash>,
+    /// The number of the block that included the transaction.
+    pub block_number: Option<BlockNumber>,
+    /// The gas used by the transaction.
+    pub gas_used: u64,
+    /// Whether the transaction succeeded, i.e. did not revert.
+    pub success: bool,
+}
+
+impl CallReceipt {
+    /// Creates a new receipt from an `eth_` API receipt.
+    pub(crate) fn new<R: ReceiptResponse>(receipt: &R) -> Self {
+        Self {
+            transaction_hash: receipt.transaction_hash(),
+            block_hash: receipt.block_hash(),
+            block_number: receipt.block_number(),
+            gas_used: u64::try_from(receipt.gas_used()).unwrap_or(u64::MAX),
+            success: receipt.status(),
+        }
+    }
+}
+
+/// The state of a transaction of a bundle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TransactionState {
+    /// The transaction is included.
+    Included(CallReceipt),
+    /// The transaction is known, but not included yet.
+    Pending,
+    /// The transaction is not known, e.g. because it was dropped from the pool.
+    Unknown,
+}
+
+/// The transactions of the most recently submitted bundles.
+#[derive(Debug, Default)]
+pub(crate) struct Bundles {
+    inner: Mutex<TrackedBundles>,
+}
+
+#[derive(Debug, Default)]
+struct TrackedBundles {
+    transactions: HashMap<B256, Vec<TxHash>>,
+    /// The ids of the tracked bundles, oldest first.
+    order: VecDeque<B256>,
+}
+
+impl Bundles {
+    /// Tracks a bundle of transactions, returning its id.
+    ///
+    /// The id is the hash of the concatenated transaction hashes, so it is unique as long as the
+    /// transactions are.
+    pub(crate) fn insert(&self, transactions: Vec<TxHash>) -> B256 {
+        let id = keccak256(transactions.concat());
+        let mut inner = self.inner.lock();
+        if inner.transactions.insert(id, transactions).is_none() {
+            inner.order.push_back(id);
+        }
+        while inner.order.len() > MAX_TRACKED_BUNDLES {
+            if let Some(oldest) = inner.order.pop_front() {
+                inner.transactions.remove(&oldest);
+            }
+        }
+        id
+    }
+
+    /// Returns the transactions of the bundle, if it is still tracked.
+    pub(crate) fn get(&self, id: &B256) -> Option<Vec<TxHash>> {
+        self.inner.lock().transactions.get(id).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Bytes, U256};
+
+    #[test]
+    fn validates_params() {
+        let account = Address::with_last_byte(1);
+        let call = |to| Call { to, data: Bytes::new(), value: None };
+        let params = SendCallsParams {
+            version: None,
+            chain_id: U64::from(911867),
+            from: Some(account),
+            calls: vec![call(account), call(account)],
+            attestation: None,
+        };
+        assert_eq!(params.validate(911867), Ok(()));
+        assert_eq!(
+            params.validate(1),
+            Err(OdysseyWalletError::ChainIdMismatch { chain_id: 911867 })
+        );
+        assert_eq!(
+            SendCallsParams { calls: vec![], ..params.clone() }.validate(911867),
+            Err(OdysseyWalletError::InvalidTransactionRequest)
+        );
+
+        // all calls must be sent to the account, if set
+        let mixed = SendCallsParams {
+            calls: vec![call(account), call(Address::with_last_byte(2))],
+            ..params
+        };
+        assert_eq!(mixed.validate(911867), Err(OdysseyWalletError::IllegalDestination));
+        assert_eq!(SendCallsParams { from: None, ..mixed }.validate(911867), Ok(()));
+
+        let parsed: SendCallsParams = serde_json::from_str(
+            r#"{
+                "version": "1.0",
+                "chainId": "0xde9fb",
+                "from": "0x0000000000000000000000000000000000000001",
+                "calls": [{"to": "0x0000000000000000000000000000000000000001", "value": "0x1"}]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.calls[0].value, Some(U256::from(1)));
+        assert_eq!(parsed.validate(911867), Ok(()));
+    }
+
+    #[test]
+    fn aggregates_status() {
+        let receipt = |last_byte, success| {
+            TransactionState::Included(CallReceipt {
+                transaction_hash: B256::with_last_byte(last_byte),
+                block_hash: Some(B256::with_last_byte(0xbb)),
+                block_number: Some(1),
+                gas_used: 21_000,
+                success,
+            })
+        };
+        let status = |transactions: Vec<TransactionState>| CallsStatus::of(transactions).status;
+
+        assert_eq!(status(vec![receipt(1, true), receipt(2, true)]), CallStatus::Included);
+        assert_eq!(status(vec![receipt(1, true), TransactionState::Pending]), CallStatus::Pending);
+        assert_eq!(status(vec![receipt(1, false), receipt(2, true)]), CallStatus::Failed);
+        assert_eq!(status(vec![receipt(1, true), TransactionState::Unknown]), CallStatus::Failed);
+        // pending takes precedence, as the outcome of the bundle is not known yet
+        assert_eq!(status(vec![receipt(1, false), TransactionState::Pending]), CallStatus::Pending);
+        assert_eq!(
+            status(vec![TransactionState::Unknown, TransactionState::Pending]),
+            CallStatus::Pending
+        );
+
+        // only included transactions have receipts
+        let bundle = CallsStatus::of(vec![receipt(1, true), TransactionState::Pending]);
+        assert_eq!(bundle.receipts.len(), 1);
+        assert_eq!(bundle.receipts[0].transaction_hash, B256::with_last_byte(1));
+    }
+
+    #[test]
+    fn tracks_recent_bundles() {
+        let bundles = Bundles::default();
+        let hashes = vec![B256::with_last_byte(1), B256::with_last_byte(2)];
+        let id = bundles.insert(hashes.clone());
+        assert_eq!(bundles.get(&id), Some(hashes.clone()));
+        assert_eq!(bundles.insert(hashes), id);
+        assert_ne!(bundles.insert(vec![B256::with_last_byte(1)]), id);
+
+        // the oldest bundles are forgotten first
+        for i in 0..MAX_TRACKED_BUNDLES as u64 - 1 {
+            bundles.insert(vec![B256::from(U256::from(i + 3))]);
+        }
+        assert_eq!(bundles.get(&id), None);
+        assert!(bundles.get(&keccak256(B256::with_last_byte(1))).is_some());
+    }
+}
@@ -0,0 +1,589 @@
+//! The catalog of errors returned by the `wallet_` namespace.
+
+use crate::OdysseyWalletError;
+use alloy_primitives::{Address, Bytes, B256, U256};
+use jsonrpsee::types::error::{INTERNAL_ERROR_CODE, METHOD_NOT_FOUND_CODE};
+use serde::{Deserialize, Serialize};
+
+/// An error that may be returned by the `wallet_` namespace, as listed by `wallet_errorCatalog`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorCatalogEntry {
+    /// The name of the error.
+    pub name: String,
+    /// The JSON-RPC error code.
+    pub code: i32,
+    /// The template of the error message, with the fields in braces, e.g. `{target}`.
+    pub message: String,
+    /// The fields interpolated into the message.
+    pub fields: Vec<ErrorField>,
+}
+
+/// A field carried by an [`ErrorCatalogEntry`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorField {
+    /// The name of the field.
+    pub name: String,
+    /// The type of the field, e.g. `address` or `uint64`.
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+/// One instance of every [`OdysseyWalletError`] variant.
+const VARIANTS: [OdysseyWalletError; 59] = [
+    OdysseyWalletError::ValueNotZero,
+    OdysseyWalletError::FromSet,
+    OdysseyWalletError::NonceSet,
+    OdysseyWalletError::UnsupportedTransactionType { tx_type: 0 },
+    OdysseyWalletError::IllegalDestination,
+    OdysseyWalletError::SystemAddressNotAllowed { address: Address::ZERO },
+    OdysseyWalletError::AddressBlocked { address: Address::ZERO },
+    OdysseyWalletError::InvalidAuthorizationSignature,
+    OdysseyWalletError::AuthorizationNonceOutOfRange {
+        authority: Address::ZERO,
+        nonce: 0,
+        current: 0,
+    },
+    OdysseyWalletError::DestinationFlagged { target: Address::ZERO },
+    OdysseyWalletError::DestinationDestroyed { target: Address::ZERO },
+    OdysseyWalletError::DelegationTargetTooNew { target: Address::ZERO },
+    OdysseyWalletError::CohortNotEligible { address: Address::ZERO },
+    OdysseyWalletError::InconsistentBlobFields,
+    OdysseyWalletError::BlobsNotSponsored,
+    OdysseyWalletError::DestinationUnverified { target: Address::ZERO },
+    OdysseyWalletError::ProxyImplementationNotWhitelisted {
+        proxy: Address::ZERO,
+        implementation: Address::ZERO,
+    },
+    OdysseyWalletError::DelegatedStateConflict { account: Address::ZERO },
+    OdysseyWalletError::ConflictingPendingDelegation {
+        authority: Address::ZERO,
+        pending: Address::ZERO,
+    },
+    OdysseyWalletError::NodeNotSynced,
+    OdysseyWalletError::SponsorshipNotStarted { current: 0, start: 0 },
+    OdysseyWalletError::MaintenanceWindow { ends_at: 0 },
+    OdysseyWalletError::BatchCalldataTooLarge { size: 0, max: 0 },
+    OdysseyWalletError::CalldataTooLarge { size: 0, max: 0 },
+    OdysseyWalletError::SelectorNotAllowed { selector: Bytes::new() },
+    OdysseyWalletError::AttestationRequired,
+    OdysseyWalletError::Eip7702NotSupported,
+    OdysseyWalletError::EmptyAuthorizationList,
+    OdysseyWalletError::AuthorizationChainIdMismatch { chain_id: 0 },
+    OdysseyWalletError::UniversalAuthorizationNotAllowed,
+    OdysseyWalletError::ContradictoryAuthorizations { authority: Address::ZERO },
+    OdysseyWalletError::ChainIdMismatch { chain_id: 0 },
+    OdysseyWalletError::DestinationQuotaExceeded { target: Address::ZERO },
+    OdysseyWalletError::BudgetExhausted,
+    OdysseyWalletError::SponsorInsufficientFunds { balance: U256::ZERO, minimum: U256::ZERO },
+    OdysseyWalletError::SuspiciousActivity,
+    OdysseyWalletError::TransactionTooLarge { size: 0, max: 0 },
+    OdysseyWalletError::AuthorizationListTooLarge { size: 0, max: 0 },
+    OdysseyWalletError::InvalidTransactionRequest,
+    OdysseyWalletError::MissingTransactionFields { tx_type: String::new(), fields: Vec::new() },
+    OdysseyWalletError::SigningFailed { reason: String::new() },
+    OdysseyWalletError::InvalidCorrelationId,
+    OdysseyWalletError::UnknownBundle { id: B256::ZERO },
+    OdysseyWalletError::BundlePartiallySubmitted {
+        id: B256::ZERO,
+        hashes: Vec::new(),
+        reason: String::new(),
+    },
+    OdysseyWalletError::InvalidAuditCursor,
+    OdysseyWalletError::Unauthorized,
+    OdysseyWalletError::DestinationRequiresValue,
+    OdysseyWalletError::GasEstimateTooHigh { estimate: 0, limit: 0 },
+    OdysseyWalletError::AnomalousGasProfile { gas_per_byte: 0 },
+    OdysseyWalletError::ExcessiveStateGrowth { slots: 0, max: 0 },
+    OdysseyWalletError::GasLimitAboveEstimate { gas_limit: 0, estimate: 0 },
+    OdysseyWalletError::FeeBelowMinimum { max_fee_per_gas: 0, minimum: 0 },
+    OdysseyWalletError::StaleFeeData { age: 0 },
+    OdysseyWalletError::SignerNonceRateLimited,
+    OdysseyWalletError::GlobalRateLimited,
+    OdysseyWalletError::RateLimited { retry_after: 0, escalation: 0 },
+    OdysseyWalletError::Overloaded,
+    OdysseyWalletError::MethodDisabled { method: "" },
+    OdysseyWalletError::InternalError,
+];
+
+impl OdysseyWalletError {
+    /// Returns the JSON-RPC error code of the error.
+    ///
+    /// Each error has a distinct, stable code in the range reserved for server errors, starting
+    /// at `-32010`. Codes are never reassigned, so new errors take the next free code. Internal
+    /// errors and disabled methods use the standard JSON-RPC codes.
+    pub(crate) const fn code(&self) -> i32 {
+        match self {
+            Self::ValueNotZero => -32010,
+            Self::FromSet => -32011,
+            Self::NonceSet => -32012,
+            Self::UnsupportedTransactionType { .. } => -32063,
+            Self::IllegalDestination => -32013,
+            Self::SystemAddressNotAllowed { .. } => -32014,
+            Self::AddressBlocked { .. } => -32060,
+            Self::InvalidAuthorizationSignature => -32015,
+            Self::AuthorizationNonceOutOfRange { .. } => -32016,
+            Self::DestinationFlagged { .. } => -32017,
+            Self::DestinationDestroyed { .. } => -32018,
+            Self::DelegationTargetTooNew { .. } => -32019,
+            Self::CohortNotEligible { .. } => -32020,
+            Self::InconsistentBlobFields => -32021,
+            Self::BlobsNotSponsored => -32065,
+            Self::DestinationUnverified { .. } => -32022,
+            Self::ProxyImplementationNotWhitelisted { .. } => -32051,
+            Self::DelegatedStateConflict { .. } => -32023,
+            Self::ConflictingPendingDelegation { .. } => -32024,
+            Self::NodeNotSynced => -32025,
+            Self::SponsorshipNotStarted { .. } => -32026,
+            Self::MaintenanceWindow { .. } => -32054,
+            Self::BatchCalldataTooLarge { .. } => -32027,
+            Self::CalldataTooLarge { .. } => -32055,
+            Self::SelectorNotAllowed { .. } => -32056,
+            Self::AttestationRequired => -32057,
+            Self::Eip7702NotSupported => -32028,
+            Self::EmptyAuthorizationList => -32029,
+            Self::AuthorizationChainIdMismatch { .. } => -32030,
+            Self::UniversalAuthorizationNotAllowed => -32031,
+            Self::ContradictoryAuthorizations { .. } => -32050,
+            Self::ChainIdMismatch { .. } => -32032,
+            Self::DestinationQuotaExceeded { .. } => -32033,
+            Self::BudgetExhausted => -32034,
+            Self::SponsorInsufficientFunds { .. } => -32052,
+            Self::SuspiciousActivity => -32035,
+            Self::TransactionTooLarge { .. } => -32036,
+            Self::AuthorizationListTooLarge { .. } => -32053,
+            Self::InvalidTransactionRequest => -32037,
+            Self::MissingTransactionFields { .. } => -32058,
+            Self::SigningFailed { .. } => -32059,
+            Self::InvalidCorrelationId => -32038,
+            Self::UnknownBundle { .. } => -32039,
+            Self::BundlePartiallySubmitted { .. } => -32066,
+            Self::InvalidAuditCursor => -32062,
+            Self::Unauthorized => -32061,
+            Self::DestinationRequiresValue => -32040,
+            Self::GasEstimateTooHigh { .. } => -32041,
+            Self::AnomalousGasProfile { .. } => -32042,
+            Self::ExcessiveStateGrowth { .. } => -32064,
+            Self::GasLimitAboveEstimate { .. } => -32043,
+            Self::FeeBelowMinimum { .. } => -32044,
+            Self::StaleFeeData { .. } => -32045,
+            Self::SignerNonceRateLimited => -32046,
+            Self::GlobalRateLimited => -32047,
+            Self::RateLimited { .. } => -32048,
+            Self::Overloaded => -32049,
+            Self::MethodDisabled { .. } => METHOD_NOT_FOUND_CODE,
+            Self::InternalError => INTERNAL_ERROR_CODE,
+        }
+    }
+
+    /// Returns the name, message template and fields of the error.
+    const fn describe(
+        &self,
+    ) -> (&'static str, &'static str, &'static [(&'static str, &'static str)]) {
+        const TARGET: &[(&str, &str)] = &[("target", "address")];
+        const SIZE: &[(&str, &str)] = &[("size", "uint64"), ("max", "uint64")];
+
+        match self {
+            Self::ValueNotZero => ("ValueNotZero", "tx value not zero", &[]),
+            Self::FromSet => ("FromSet", "tx from field is set", &[]),
+            Self::NonceSet => ("NonceSet", "tx nonce is set", &[]),
+            Self::UnsupportedTransactionType { .. } => (
+                "UnsupportedTransactionType",
+                "unsupported transaction type {tx_type}",
+                &[("tx_type", "uint8")],
+            ),
+            Self::IllegalDestination => (
+                "IllegalDestination",
+                "the destination of the transaction is not a delegated account",
+                &[],
+            ),
+            Self::SystemAddressNotAllowed { .. } => (
+                "SystemAddressNotAllowed",
+                "the transaction targets a system address: {address}",
+                &[("address", "address")],
+            ),
+            Self::AddressBlocked { .. } => {
+                ("AddressBlocked", "address {address} is blocked", &[("address", "address")])
+            }
+            Self::InvalidAuthorizationSignature => {
+                ("InvalidAuthorizationSignature", "invalid authorization signature", &[])
+            }
+            Self::AuthorizationNonceOutOfRange { .. } => (
+                "AuthorizationNonceOutOfRange",
+                "authorization nonce {nonce} for {authority} is out of range, current nonce is \
+                 {current}",
+                &[("authority", "address"), ("nonce", "uint64"), ("current", "uint64")],
+            ),
+            Self::DestinationFlagged { .. } => {
+                ("DestinationFlagged", "the delegation target {target} is flagged", TARGET)
+            }
+            Self::DestinationDestroyed { .. } => (
+                "DestinationDestroyed",
+                "the delegation target {target} has been destroyed",
+                TARGET,
+            ),
+            Self::DelegationTargetTooNew { .. } => (
+                "DelegationTargetTooNew",
+                "the delegation target {target} was deployed too recently",
+                TARGET,
+            ),
+            Self::CohortNotEligible { .. } => (
+                "CohortNotEligible",
+                "the delegation of {address} is not eligible for sponsorship",
+                &[("address", "address")],
+            ),
+            Self::InconsistentBlobFields => {
+                ("InconsistentBlobFields", "blob fee and blobs must be set together", &[])
+            }
+            Self::BlobsNotSponsored => {
+                ("BlobsNotSponsored", "blob transactions are not sponsored", &[])
+            }
+            Self::DestinationUnverified { .. } => {
+                ("DestinationUnverified", "the delegation target {target} is not verified", TARGET)
+            }
+            Self::ProxyImplementationNotWhitelisted { .. } => (
+                "ProxyImplementationNotWhitelisted",
+                "the implementation {implementation} of the proxy {proxy} is not whitelisted",
+                &[("proxy", "address"), ("implementation", "address")],
+            ),
+            Self::DelegatedStateConflict { .. } => (
+                "DelegatedStateConflict",
+                "the delegated account {account} has pending transactions that may conflict",
+                &[("account", "address")],
+            ),
+            Self::ConflictingPendingDelegation { .. } => (
+                "ConflictingPendingDelegation",
+                "{authority} has a pending delegation to {pending}",
+                &[("authority", "address"), ("pending", "address")],
+            ),
+            Self::NodeNotSynced => ("NodeNotSynced", "node is not synced", &[]),
+            Self::SponsorshipNotStarted { .. } => (
+                "SponsorshipNotStarted",
+                "sponsorship starts at block {start}, current block is {current}",
+                &[("current", "uint64"), ("start", "uint64")],
+            ),
+            Self::MaintenanceWindow { .. } => (
+                "MaintenanceWindow",
+                "sponsorship is paused for maintenance until {ends_at}",
+                &[("ends_at", "uint64")],
+            ),
+            Self::BatchCalldataTooLarge { .. } => (
+                "BatchCalldataTooLarge",
+                "batch calldata of {size} bytes exceeds the maximum of {max} bytes",
+                SIZE,
+            ),
+            Self::CalldataTooLarge { .. } => (
+                "CalldataTooLarge",
+                "calldata of {size} bytes exceeds the maximum of {max} bytes",
+                SIZE,
+            ),
+            Self::SelectorNotAllowed { .. } => (
+                "SelectorNotAllowed",
+                "function selector {selector} is not allowed",
+                &[("selector", "bytes")],
+            ),
+            Self::AttestationRequired => {
+                ("AttestationRequired", "a valid attestation is required", &[])
+            }
+            Self::Eip7702NotSupported => {
+                ("Eip7702NotSupported", "EIP-7702 is not supported on this chain", &[])
+            }
+            Self::EmptyAuthorizationList => {
+                ("EmptyAuthorizationList", "authorization list is empty", &[])
+            }
+            Self::AuthorizationChainIdMismatch { .. } => (
+                "AuthorizationChainIdMismatch",
+                "authorization for chain {chain_id} is not valid on this chain",
+                &[("chain_id", "uint64")],
+            ),
+            Self::UniversalAuthorizationNotAllowed => (
+                "UniversalAuthorizationNotAllowed",
+                "universal authorizations with chain id 0 are not allowed",
+                &[],
+            ),
+            Self::ContradictoryAuthorizations { .. } => (
+                "ContradictoryAuthorizations",
+                "{authority} delegates to different targets in the same authorization list",
+                &[("authority", "address")],
+            ),
+            Self::ChainIdMismatch { .. } => (
+                "ChainIdMismatch",
+                "the request for chain {chain_id} is not valid on this chain",
+                &[("chain_id", "uint64")],
+            ),
+            Self::DestinationQuotaExceeded { .. } => (
+                "DestinationQuotaExceeded",
+                "the delegation target {target} reached its daily quota",
+                TARGET,
+            ),
+            Self::BudgetExhausted => ("BudgetExhausted", "sponsorship budget exhausted", &[]),
+            Self::SponsorInsufficientFunds { .. } => (
+                "SponsorInsufficientFunds",
+                "sponsor balance {balance} is below the minimum of {minimum}",
+                &[("balance", "uint256"), ("minimum", "uint256")],
+            ),
+            Self::SuspiciousActivity => ("SuspiciousActivity", "suspicious activity", &[]),
+            Self::TransactionTooLarge { .. } => (
+                "TransactionTooLarge",
+                "transaction of {size} bytes exceeds the maximum of {max} bytes",
+                SIZE,
+            ),
+            Self::AuthorizationListTooLarge { .. } => (
+                "AuthorizationListTooLarge",
+                "authorization list of {size} bytes exceeds the maximum of {max} bytes",
+                SIZE,
+            ),
+            Self::InvalidTransactionRequest => {
+                ("InvalidTransactionRequest", "invalid tx request", &[])
+            }
+            Self::MissingTransactionFields { .. } => (
+                "MissingTransactionFields",
+                "{tx_type} transaction is missing fields: {fields}",
+                &[("tx_type", "string"), ("fields", "string[]")],
+            ),
+            Self::SigningFailed { .. } => {
+                ("SigningFailed", "failed to sign transaction: {reason}", &[("reason", "string")])
+            }
+            Self::InvalidCorrelationId => ("InvalidCorrelationId", "invalid correlation id", &[]),
+            Self::UnknownBundle { .. } => {
+                ("UnknownBundle", "unknown bundle or transaction {id}", &[("id", "bytes32")])
+            }
+            Self::BundlePartiallySubmitted { .. } => (
+                "BundlePartiallySubmitted",
+                "bundle {id} aborted after submitting some of its calls: {reason}",
+                &[("id", "bytes32"), ("reason", "string")],
+            ),
+            Self::InvalidAuditCursor => ("InvalidAuditCursor", "invalid audit log cursor", &[]),
+            Self::Unauthorized => ("Unauthorized", "invalid operator api key", &[]),
+            Self::DestinationRequiresValue => (
+                "DestinationRequiresValue",
+                "the destination requires a non-zero value, which is not sponsored",
+                &[],
+            ),
+            Self::GasEstimateTooHigh { .. } => (
+                "GasEstimateTooHigh",
+                "request would use too much gas: estimated {estimate}, limit is {limit}",
+                &[("estimate", "uint64"), ("limit", "uint64")],
+            ),
+            Self::AnomalousGasProfile { .. } => (
+                "AnomalousGasProfile",
+                "anomalous gas profile: {gas_per_byte} gas per calldata byte",
+                &[("gas_per_byte", "uint64")],
+            ),
+            Self::ExcessiveStateGrowth { .. } => (
+                "ExcessiveStateGrowth",
+                "transaction creates {slots} new storage slots, exceeding the maximum of {max}",
+                &[("slots", "uint64"), ("max", "uint64")],
+            ),
+            Self::GasLimitAboveEstimate { .. } => (
+                "GasLimitAboveEstimate",
+                "gas limit {gas_limit} exceeds the estimate of {estimate}",
+                &[("gas_limit", "uint64"), ("estimate", "uint64")],
+            ),
+            Self::FeeBelowMinimum { .. } => (
+                "FeeBelowMinimum",
+                "max fee per gas {max_fee_per_gas} is below the minimum of {minimum}",
+                &[("max_fee_per_gas", "uint128"), ("minimum", "uint128")],
+            ),
+            Self::StaleFeeData { .. } => (
+                "StaleFeeData",
+                "fee data is stale: latest block is {age}s old",
+                &[("age", "uint64")],
+            ),
+            Self::SignerNonceRateLimited => {
+                ("SignerNonceRateLimited", "signer nonce rate limit exceeded", &[])
+            }
+            Self::GlobalRateLimited => ("GlobalRateLimited", "global rate limit exceeded", &[]),
+            Self::RateLimited { .. } => (
+                "RateLimited",
+                "rate limited, retry after {retry_after}s (escalation {escalation})",
+                &[("retry_after", "uint64"), ("escalation", "uint32")],
+            ),
+            Self::Overloaded => ("Overloaded", "wallet is overloaded", &[]),
+            Self::MethodDisabled { .. } => {
+                ("MethodDisabled", "method {method} is disabled", &[("method", "string")])
+            }
+            Self::InternalError => ("InternalError", "internal error", &[]),
+        }
+    }
+}
+
+/// Returns the catalog of all errors returned by the `wallet_` namespace.
+pub fn error_catalog() -> Vec<ErrorCatalogEntry> {
+    VARIANTS
+        .iter()
+        .map(|error| {
+            let (name, message, fields) = error.describe();
+            ErrorCatalogEntry {
+                name: name.to_string(),
+                code: error.code(),
+                message: message.to_string(),
+                fields: fields
+                    .iter()
+                    .map(|(name, ty)| ErrorField { name: name.to_string(), ty: ty.to_string() })
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn codes_are_stable() {
+        let codes: Vec<_> =
+            error_catalog().into_iter().map(|entry| (entry.name, entry.code)).collect();
+        let expected = [
+            ("ValueNotZero", -32010),
+            ("FromSet", -32011),
+            ("NonceSet", -32012),
+            ("UnsupportedTransactionType", -32063),
+            ("IllegalDestination", -32013),
+            ("SystemAddressNotAllowed", -32014),
+            ("AddressBlocked", -32060),
+            ("InvalidAuthorizationSignature", -32015),
+            ("AuthorizationNonceOutOfRange", -32016),
+            ("DestinationFlagged", -32017),
+            ("DestinationDestroyed", -32018),
+            ("DelegationTargetTooNew", -32019),
+            ("CohortNotEligible", -32020),
+            ("InconsistentBlobFields", -32021),
+            ("BlobsNotSponsored", -32065),
+            ("DestinationUnverified", -32022),
+            ("ProxyImplementationNotWhitelisted", -32051),
+            ("DelegatedStateConflict", -32023),
+            ("ConflictingPendingDelegation", -32024),
+            ("NodeNotSynced", -32025),
+            ("SponsorshipNotStarted", -32026),
+            ("MaintenanceWindow", -32054),
+            ("BatchCalldataTooLarge", -32027),
+            ("CalldataTooLarge", -32055),
+            ("SelectorNotAllowed", -32056),
+            ("AttestationRequired", -32057),
+            ("Eip7702NotSupported", -32028),
+            ("EmptyAuthorizationList", -32029),
+            ("AuthorizationChainIdMismatch", -32030),
+            ("UniversalAuthorizationNotAllowed", -32031),
+            ("ContradictoryAuthorizations", -32050),
+            ("ChainIdMismatch", -32032),
+            ("DestinationQuotaExceeded", -32033),
+            ("BudgetExhausted", -32034),
+            ("SponsorInsufficientFunds", -32052),
+            ("SuspiciousActivity", -32035),
+            ("TransactionTooLarge", -32036),
+            ("AuthorizationListTooLarge", -32053),
+            ("InvalidTransactionRequest", -32037),
+            ("MissingTransactionFields", -32058),
+            ("SigningFailed", -32059),
+            ("InvalidCorrelationId", -32038),
+            ("UnknownBundle", -32039),
+            ("BundlePartiallySubmitted", -32066),
+            ("InvalidAuditCursor", -32062),
+            ("Unauthorized", -32061),
+            ("DestinationRequiresValue", -32040),
+            ("GasEstimateTooHigh", -32041),
+            ("AnomalousGasProfile", -32042),
+            ("ExcessiveStateGrowth", -32064),
+            ("GasLimitAboveEstimate", -32043),
+            ("FeeBelowMinimum", -32044),
+            ("StaleFeeData", -32045),
+            ("SignerNonceRateLimited", -32046),
+            ("GlobalRateLimited", -32047),
+            ("RateLimited", -32048),
+            ("Overloaded", -32049),
+            ("MethodDisabled", METHOD_NOT_FOUND_CODE),
+            ("InternalError", INTERNAL_ERROR_CODE),
+        ];
+        assert_eq!(codes.len(), expected.len());
+        for ((name, code), (expected_name, expected_code)) in codes.iter().zip(expected) {
+            assert_eq!((name.as_str(), *code), (expected_name, expected_code));
+        }
+    }
+
+    #[test]
+    fn lists_every_variant() {
+        // adding a variant fails to compile here, as a reminder to add it to `VARIANTS`
+        let _ = |error: OdysseyWalletError| match error {
+            OdysseyWalletError::ValueNotZero
+            | OdysseyWalletError::FromSet
+            | OdysseyWalletError::NonceSet
+            | OdysseyWalletError::UnsupportedTransactionType { .. }
+            | OdysseyWalletError::IllegalDestination
+            | OdysseyWalletError::SystemAddressNotAllowed { .. }
+            | OdysseyWalletError::AddressBlocked { .. }
+            | OdysseyWalletError::InvalidAuthorizationSignature
+            | OdysseyWalletError::AuthorizationNonceOutOfRange { .. }
+            | OdysseyWalletError::DestinationFlagged { .. }
+            | OdysseyWalletError::DestinationDestroyed { .. }
+            | OdysseyWalletError::DelegationTargetTooNew { .. }
+            | OdysseyWalletError::CohortNotEligible { .. }
+            | OdysseyWalletError::InconsistentBlobFields
+            | OdysseyWalletError::BlobsNotSponsored
+            | OdysseyWalletError::DestinationUnverified { .. }
+            | OdysseyWalletError::ProxyImplementationNotWhitelisted { .. }
+            | OdysseyWalletError::DelegatedStateConflict { .. }
+            | OdysseyWalletError::ConflictingPendingDelegation { .. }
+            | OdysseyWalletError::NodeNotSynced
+            | OdysseyWalletError::SponsorshipNotStarted { .. }
+            | OdysseyWalletError::MaintenanceWindow { .. }
+            | OdysseyWalletError::BatchCalldataTooLarge { .. }
+            | OdysseyWalletError::CalldataTooLarge { .. }
+            | OdysseyWalletError::SelectorNotAllowed { .. }
+            | OdysseyWalletError::AttestationRequired
+            | OdysseyWalletError::Eip7702NotSupported
+            | OdysseyWalletError::EmptyAuthorizationList
+            | OdysseyWalletError::AuthorizationChainIdMismatch { .. }
+            | OdysseyWalletError::UniversalAuthorizationNotAllowed
+            | OdysseyWalletError::ContradictoryAuthorizations { .. }
+            | OdysseyWalletError::ChainIdMismatch { .. }
+            | OdysseyWalletError::DestinationQuotaExceeded { .. }
+            | OdysseyWalletError::BudgetExhausted
+            | OdysseyWalletError::SponsorInsufficientFunds { .. }
+            | OdysseyWalletError::SuspiciousActivity
+            | OdysseyWalletError::TransactionTooLarge { .. }
+            | OdysseyWalletError::AuthorizationListTooLarge { .. }
+            | OdysseyWalletError::InvalidTransactionRequest
+            | OdysseyWalletError::MissingTransactionFields { .. }
+            | OdysseyWalletError::SigningFailed { .. }
+            | OdysseyWalletError::InvalidCorrelationId
+            | OdysseyWalletError::UnknownBundle { .. }
+            | OdysseyWalletError::BundlePartiallySubmitted { .. }
+            | OdysseyWalletError::InvalidAuditCursor
+            | OdysseyWalletError::Unauthorized
+            | OdysseyWalletError::DestinationRequiresValue
+            | OdysseyWalletError::GasEstimateTooHigh { .. }
+            | OdysseyWalletError::AnomalousGasProfile { .. }
+            | OdysseyWalletError::ExcessiveStateGrowth { .. }
+            | OdysseyWalletError::GasLimitAboveEstimate { .. }
+            | OdysseyWalletError::FeeBelowMinimum { .. }
+            | OdysseyWalletError::StaleFeeData { .. }
+            | OdysseyWalletError::SignerNonceRateLimited
+            | OdysseyWalletError::GlobalRateLimited
+            | OdysseyWalletError::RateLimited { .. }
+            | OdysseyWalletError::Overloaded
+            | OdysseyWalletError::MethodDisabled { .. }
+            | OdysseyWalletError::InternalError => (),
+        };
+
+        let catalog = error_catalog();
+        let names: HashSet<_> = catalog.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names.len(), 59);
+        assert_eq!(catalog.len(), 59);
+
+        for (error, entry) in VARIANTS.iter().zip(&catalog) {
+            // the template matches the rendered message
+            let rendered = error.to_string();
+            let mut rest = rendered.as_str();
+            for literal in entry.message.split(['{', '}']).step_by(2) {
+                let at = rest.find(literal).unwrap_or_else(|| panic!("{} mismatch", entry.name));
+                rest = &rest[at + literal.len()..];
+            }
+            for field in &entry.fields {
+                assert!(entry.message.contains(&format!("{{{}}}", field.name)));
+            }
+        }
+    }
+}
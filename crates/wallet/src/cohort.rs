@@ -0,0 +1,72 @@
+//! Gating of sponsorship by the block in which an account was first delegated.
+//!
+//! The block in which an account was first delegated is not indexed, so it is derived from
+//! historical state instead: an account that is delegated at the end of the cohort, but not right
+//! before its start, was delegated within the cohort. This requires at most three state lookups,
+//! but assumes that the delegation was not revoked and re-established in between. An account that
+//! revoked its delegation and re-delegated later may therefore be attributed to the wrong cohort.
+
+use alloy_primitives::BlockNumber;
+use std::ops::RangeInclusive;
+
+/// Returns whether an account was first delegated within the cohort.
+///
+/// `is_delegated_at` returns whether the account is delegated at the given block. An account that
+/// is not delegated at the `latest` block is delegated by the transaction being sponsored, and so
+/// belongs to the cohort of the next block.
+pub(crate) fn is_in_cohort<E>(
+    cohort: &RangeInclusive<BlockNumber>,
+    latest: BlockNumber,
+    mut is_delegated_at: impl FnMut(BlockNumber) -> Result<bool, E>,
+) -> Result<bool, E> {
+    if !is_delegated_at(latest)? {
+        return Ok(cohort.contains(&latest.saturating_add(1)));
+    }
+
+    // the account was delegated before the cohort started
+    if *cohort.start() > latest {
+        return Ok(false);
+    }
+
+    // the account was delegated after the cohort ended
+    let end = (*cohort.end()).min(latest);
+    if end != latest && !is_delegated_at(end)? {
+        return Ok(false);
+    }
+
+    // the account was delegated before the cohort started
+    if *cohort.start() > 0 && is_delegated_at(cohort.start() - 1)? {
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+
+    #[test]
+    fn gates_by_first_delegation() {
+        // delegated since block 100
+        let delegated_since =
+            |first: BlockNumber| move |block: BlockNumber| Ok::<_, Infallible>(block >= first);
+        let latest = 1_000;
+
+        assert_eq!(is_in_cohort(&(50..=150), latest, delegated_since(100)), Ok(true));
+        assert_eq!(is_in_cohort(&(100..=100), latest, delegated_since(100)), Ok(true));
+        assert_eq!(is_in_cohort(&(0..=u64::MAX), latest, delegated_since(100)), Ok(true));
+
+        // delegated before the cohort
+        assert_eq!(is_in_cohort(&(101..=200), latest, delegated_since(100)), Ok(false));
+        assert_eq!(is_in_cohort(&(2_000..=3_000), latest, delegated_since(100)), Ok(false));
+
+        // delegated after the cohort
+        assert_eq!(is_in_cohort(&(0..=99), latest, delegated_since(100)), Ok(false));
+
+        // delegated by the sponsored transaction
+        assert_eq!(is_in_cohort(&(500..=u64::MAX), latest, delegated_since(2_000)), Ok(true));
+        assert_eq!(is_in_cohort(&(0..=1_000), latest, delegated_since(2_000)), Ok(false));
+    }
+}
@@ -0,0 +1,163 @@
+//! A bound on the number of concurrent gas estimations.
+//!
+//! Each estimate runs an EVM execution, so too many concurrent estimations can overwhelm the
+//! executor of the node. This is independent of the signing permit, which only serializes the
+//! assignment of nonces.
+
+use crate::OdysseyWalletError;
+use metrics::Gauge;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// How estimations beyond the concurrency limit are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaturatedEstimations {
+    /// The request waits until an estimation finishes.
+    Queue,
+    /// The request is rejected with [`OdysseyWalletError::Overloaded`].
+    Reject,
+}
+
+/// Settings for the bound on concurrent gas estimations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EstimationLimit {
+    /// The maximum number of estimations in flight.
+    pub max_concurrent: usize,
+    /// How estimations beyond the limit are handled.
+    pub on_saturated: SaturatedEstimations,
+}
+
+/// Bounds the number of concurrent gas estimations.
+#[derive(Debug)]
+pub(crate) struct EstimationLimiter {
+    permits: Semaphore,
+    on_saturated: SaturatedEstimations,
+    /// The number of requests waiting for a permit.
+    queued: AtomicUsize,
+}
+
+impl EstimationLimiter {
+    /// Creates a new limiter with the given settings.
+    pub(crate) fn new(limit: EstimationLimit) -> Self {
+        Self {
+            permits: Semaphore::new(limit.max_concurrent),
+            on_saturated: limit.on_saturated,
+            queued: AtomicUsize::new(0),
+        }
+    }
+
+    /// Acquires a permit to run an estimation, which is held until it is dropped.
+    ///
+    /// The number of waiting requests is reported to `queue_depth`.
+    pub(crate) async fn acquire(
+        &self,
+        queue_depth: &Gauge,
+    ) -> Result<SemaphorePermit<'_>, OdysseyWalletError> {
+        if let Ok(permit) = self.permits.try_acquire() {
+            return Ok(permit);
+        }
+        if self.on_saturated == SaturatedEstimations::Reject {
+            return Err(OdysseyWalletError::Overloaded);
+        }
+
+        let _queued = Queued::enter(&self.queued, queue_depth);
+        // the semaphore is never closed
+        self.permits.acquire().await.map_err(|_| OdysseyWalletError::InternalError)
+    }
+
+    /// Acquires a permit without waiting, regardless of how saturated estimations are handled.
+    ///
+    /// Returns `None` if all permits are taken. The request is not counted as queued.
+    pub(crate) fn try_acquire(&self) -> Option<SemaphorePermit<'_>> {
+        self.permits.try_acquire().ok()
+    }
+}
+
+/// A request waiting for a permit, which leaves the queue when dropped, including if the request
+/// is cancelled.
+struct Queued<'a> {
+    queued: &'a AtomicUsize,
+    queue_depth: &'a Gauge,
+}
+
+impl<'a> Queued<'a> {
+    fn enter(queued: &'a AtomicUsize, queue_depth: &'a Gauge) -> Self {
+        let depth = queued.fetch_add(1, Ordering::SeqCst) + 1;
+        queue_depth.set(depth as f64);
+        Self { queued, queue_depth }
+    }
+}
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        let depth = self.queued.fetch_sub(1, Ordering::SeqCst) - 1;
+        self.queue_depth.set(depth as f64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{sync::Arc, time::Duration};
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn bounds_concurrent_estimations() {
+        let limiter = Arc::new(EstimationLimiter::new(EstimationLimit {
+            max_concurrent: 3,
+            on_saturated: SaturatedEstimations::Queue,
+        }));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+
+        let tasks: Vec<_> = (0..50)
+            .map(|_| {
+                let limiter = limiter.clone();
+                let in_flight = in_flight.clone();
+                let max_in_flight = max_in_flight.clone();
+                tokio::spawn(async move {
+                    let _permit = limiter.acquire(&Gauge::noop()).await.unwrap();
+                    let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_in_flight.fetch_max(current, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert!(max_in_flight.load(Ordering::SeqCst) <= 3);
+        assert_eq!(limiter.queued.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn rejects_excess_estimations() {
+        let limiter = EstimationLimiter::new(EstimationLimit {
+            max_concurrent: 1,
+            on_saturated: SaturatedEstimations::Reject,
+        });
+
+        let permit = limiter.acquire(&Gauge::noop()).await.unwrap();
+        assert_eq!(
+            limiter.acquire(&Gauge::noop()).await.map(drop),
+            Err(OdysseyWalletError::Overloaded)
+        );
+        drop(permit);
+        assert!(limiter.acquire(&Gauge::noop()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn try_acquire_never_queues() {
+        let limiter = EstimationLimiter::new(EstimationLimit {
+            max_concurrent: 1,
+            on_saturated: SaturatedEstimations::Queue,
+        });
+
+        let permit = limiter.try_acquire().unwrap();
+        assert!(limiter.try_acquire().is_none());
+        assert_eq!(limiter.queued.load(Ordering::SeqCst), 0);
+        drop(permit);
+        assert!(limiter.try_acquire().is_some());
+    }
+}
@@ -0,0 +1,1623 @@
+//! Configuration for the Odyssey `wallet_` namespace.
+
+use crate::{
+    age::TargetAges,
+    alert::BudgetAlerts,
+    audit::OperatorApiKey,
+    authorization::ContradictoryAuthorizations,
+    batch::SubmissionBatcher,
+    budget::{SpendingCap, SpendingCapConfig, SponsorshipBudget, SponsorshipBudgetConfig},
+    concurrency::{EstimationLimit, EstimationLimiter, SaturatedEstimations},
+    confirmation::ConfirmationTracking,
+    destroyed::{DestroyedTargetCheck, DestroyedTargets},
+    freshness::{FeeDataMaxAge, StaleFeeDataPolicy},
+    gas::{
+        AdaptiveGasCeiling, AdaptiveGasCeilingConfig, CallDepthProbe, DeepCallGasCeiling,
+        GasCeilingOverrides,
+    },
+    growth::{StateGrowthLimit, StateGrowthProbe},
+    maintenance::{MaintenanceSchedule, MaintenanceWindow},
+    min_fee::{BelowMinimumFee, MinimumFeeCheck},
+    nonce::SignerNonceRate,
+    oracle::{
+        AttestationVerifier, NoopAttestationVerifier, NoopRiskOracle, NoopVerificationOracle,
+        RiskOracle, VerificationOracle,
+    },
+    pattern::{PatternDetection, PatternDetector},
+    pending::{ConflictingDelegations, PendingDelegationTracking, PendingDelegations},
+    persist::StateStore,
+    priority_fee::{PriorityFee, DEFAULT_PRIORITY_FEE},
+    proxy::ProxyDelegationPolicy,
+    quota::DestinationQuotas,
+    rate_limit::{GlobalRateLimitConfig, GlobalRateLimiter, RateLimitConfig, RateLimiter},
+    registry::{RegistryWhitelist, WhitelistRegistryConfig},
+    system::SystemAddresses,
+    whitelist::{duplicates, DelegationWhitelist, DuplicateWhitelistEntries, WhitelistSources},
+    DelegationCapability,
+};
+use alloy_primitives::{Address, BlockNumber, U256};
+use jsonrpsee::ConnectionId;
+use std::{
+    collections::{HashMap, HashSet},
+    ops::RangeInclusive,
+    sync::{Arc, OnceLock},
+    time::{Duration, Instant},
+};
+
+/// The default gas ceiling for sponsored transactions.
+///
+/// Requests estimated to consume this much gas or more are rejected.
+pub const DEFAULT_GAS_CEILING: u64 = 350_000;
+
+/// The default buffer on top of the gas estimate, in percent.
+pub const DEFAULT_GAS_BUFFER_PERCENT: u64 = 10;
+
+/// The default maximum calldata of a sponsored transaction, in bytes.
+///
+/// This matches the maximum size of a transaction accepted by the transaction pool.
+pub const DEFAULT_MAX_CALLDATA_BYTES: usize = 128 * 1024;
+
+/// Tunables for [`OdysseyWallet`](crate::OdysseyWallet).
+///
+/// Unless stated otherwise, optional checks are disabled by default.
+#[derive(Debug, Clone)]
+pub struct OdysseyWalletConfig {
+    /// The static gas ceiling, enforced unless the adaptive gas ceiling is enabled.
+    ///
+    /// Defaults to [`DEFAULT_GAS_CEILING`].
+    pub gas_ceiling: u64,
+    /// Adaptive gas ceiling settings.
+    ///
+    /// If `None`, the static [`gas_ceiling`](Self::gas_ceiling) is enforced.
+    pub adaptive_gas_ceiling: Option<AdaptiveGasCeilingConfig>,
+    /// Gas ceilings for specific destinations and functions, which take precedence over the
+    /// global ceiling.
+    pub gas_ceiling_overrides: GasCeilingOverrides,
+    /// The buffer on top of the gas estimate transactions are signed with, in percent.
+    ///
+    /// The gas ceiling is enforced against the buffered estimate. Defaults to
+    /// [`DEFAULT_GAS_BUFFER_PERCENT`].
+    pub gas_buffer_percent: u64,
+    /// The maximum calldata of a request, in bytes.
+    ///
+    /// Larger requests are rejected before any state is accessed, as they are expensive to
+    /// estimate and submit even if they revert. For `wallet_sendCalls`, this applies to each call.
+    /// Defaults to [`DEFAULT_MAX_CALLDATA_BYTES`].
+    pub max_calldata_bytes: usize,
+    /// The function selectors requests may call.
+    ///
+    /// If set, requests whose calldata does not start with a listed selector are rejected,
+    /// including requests with less than 4 bytes of calldata. For `wallet_sendCalls`, this applies
+    /// to each call.
+    pub allowed_selectors: Option<HashSet<[u8; 4]>>,
+    /// The function selectors requests may not call.
+    ///
+    /// This takes precedence over [`allowed_selectors`](Self::allowed_selectors). Requests with
+    /// less than 4 bytes of calldata are not blocked.
+    pub blocked_selectors: HashSet<[u8; 4]>,
+    /// The delegation targets that are sponsored.
+    ///
+    /// Requests that would call into, or delegate to, a target that is not listed are rejected.
+    /// If neither this nor the [`whitelist_registry`](Self::whitelist_registry) is set, no
+    /// delegation target is sponsored. Duplicate entries are handled according to
+    /// [`duplicate_whitelist_entries`](Self::duplicate_whitelist_entries).
+    pub delegation_whitelist: Option<DelegationCapability>,
+    /// How duplicate entries in the [`delegation_whitelist`](Self::delegation_whitelist) are
+    /// handled.
+    ///
+    /// Duplicates are removed either way, but may indicate a configuration error. Defaults to
+    /// [`DuplicateWhitelistEntries::Warn`].
+    pub duplicate_whitelist_entries: DuplicateWhitelistEntries,
+    /// The on-chain registry the delegation whitelist is loaded from.
+    ///
+    /// The registry is read by
+    /// [`OdysseyWallet::refresh_whitelist_periodically`](crate::OdysseyWallet::refresh_whitelist_periodically),
+    /// and requests are validated against the last list read successfully. If the
+    /// [`delegation_whitelist`](Self::delegation_whitelist) is set too, the two are combined
+    /// according to [`whitelist_sources`](Self::whitelist_sources). Otherwise, no delegation
+    /// target is sponsored until the first successful read.
+    pub whitelist_registry: Option<WhitelistRegistryConfig>,
+    /// How the [`delegation_whitelist`](Self::delegation_whitelist) is combined with the list
+    /// read from the [`whitelist_registry`](Self::whitelist_registry), if both are set.
+    ///
+    /// Defaults to [`WhitelistSources::RegistryOverride`].
+    pub whitelist_sources: WhitelistSources,
+    /// Precompile and system addresses that transactions may neither call nor delegate to.
+    ///
+    /// Defaults to [`SystemAddresses::op_stack`].
+    pub system_addresses: SystemAddresses,
+    /// Accounts that are banned from sponsorship, e.g. for abuse.
+    ///
+    /// Requests sent to a blocked account, or carrying an authorization signed by one, are
+    /// rejected. The list can be changed at runtime with
+    /// [`OdysseyWallet::update_config`](crate::OdysseyWallet::update_config).
+    pub blocked_addresses: HashSet<Address>,
+    /// How far ahead of the authority's current nonce an [EIP-7702][eip-7702] authorization
+    /// nonce may be.
+    ///
+    /// If set, authorizations with a nonce outside of `current..=current + tolerance` are
+    /// rejected. This requires recovering the authority of each authorization and reading its
+    /// nonce from state.
+    ///
+    /// [eip-7702]: https://eips.ethereum.org/EIPS/eip-7702
+    pub authorization_nonce_tolerance: Option<u64>,
+    /// The methods of the `wallet_` namespace that are enabled.
+    ///
+    /// Defaults to all methods being enabled.
+    pub methods: MethodFlags,
+    /// The oracle consulted to reject delegation targets that are flagged.
+    ///
+    /// Defaults to [`NoopRiskOracle`].
+    pub risk_oracle: Arc<dyn RiskOracle>,
+    /// The oracle consulted to reject delegation targets whose source is not verified.
+    ///
+    /// Defaults to [`NoopVerificationOracle`]. Wrap remote oracles in a
+    /// [`CachedVerificationOracle`](crate::CachedVerificationOracle) to avoid a lookup per
+    /// request.
+    pub verification_oracle: Arc<dyn VerificationOracle>,
+    /// The verifier of the attestation tokens clients attach to `wallet_sendTransaction`,
+    /// `wallet_sendTransactionWithRaw` and `wallet_sendCalls`.
+    ///
+    /// Defaults to [`NoopAttestationVerifier`], which does not require a token.
+    pub attestation_verifier: Arc<dyn AttestationVerifier>,
+    /// The window over which signed transactions are collected before being submitted together.
+    ///
+    /// Transactions are submitted in nonce order, and each caller receives the hash of its
+    /// transaction once the batch has been submitted. If too many transactions are waiting, e.g.
+    /// because submissions stall, further requests are rejected. If `None`, transactions are
+    /// submitted immediately.
+    pub submission_batch_window: Option<Duration>,
+    /// The maximum random delay before a signed transaction is submitted.
+    ///
+    /// This spreads bursts of submissions. Transactions are still submitted in nonce order, so a
+    /// transaction also waits for the submission of all transactions with a lower nonce. Ignored
+    /// if
+    /// [`submission_batch_window`](Self::submission_batch_window) is set.
+    pub submission_jitter: Option<Duration>,
+    /// Settings for tracking submitted transactions until they are included.
+    ///
+    /// If set, the gas used by each included transaction is compared to its estimate, and the
+    /// difference is recorded in the `gas_estimate_error` histogram. If `None`, transactions are
+    /// not tracked after their submission.
+    pub confirmation_tracking: Option<ConfirmationTracking>,
+    /// The maximum time a request waits for the signing permit, which is held while a nonce is
+    /// reserved.
+    ///
+    /// Requests that cannot acquire the permit in time are rejected with
+    /// [`OdysseyWalletError::Overloaded`](crate::OdysseyWalletError::Overloaded). If `None`,
+    /// requests wait indefinitely.
+    pub permit_acquire_timeout: Option<Duration>,
+    /// Settings for rejecting delegation targets whose code has been removed.
+    ///
+    /// If set, requests that would call into, or delegate to, a target that had code within the
+    /// lookback but has none at the latest block are rejected.
+    pub destroyed_target_check: Option<DestroyedTargetCheck>,
+    /// The minimum number of blocks since the deployment of delegation targets.
+    ///
+    /// If set, requests that would call into, or delegate to, a target that had no code this many
+    /// blocks before the latest block are rejected. This avoids sponsoring freshly deployed, and
+    /// possibly unaudited, implementations.
+    pub min_delegation_target_age: Option<u64>,
+    /// Stricter rules for requests to accounts delegating to an upgradeable proxy.
+    ///
+    /// If `None`, proxies are treated like any other delegation target.
+    pub proxy_delegations: Option<ProxyDelegationPolicy>,
+    /// Rate limit for `wallet_sendTransaction` per client connection.
+    ///
+    /// Connections are identified by the [`ConnectionId`](jsonrpsee::ConnectionId) the server
+    /// attaches to each request. This catches clients that rotate EOAs over a single connection,
+    /// e.g. a WebSocket or a keep-alive HTTP connection, but not clients that open a new
+    /// connection per request. The peer address is not exposed to RPC handlers, so limiting per
+    /// IP requires an HTTP middleware that inserts the peer address into the request extensions.
+    pub connection_rate_limit: Option<RateLimitConfig>,
+    /// Rate limit for `wallet_sendTransaction` per sponsored account.
+    ///
+    /// The account is the destination of EIP-1559 transactions, and each authority of EIP-7702
+    /// transactions. This catches accounts spamming the sequencer across connections.
+    pub account_rate_limit: Option<RateLimitConfig>,
+    /// The limit on `wallet_sendTransaction` requests across all callers.
+    ///
+    /// This is a coarse protection of the service and the sequencer against traffic surges,
+    /// regardless of their source. Defaults to a generous [`GlobalRateLimitConfig::default`].
+    pub global_rate_limit: Option<GlobalRateLimitConfig>,
+    /// The bound on concurrent gas estimations across all requests.
+    ///
+    /// Estimations run an EVM execution each, so this protects the executor of the node under
+    /// load. If `None`, estimations are not bounded.
+    pub estimation_limit: Option<EstimationLimit>,
+    /// The blocks in which the destination must have first been delegated.
+    ///
+    /// If set, only transactions to accounts that were first delegated within this range are
+    /// sponsored, which allows rolling out sponsorship in phases. The block of the first
+    /// delegation is derived from historical state, assuming the delegation was not revoked and
+    /// re-established in between.
+    pub delegation_cohort: Option<RangeInclusive<BlockNumber>>,
+    /// Whether to reject `wallet_sendTransaction` while the node is syncing.
+    ///
+    /// While syncing, state reads and estimates may be based on stale state.
+    pub require_synced: bool,
+    /// Whether to reject EIP-1559 calls to delegated accounts that have transactions pending in
+    /// the pool.
+    ///
+    /// See [`DelegatedStateConflict`](crate::OdysseyWalletError::DelegatedStateConflict).
+    pub reject_delegated_state_conflicts: bool,
+    /// The maximum number of nonces assigned to the signer per minute.
+    ///
+    /// This throttles the signer itself, protecting it against bugs or abuse that rapidly consume
+    /// nonces, independent of the limits per client. If `None`, the rate is not limited.
+    pub max_signer_nonces_per_minute: Option<u32>,
+    /// The block number from which `wallet_sendTransaction` sponsors transactions.
+    ///
+    /// Requests are rejected until the chain reaches this block, which ties the activation of
+    /// sponsorship to on-chain state rather than the clocks of the nodes. If `None`, sponsorship
+    /// is active from the start.
+    pub sponsorship_start_block: Option<BlockNumber>,
+    /// The planned maintenance windows, during which `wallet_sendTransaction` is rejected with
+    /// [`OdysseyWalletError::MaintenanceWindow`](crate::OdysseyWalletError::MaintenanceWindow).
+    ///
+    /// Unlike the start block, windows are scheduled by the clock of the node.
+    pub maintenance: Option<MaintenanceSchedule>,
+    /// The accepted gas estimate per byte of calldata.
+    ///
+    /// If set, requests with calldata whose estimate per byte of calldata falls outside this band
+    /// are rejected. A low ratio indicates calldata used for data availability rather than
+    /// execution, a high ratio an execution bomb. Requests without calldata are not checked.
+    pub gas_per_calldata_byte: Option<RangeInclusive<u64>>,
+    /// The maximum combined calldata of all calls in a `wallet_sendCalls` batch, in bytes.
+    ///
+    /// This bounds the data availability cost of a batch, independent of the size of each call.
+    pub max_batch_calldata_bytes: Option<usize>,
+    /// The maximum size of the signed, EIP-2718 encoded transaction, in bytes.
+    ///
+    /// This is checked after signing, and catches transactions bloated by e.g. many
+    /// authorizations or a large access list.
+    pub max_tx_bytes: Option<usize>,
+    /// The maximum size of the RLP encoded authorization list of a request, in bytes.
+    ///
+    /// This bounds the data availability cost of delegation-setup transactions, and is checked
+    /// before estimating the request.
+    pub max_authorization_list_bytes: Option<usize>,
+    /// Whether [EIP-7702][eip-7702] is active on the chain.
+    ///
+    /// If `false`, requests carrying an authorization list are rejected, as they would be invalid
+    /// on chain. Defaults to `true`.
+    ///
+    /// [eip-7702]: https://eips.ethereum.org/EIPS/eip-7702
+    pub supports_7702: bool,
+    /// Whether to reject universal [EIP-7702][eip-7702] authorizations, i.e. those with chain id
+    /// 0.
+    ///
+    /// Universal authorizations are valid on any chain, so a sponsored delegation could be
+    /// replayed on other chains.
+    ///
+    /// [eip-7702]: https://eips.ethereum.org/EIPS/eip-7702
+    pub reject_universal_authorizations: bool,
+    /// Whether to sponsor [EIP-7702][eip-7702] authorizations to the zero address, which revoke
+    /// the delegation of the authority.
+    ///
+    /// Revocations do not delegate to any code, so if allowed, they are exempt from the checks on
+    /// delegation targets, e.g. the whitelist. This lets users remove a compromised delegation
+    /// through sponsorship. Disabled by default, in which case the zero address is checked like
+    /// any other target.
+    ///
+    /// [eip-7702]: https://eips.ethereum.org/EIPS/eip-7702
+    pub allow_revocation: bool,
+    /// How EIP-7702 authorization lists that delegate an authority to different targets are
+    /// handled.
+    ///
+    /// If `None`, such authorization lists are sponsored as they are.
+    pub contradictory_authorizations: Option<ContradictoryAuthorizations>,
+    /// The budget for sponsored transactions.
+    ///
+    /// If set, the maximum cost of each transaction is consumed from a continuously refilling
+    /// budget, and transactions are rejected while the budget does not cover their cost.
+    pub sponsorship_budget: Option<SponsorshipBudgetConfig>,
+    /// The hard cap on the funds spent on sponsorship within a fixed window, e.g. a day.
+    ///
+    /// If set, the maximum cost of each transaction counts towards the cap, and once it is
+    /// reached, all transactions are rejected until the window ends.
+    pub spending_cap: Option<SpendingCapConfig>,
+    /// The balance of the sponsor below which sponsorship is paused.
+    ///
+    /// If set, requests are rejected with
+    /// [`OdysseyWalletError::SponsorInsufficientFunds`](crate::OdysseyWalletError::SponsorInsufficientFunds)
+    /// while the balance is below the minimum, rather than failing once the sponsor is drained.
+    pub min_sponsor_balance: Option<U256>,
+    /// The channel alerts on the sponsorship budget crossing its thresholds are pushed to.
+    ///
+    /// If set, `wallet_subscribeBudget` is enabled. Otherwise, subscriptions are rejected with
+    /// [`OdysseyWalletError::MethodDisabled`](crate::OdysseyWalletError::MethodDisabled).
+    pub budget_alerts: Option<BudgetAlerts>,
+    /// Whether the methods of the `admin_` namespace are enabled.
+    ///
+    /// These change what is sponsored at runtime, so the namespace should only be served on
+    /// trusted transports. Disabled by default.
+    pub admin_api: bool,
+    /// The key operators authenticate with to read `wallet_auditLog`.
+    ///
+    /// If `None`, `wallet_auditLog` is disabled.
+    pub operator_api_key: Option<OperatorApiKey>,
+    /// The maximum number of sponsored calls per delegation target within a rolling 24 hour
+    /// window.
+    ///
+    /// If set, requests that would call into, or delegate to, a target that reached its quota are
+    /// rejected. Targets without a quota are not limited.
+    pub destination_quotas: Option<HashMap<Address, u32>>,
+    /// The maximum value, in wei, that may be sent with calls handled by each delegation target.
+    ///
+    /// By default only transactions without value are sponsored. If set, transactions to accounts
+    /// delegated to a listed target, or delegating to it, may carry a value up to its allowance,
+    /// e.g. to pay a small fee through a whitelisted contract. If a transaction has several
+    /// delegation targets, the lowest allowance applies. The value is paid by the sponsor.
+    pub value_allowances: Option<HashMap<Address, U256>>,
+    /// Settings for detecting EIP-7702 delegations that conflict with a pending delegation of the
+    /// same authority to another target.
+    ///
+    /// If `None`, conflicting delegations are sponsored.
+    pub pending_delegation_tracking: Option<PendingDelegationTracking>,
+    /// Whether the node forwards transactions to a sequencer, e.g. because
+    /// `--rollup.sequencer-http` is set.
+    ///
+    /// This is reported in the [`SubmissionOutcome`](crate::SubmissionOutcome) of
+    /// `wallet_sendTransactionWithRaw`.
+    pub forwards_to_sequencer: bool,
+    /// Detection of suspicious request patterns.
+    ///
+    /// If set, `wallet_sendTransaction` requests scored at or above the threshold by the detector
+    /// are rejected.
+    pub pattern_detection: Option<PatternDetection>,
+    /// A tighter gas ceiling for requests with deep call stacks, e.g. because of a delegation
+    /// loop.
+    ///
+    /// No [`CallDepthProbe`] is provided, as the call depth requires tracing the request.
+    pub deep_call_gas_ceiling: Option<DeepCallGasCeiling>,
+    /// The maximum number of new storage slots a transaction may create.
+    ///
+    /// No [`StateGrowthProbe`] is provided, as the new storage slots require tracing the request,
+    /// e.g. with a prestate trace in diff mode.
+    pub state_growth_limit: Option<StateGrowthLimit>,
+    /// The base fee used if fetching the current fees fails, in wei.
+    ///
+    /// If `None`, requests are rejected when the fees cannot be fetched.
+    pub fallback_base_fee: Option<u128>,
+    /// How the priority fee per gas of sponsored transactions is determined.
+    ///
+    /// Defaults to a fixed 1 gwei.
+    pub priority_fee: PriorityFee,
+    /// Whether a gas limit set by the client is honored if it is below the estimate.
+    ///
+    /// The transaction is then signed with the lower limit, and may run out of gas, e.g. to force
+    /// a revert instead of an expensive path. Gas limits above the estimate are rejected. If
+    /// `false`, the gas limit is always set to the estimate.
+    pub honor_lower_gas_limit: bool,
+    /// The value a request whose estimate reverts is estimated again with, to detect calls that
+    /// require a non-zero value.
+    ///
+    /// If the estimate then succeeds, the request is rejected with
+    /// [`DestinationRequiresValue`](crate::OdysseyWalletError::DestinationRequiresValue) instead
+    /// of the revert. The signer must hold at least this value. If `None`, reverts are returned
+    /// as is.
+    pub value_requirement_probe: Option<U256>,
+    /// Checks the fee of sponsored transactions against the minimum accepted by the pool before
+    /// they are signed.
+    ///
+    /// If `None`, the fee is derived from the base fee at estimation, which may fall below the
+    /// minimum if the base fee rises quickly.
+    pub minimum_fee_check: Option<MinimumFeeCheck>,
+    /// The store the sponsorship budget, the spending cap, the destination quotas and the account
+    /// rate limits are persisted to, so they survive restarts.
+    ///
+    /// The state is restored from the store when the wallet is constructed, and saved by
+    /// [`OdysseyWallet::persist_state`](crate::OdysseyWallet::persist_state). If `None`, the state
+    /// resets on every restart.
+    pub state_store: Option<Arc<dyn StateStore>>,
+    /// The maximum age of the fetched fee data, and how stale fee data is handled.
+    ///
+    /// If `None`, the fee data is trusted regardless of its age.
+    pub fee_data_max_age: Option<FeeDataMaxAge>,
+    /// A candidate configuration each `wallet_sendTransaction` request is also evaluated against,
+    /// without affecting the live decision.
+    ///
+    /// Requests the two configurations decide differently on are logged and counted. The
+    /// candidate is evaluated up to the gas ceiling, estimating the request again, so requests
+    /// rejected by later checks, e.g. the sponsorship budget, count as accepted by it. Its own
+    /// shadow configuration is ignored.
+    pub shadow_config: Option<Box<Self>>,
+    /// Whether to register the metrics of the wallet.
+    ///
+    /// If `false`, or if registering the metrics fails, the wallet records no metrics. This is
+    /// only consulted when the wallet is constructed. Defaults to `true`.
+    pub metrics_enabled: bool,
+}
+
+impl Default for OdysseyWalletConfig {
+    fn default() -> Self {
+        Self {
+            gas_ceiling: DEFAULT_GAS_CEILING,
+            gas_buffer_percent: DEFAULT_GAS_BUFFER_PERCENT,
+            max_calldata_bytes: DEFAULT_MAX_CALLDATA_BYTES,
+            allowed_selectors: None,
+            blocked_selectors: HashSet::new(),
+            adaptive_gas_ceiling: None,
+            gas_ceiling_overrides: GasCeilingOverrides::default(),
+            delegation_whitelist: None,
+            duplicate_whitelist_entries: DuplicateWhitelistEntries::default(),
+            whitelist_registry: None,
+            whitelist_sources: WhitelistSources::default(),
+            system_addresses: SystemAddresses::default(),
+            blocked_addresses: HashSet::new(),
+            authorization_nonce_tolerance: None,
+            methods: MethodFlags::default(),
+            risk_oracle: Arc::new(NoopRiskOracle),
+            verification_oracle: Arc::new(NoopVerificationOracle),
+            attestation_verifier: Arc::new(NoopAttestationVerifier),
+            submission_batch_window: None,
+            submission_jitter: None,
+            confirmation_tracking: None,
+            permit_acquire_timeout: None,
+            destroyed_target_check: None,
+            min_delegation_target_age: None,
+            proxy_delegations: None,
+            connection_rate_limit: None,
+            account_rate_limit: None,
+            global_rate_limit: Some(GlobalRateLimitConfig::default()),
+            estimation_limit: None,
+            delegation_cohort: None,
+            gas_per_calldata_byte: None,
+            require_synced: false,
+            reject_delegated_state_conflicts: false,
+            max_signer_nonces_per_minute: None,
+            sponsorship_start_block: None,
+            maintenance: None,
+            max_batch_calldata_bytes: None,
+            max_tx_bytes: None,
+            max_authorization_list_bytes: None,
+            supports_7702: true,
+            reject_universal_authorizations: false,
+            allow_revocation: false,
+            contradictory_authorizations: None,
+            sponsorship_budget: None,
+            spending_cap: None,
+            min_sponsor_balance: None,
+            budget_alerts: None,
+            admin_api: false,
+            operator_api_key: None,
+            destination_quotas: None,
+            value_allowances: None,
+            pending_delegation_tracking: None,
+            forwards_to_sequencer: false,
+            pattern_detection: None,
+            deep_call_gas_ceiling: None,
+            state_growth_limit: None,
+            fallback_base_fee: None,
+            priority_fee: PriorityFee::Fixed(DEFAULT_PRIORITY_FEE),
+            honor_lower_gas_limit: false,
+            value_requirement_probe: None,
+            minimum_fee_check: None,
+            state_store: None,
+            fee_data_max_age: None,
+            shadow_config: None,
+            metrics_enabled: true,
+        }
+    }
+}
+
+impl OdysseyWalletConfig {
+    /// Sets the static gas ceiling.
+    pub const fn with_gas_ceiling(mut self, ceiling: u64) -> Self {
+        self.gas_ceiling = ceiling;
+        self
+    }
+
+    /// Sets the buffer on top of the gas estimate, in percent.
+    pub const fn with_gas_buffer_percent(mut self, percent: u64) -> Self {
+        self.gas_buffer_percent = percent;
+        self
+    }
+
+    /// Sets the maximum calldata of a request, in bytes.
+    pub const fn with_max_calldata_bytes(mut self, max: usize) -> Self {
+        self.max_calldata_bytes = max;
+        self
+    }
+
+    /// Only sponsors requests calling one of the given function selectors.
+    pub fn with_allowed_selectors(mut self, selectors: impl IntoIterator<Item = [u8; 4]>) -> Self {
+        self.allowed_selectors = Some(selectors.into_iter().collect());
+        self
+    }
+
+    /// Rejects requests calling any of the given function selectors.
+    pub fn with_blocked_selectors(mut self, selectors: impl IntoIterator<Item = [u8; 4]>) -> Self {
+        self.blocked_selectors = selectors.into_iter().collect();
+        self
+    }
+
+    /// Enables the adaptive gas ceiling with the given settings.
+    pub const fn with_adaptive_gas_ceiling(mut self, config: AdaptiveGasCeilingConfig) -> Self {
+        self.adaptive_gas_ceiling = Some(config);
+        self
+    }
+
+    /// Sets the gas ceilings for specific destinations and functions.
+    pub fn with_gas_ceiling_overrides(mut self, overrides: GasCeilingOverrides) -> Self {
+        self.gas_ceiling_overrides = overrides;
+        self
+    }
+
+    /// Only sponsors delegations to the addresses of the capability.
+    pub fn with_delegation_whitelist(mut self, capability: DelegationCapability) -> Self {
+        self.delegation_whitelist = Some(capability);
+        self
+    }
+
+    /// Sets how duplicate entries in the delegation whitelist are handled.
+    pub const fn with_duplicate_whitelist_entries(
+        mut self,
+        handling: DuplicateWhitelistEntries,
+    ) -> Self {
+        self.duplicate_whitelist_entries = handling;
+        self
+    }
+
+    /// Loads the delegation whitelist from the registry at `address`, read every
+    /// `refresh_interval`, instead of the configured whitelist.
+    pub const fn with_whitelist_registry(
+        mut self,
+        address: Address,
+        refresh_interval: Duration,
+    ) -> Self {
+        self.whitelist_registry = Some(WhitelistRegistryConfig { address, refresh_interval });
+        self
+    }
+
+    /// Sets how the configured whitelist is combined with the list read from the registry.
+    pub const fn with_whitelist_sources(mut self, sources: WhitelistSources) -> Self {
+        self.whitelist_sources = sources;
+        self
+    }
+
+    /// Sets the precompile and system addresses that transactions may not target.
+    pub fn with_system_addresses(mut self, system_addresses: SystemAddresses) -> Self {
+        self.system_addresses = system_addresses;
+        self
+    }
+
+    /// Bans the given accounts from sponsorship.
+    pub fn with_blocked_addresses(mut self, addresses: impl IntoIterator<Item = Address>) -> Self {
+        self.blocked_addresses = addresses.into_iter().collect();
+        self
+    }
+
+    /// Enables the authorization nonce window check with the given tolerance.
+    pub const fn with_authorization_nonce_tolerance(mut self, tolerance: u64) -> Self {
+        self.authorization_nonce_tolerance = Some(tolerance);
+        self
+    }
+
+    /// Sets the methods of the `wallet_` namespace that are enabled.
+    pub const fn with_methods(mut self, methods: MethodFlags) -> Self {
+        self.methods = methods;
+        self
+    }
+
+    /// Enables batched submission with the given window.
+    pub const fn with_submission_batch_window(mut self, window: Duration) -> Self {
+        self.submission_batch_window = Some(window);
+        self
+    }
+
+    /// Delays each submission by a random duration of up to `max_jitter`.
+    pub const fn with_submission_jitter(mut self, max_jitter: Duration) -> Self {
+        self.submission_jitter = Some(max_jitter);
+        self
+    }
+
+    /// Tracks submitted transactions until they are included, requesting their receipt every
+    /// `poll_interval` for up to `timeout`.
+    pub const fn with_confirmation_tracking(
+        mut self,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Self {
+        self.confirmation_tracking = Some(ConfirmationTracking { poll_interval, timeout });
+        self
+    }
+
+    /// Rejects requests that wait longer than `timeout` for the signing permit.
+    pub const fn with_permit_acquire_timeout(mut self, timeout: Duration) -> Self {
+        self.permit_acquire_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the [`RiskOracle`] consulted for delegation targets.
+    pub fn with_risk_oracle(mut self, risk_oracle: Arc<dyn RiskOracle>) -> Self {
+        self.risk_oracle = risk_oracle;
+        self
+    }
+
+    /// Sets the [`VerificationOracle`] consulted for delegation targets.
+    pub fn with_verification_oracle(
+        mut self,
+        verification_oracle: Arc<dyn VerificationOracle>,
+    ) -> Self {
+        self.verification_oracle = verification_oracle;
+        self
+    }
+
+    /// Sets the [`AttestationVerifier`] consulted for the accounts requests are sponsored for.
+    pub fn with_attestation_verifier(
+        mut self,
+        attestation_verifier: Arc<dyn AttestationVerifier>,
+    ) -> Self {
+        self.attestation_verifier = attestation_verifier;
+        self
+    }
+
+    /// Persists the sponsorship budget, the spending cap, the destination quotas and the account
+    /// rate limits to the given [`StateStore`].
+    pub fn with_state_store(mut self, state_store: Arc<dyn StateStore>) -> Self {
+        self.state_store = Some(state_store);
+        self
+    }
+
+    /// Enables rejection of destroyed delegation targets with the given settings.
+    pub const fn with_destroyed_target_check(mut self, check: DestroyedTargetCheck) -> Self {
+        self.destroyed_target_check = Some(check);
+        self
+    }
+
+    /// Rejects delegation targets deployed less than `min_age_blocks` blocks ago.
+    pub const fn with_min_delegation_target_age(mut self, min_age_blocks: u64) -> Self {
+        self.min_delegation_target_age = Some(min_age_blocks);
+        self
+    }
+
+    /// Applies the policy to requests to accounts delegating to an upgradeable proxy.
+    pub const fn with_proxy_delegations(mut self, policy: ProxyDelegationPolicy) -> Self {
+        self.proxy_delegations = Some(policy);
+        self
+    }
+
+    /// Sets the limit on requests across all callers, or disables it if `None`.
+    pub const fn with_global_rate_limit(mut self, config: Option<GlobalRateLimitConfig>) -> Self {
+        self.global_rate_limit = config;
+        self
+    }
+
+    /// Bounds the number of concurrent gas estimations to `max_concurrent`, handling excess
+    /// estimations according to `on_saturated`.
+    pub const fn with_estimation_limit(
+        mut self,
+        max_concurrent: usize,
+        on_saturated: SaturatedEstimations,
+    ) -> Self {
+        self.estimation_limit = Some(EstimationLimit { max_concurrent, on_saturated });
+        self
+    }
+
+    /// Enables the per-connection rate limit with the given settings.
+    pub const fn with_connection_rate_limit(mut self, config: RateLimitConfig) -> Self {
+        self.connection_rate_limit = Some(config);
+        self
+    }
+
+    /// Enables the per-account rate limit with the given settings.
+    pub const fn with_account_rate_limit(mut self, config: RateLimitConfig) -> Self {
+        self.account_rate_limit = Some(config);
+        self
+    }
+
+    /// Only sponsors transactions to accounts first delegated within the given blocks.
+    pub const fn with_delegation_cohort(mut self, cohort: RangeInclusive<BlockNumber>) -> Self {
+        self.delegation_cohort = Some(cohort);
+        self
+    }
+
+    /// Rejects requests whose gas estimate per byte of calldata falls outside the given band.
+    pub const fn with_gas_per_calldata_byte(mut self, band: RangeInclusive<u64>) -> Self {
+        self.gas_per_calldata_byte = Some(band);
+        self
+    }
+
+    /// Rejects `wallet_sendTransaction` while the node is syncing.
+    pub const fn with_require_synced(mut self) -> Self {
+        self.require_synced = true;
+        self
+    }
+
+    /// Rejects EIP-1559 calls to delegated accounts that have transactions pending in the pool.
+    pub const fn with_delegated_state_conflict_check(mut self) -> Self {
+        self.reject_delegated_state_conflicts = true;
+        self
+    }
+
+    /// Limits the number of nonces assigned to the signer per minute.
+    pub const fn with_max_signer_nonces_per_minute(mut self, max: u32) -> Self {
+        self.max_signer_nonces_per_minute = Some(max);
+        self
+    }
+
+    /// Rejects `wallet_sendTransaction` until the chain reaches the given block.
+    pub const fn with_sponsorship_start_block(mut self, block: BlockNumber) -> Self {
+        self.sponsorship_start_block = Some(block);
+        self
+    }
+
+    /// Pauses sponsorship during the windows of the maintenance schedule.
+    pub fn with_maintenance_schedule(mut self, schedule: MaintenanceSchedule) -> Self {
+        self.maintenance = Some(schedule);
+        self
+    }
+
+    /// Limits the combined calldata of all calls in a batch.
+    pub const fn with_max_batch_calldata_bytes(mut self, max: usize) -> Self {
+        self.max_batch_calldata_bytes = Some(max);
+        self
+    }
+
+    /// Limits the size of the signed transaction.
+    pub const fn with_max_tx_bytes(mut self, max: usize) -> Self {
+        self.max_tx_bytes = Some(max);
+        self
+    }
+
+    /// Sets the maximum size of the RLP encoded authorization list of a request.
+    pub const fn with_max_authorization_list_bytes(mut self, max: usize) -> Self {
+        self.max_authorization_list_bytes = Some(max);
+        self
+    }
+
+    /// Sets whether EIP-7702 is active on the chain.
+    pub const fn with_supports_7702(mut self, supports_7702: bool) -> Self {
+        self.supports_7702 = supports_7702;
+        self
+    }
+
+    /// Rejects universal EIP-7702 authorizations.
+    pub const fn with_reject_universal_authorizations(mut self) -> Self {
+        self.reject_universal_authorizations = true;
+        self
+    }
+
+    /// Sponsors EIP-7702 authorizations that revoke the delegation of the authority.
+    pub const fn with_allow_revocation(mut self) -> Self {
+        self.allow_revocation = true;
+        self
+    }
+
+    /// Handles authorization lists that delegate an authority to different targets according to
+    /// `handling`.
+    pub const fn with_contradictory_authorizations(
+        mut self,
+        handling: ContradictoryAuthorizations,
+    ) -> Self {
+        self.contradictory_authorizations = Some(handling);
+        self
+    }
+
+    /// Honors gas limits set by the client that are below the estimate.
+    pub const fn with_honor_lower_gas_limit(mut self) -> Self {
+        self.honor_lower_gas_limit = true;
+        self
+    }
+
+    /// Estimates requests that revert again with `probe` as value, to detect calls that require a
+    /// non-zero value.
+    pub const fn with_value_requirement_probe(mut self, probe: U256) -> Self {
+        self.value_requirement_probe = Some(probe);
+        self
+    }
+
+    /// Ensures the max fee per gas is at least the current base fee and `floor`, handling lower
+    /// fees according to `on_below`.
+    pub const fn with_minimum_fee_check(mut self, floor: u128, on_below: BelowMinimumFee) -> Self {
+        self.minimum_fee_check = Some(MinimumFeeCheck { floor, on_below });
+        self
+    }
+
+    /// Enables the sponsorship budget with the given settings.
+    pub const fn with_sponsorship_budget(mut self, config: SponsorshipBudgetConfig) -> Self {
+        self.sponsorship_budget = Some(config);
+        self
+    }
+
+    /// Caps the funds spent on sponsorship to `limit` wei per `window`.
+    pub const fn with_spending_cap(mut self, limit: U256, window: Duration) -> Self {
+        self.spending_cap = Some(SpendingCapConfig { limit, window });
+        self
+    }
+
+    /// Pauses sponsorship while the balance of the sponsor is below `minimum` wei.
+    pub const fn with_min_sponsor_balance(mut self, minimum: U256) -> Self {
+        self.min_sponsor_balance = Some(minimum);
+        self
+    }
+
+    /// Pushes alerts on the sponsorship budget to the subscribers of the given channel.
+    pub fn with_budget_alerts(mut self, alerts: BudgetAlerts) -> Self {
+        self.budget_alerts = Some(alerts);
+        self
+    }
+
+    /// Enables the methods of the `admin_` namespace.
+    pub const fn with_admin_api(mut self) -> Self {
+        self.admin_api = true;
+        self
+    }
+
+    /// Enables `wallet_auditLog` for operators authenticating with the key.
+    pub fn with_operator_api_key(mut self, key: OperatorApiKey) -> Self {
+        self.operator_api_key = Some(key);
+        self
+    }
+
+    /// Limits the sponsored calls to the delegation target to `quota` per 24 hours.
+    pub fn with_destination_quota(mut self, target: Address, quota: u32) -> Self {
+        self.destination_quotas.get_or_insert_with(Default::default).insert(target, quota);
+        self
+    }
+
+    /// Allows transactions to accounts delegated to `target` to carry a value of up to `allowance`
+    /// wei.
+    pub fn with_value_allowance(mut self, target: Address, allowance: U256) -> Self {
+        self.value_allowances.get_or_insert_with(Default::default).insert(target, allowance);
+        self
+    }
+
+    /// Tracks the delegation of each authority for `expiry` after its submission, handling
+    /// conflicting delegations according to `on_conflict`.
+    ///
+    /// At most `max_tracked` authorities are tracked.
+    pub const fn with_pending_delegation_tracking(
+        mut self,
+        expiry: Duration,
+        max_tracked: usize,
+        on_conflict: ConflictingDelegations,
+    ) -> Self {
+        self.pending_delegation_tracking =
+            Some(PendingDelegationTracking { expiry, max_tracked, on_conflict });
+        self
+    }
+
+    /// Sets whether the node forwards transactions to a sequencer.
+    pub const fn with_forwards_to_sequencer(mut self, forwards_to_sequencer: bool) -> Self {
+        self.forwards_to_sequencer = forwards_to_sequencer;
+        self
+    }
+
+    /// Rejects requests that the detector scores at or above `threshold`.
+    pub fn with_pattern_detection(
+        mut self,
+        detector: Arc<dyn PatternDetector>,
+        threshold: f64,
+    ) -> Self {
+        self.pattern_detection = Some(PatternDetection { detector, threshold });
+        self
+    }
+
+    /// Applies `ceiling` to requests whose call depth, as reported by the probe, exceeds
+    /// `max_depth`.
+    pub fn with_deep_call_gas_ceiling(
+        mut self,
+        probe: Arc<dyn CallDepthProbe>,
+        max_depth: u64,
+        ceiling: u64,
+    ) -> Self {
+        self.deep_call_gas_ceiling = Some(DeepCallGasCeiling { probe, max_depth, ceiling });
+        self
+    }
+
+    /// Rejects requests that create more than `max_new_slots` new storage slots, as reported by
+    /// the probe.
+    pub fn with_state_growth_limit(
+        mut self,
+        probe: Arc<dyn StateGrowthProbe>,
+        max_new_slots: u64,
+    ) -> Self {
+        self.state_growth_limit = Some(StateGrowthLimit { probe, max_new_slots });
+        self
+    }
+
+    /// Evaluates requests against a candidate configuration, without affecting live decisions.
+    pub fn with_shadow_config(mut self, config: Self) -> Self {
+        self.shadow_config = Some(Box::new(config));
+        self
+    }
+
+    /// Sets whether the metrics of the wallet are registered.
+    pub const fn with_metrics(mut self, enabled: bool) -> Self {
+        self.metrics_enabled = enabled;
+        self
+    }
+
+    /// Sets the base fee used if fetching the current fees fails.
+    pub const fn with_fallback_base_fee(mut self, base_fee: u128) -> Self {
+        self.fallback_base_fee = Some(base_fee);
+        self
+    }
+
+    /// Sets how the priority fee per gas of sponsored transactions is determined.
+    pub const fn with_priority_fee(mut self, priority_fee: PriorityFee) -> Self {
+        self.priority_fee = priority_fee;
+        self
+    }
+
+    /// Handles fee data derived from a block older than `max_age` according to `on_stale`.
+    pub const fn with_fee_data_max_age(
+        mut self,
+        max_age: Duration,
+        on_stale: StaleFeeDataPolicy,
+    ) -> Self {
+        self.fee_data_max_age = Some(FeeDataMaxAge { max_age, on_stale });
+        self
+    }
+
+    /// Returns the validation rules enforced with this configuration, with their key parameters.
+    ///
+    /// Each rule is reported as `name` or `name=parameters`, e.g. `gas_ceiling=350000`. The
+    /// configured [`RiskOracle`], [`VerificationOracle`] and [`AttestationVerifier`] are opaque,
+    /// and are not listed.
+    pub fn active_rules(&self) -> Vec<String> {
+        let mut rules = vec!["value=zero".to_string()];
+
+        match &self.adaptive_gas_ceiling {
+            Some(adaptive) => rules.push(format!(
+                "gas_ceiling=adaptive(p{}+{}%, {}..={})",
+                adaptive.percentile, adaptive.margin_percent, adaptive.floor, adaptive.cap
+            )),
+            None => rules.push(format!("gas_ceiling={}", self.gas_ceiling)),
+        }
+        if self.gas_buffer_percent > 0 {
+            rules.push(format!("gas_buffer={}%", self.gas_buffer_percent));
+        }
+        rules.push(format!("max_calldata_bytes={}", self.max_calldata_bytes));
+        if let Some(allowed) = &self.allowed_selectors {
+            rules.push(format!("allowed_selectors={}", allowed.len()));
+        }
+        if !self.blocked_selectors.is_empty() {
+            rules.push(format!("blocked_selectors={}", self.blocked_selectors.len()));
+        }
+        if let Some(registry) = &self.whitelist_registry {
+            rules.push(format!(
+                "whitelist_registry={} every {}s",
+                registry.address,
+                registry.refresh_interval.as_secs()
+            ));
+        }
+        if let Some(whitelist) = &self.delegation_whitelist {
+            rules.push(format!(
+                "whitelist={} addresses",
+                whitelist.addresses.iter().collect::<HashSet<_>>().len()
+            ));
+        }
+        if self.whitelist_registry.is_some() && self.delegation_whitelist.is_some() {
+            let sources = match self.whitelist_sources {
+                WhitelistSources::Union => "union",
+                WhitelistSources::Intersection => "intersection",
+                WhitelistSources::RegistryOverride => "registry_override",
+            };
+            rules.push(format!("whitelist_sources={sources}"));
+        }
+        if let Some(deep) = &self.deep_call_gas_ceiling {
+            rules.push(format!(
+                "deep_call_gas_ceiling={} above depth {}",
+                deep.ceiling, deep.max_depth
+            ));
+        }
+        if let Some(growth) = &self.state_growth_limit {
+            rules.push(format!("max_new_storage_slots={}", growth.max_new_slots));
+        }
+        let overrides = &self.gas_ceiling_overrides;
+        if !overrides.destinations.is_empty() || !overrides.selectors.is_empty() {
+            rules.push(format!(
+                "gas_ceiling_overrides={} destinations, {} selectors",
+                overrides.destinations.len(),
+                overrides.selectors.len()
+            ));
+        }
+        if self.system_addresses != SystemAddresses::none() {
+            rules.push("system_addresses".to_string());
+        }
+        if !self.blocked_addresses.is_empty() {
+            rules.push(format!("blocked_addresses={}", self.blocked_addresses.len()));
+        }
+        if !self.supports_7702 {
+            rules.push("eip7702=disabled".to_string());
+        }
+        if self.reject_universal_authorizations {
+            rules.push("reject_universal_authorizations".to_string());
+        }
+        if self.allow_revocation {
+            rules.push("allow_revocation".to_string());
+        }
+        if let Some(handling) = self.contradictory_authorizations {
+            let handling = match handling {
+                ContradictoryAuthorizations::Reject => "reject",
+                ContradictoryAuthorizations::Prune => "prune",
+            };
+            rules.push(format!("contradictory_authorizations={handling}"));
+        }
+        if let Some(tolerance) = self.authorization_nonce_tolerance {
+            rules.push(format!("authorization_nonce_tolerance={tolerance}"));
+        }
+        if let Some(check) = &self.destroyed_target_check {
+            rules.push(format!("destroyed_target_check={} blocks", check.lookback_blocks));
+        }
+        if let Some(min_age) = self.min_delegation_target_age {
+            rules.push(format!("min_delegation_target_age={min_age} blocks"));
+        }
+        if let Some(policy) = &self.proxy_delegations {
+            if let Some(ceiling) = policy.gas_ceiling {
+                rules.push(format!("proxy_gas_ceiling={ceiling}"));
+            }
+            if policy.require_whitelisted_implementation {
+                rules.push("proxy_implementation_whitelisted".to_string());
+            }
+        }
+        if let Some(cohort) = &self.delegation_cohort {
+            rules.push(format!("delegation_cohort={}..={}", cohort.start(), cohort.end()));
+        }
+        if let Some(band) = &self.gas_per_calldata_byte {
+            rules.push(format!("gas_per_calldata_byte={}..={}", band.start(), band.end()));
+        }
+        if let Some(rate_limit) = &self.global_rate_limit {
+            rules.push(format!(
+                "global_rate_limit={}/s (burst {})",
+                rate_limit.requests_per_second, rate_limit.burst
+            ));
+        }
+        if let Some(limit) = &self.estimation_limit {
+            let on_saturated = match limit.on_saturated {
+                SaturatedEstimations::Queue => "queue",
+                SaturatedEstimations::Reject => "reject",
+            };
+            rules.push(format!("estimation_limit={} ({on_saturated})", limit.max_concurrent));
+        }
+        if let Some(rate_limit) = &self.connection_rate_limit {
+            rules.push(format!(
+                "connection_rate_limit={}/{}s",
+                rate_limit.max_requests,
+                rate_limit.window.as_secs()
+            ));
+            if let Some(backoff) = &rate_limit.backoff {
+                rules.push(format!(
+                    "rate_limit_backoff={}s..={}s",
+                    backoff.base.as_secs(),
+                    backoff.max.as_secs()
+                ));
+            }
+        }
+        if let Some(rate_limit) = &self.account_rate_limit {
+            rules.push(format!(
+                "account_rate_limit={}/{}s",
+                rate_limit.max_requests,
+                rate_limit.window.as_secs()
+            ));
+        }
+        if self.require_synced {
+            rules.push("require_synced".to_string());
+        }
+        if let Some(max) = self.max_signer_nonces_per_minute {
+            rules.push(format!("max_signer_nonces_per_minute={max}"));
+        }
+        if self.reject_delegated_state_conflicts {
+            rules.push("reject_delegated_state_conflicts".to_string());
+        }
+        if let Some(block) = self.sponsorship_start_block {
+            rules.push(format!("sponsorship_start_block={block}"));
+        }
+        if let Some(maintenance) = &self.maintenance {
+            rules.push(format!("maintenance_windows={}", maintenance.windows.len()));
+        }
+        if let Some(max) = self.max_batch_calldata_bytes {
+            rules.push(format!("max_batch_calldata_bytes={max}"));
+        }
+        if let Some(max) = self.max_tx_bytes {
+            rules.push(format!("max_tx_bytes={max}"));
+        }
+        if let Some(max) = self.max_authorization_list_bytes {
+            rules.push(format!("max_authorization_list_bytes={max}"));
+        }
+        if let Some(budget) = &self.sponsorship_budget {
+            rules.push(format!(
+                "sponsorship_budget={} wei, {} wei/s",
+                budget.capacity, budget.refill_per_second
+            ));
+        }
+        if let Some(cap) = &self.spending_cap {
+            rules.push(format!("spending_cap={} wei/{}s", cap.limit, cap.window.as_secs()));
+        }
+        if let Some(minimum) = self.min_sponsor_balance {
+            rules.push(format!("min_sponsor_balance={minimum} wei"));
+        }
+        if let Some(quotas) = &self.destination_quotas {
+            rules.push(format!("destination_quotas={} targets", quotas.len()));
+        }
+        if let Some(allowances) = &self.value_allowances {
+            rules.push(format!("value_allowances={} targets", allowances.len()));
+        }
+        if let Some(tracking) = &self.pending_delegation_tracking {
+            let on_conflict = match tracking.on_conflict {
+                ConflictingDelegations::Reject => "reject",
+                ConflictingDelegations::Replace => "replace",
+            };
+            rules.push(format!(
+                "conflicting_delegations={on_conflict} within {}s",
+                tracking.expiry.as_secs()
+            ));
+        }
+        if let Some(detection) = &self.pattern_detection {
+            rules.push(format!("pattern_detection=threshold {}", detection.threshold));
+        }
+        if let Some(check) = &self.minimum_fee_check {
+            let on_below = match check.on_below {
+                BelowMinimumFee::Reject => "reject",
+                BelowMinimumFee::Bump => "bump",
+            };
+            rules.push(format!("minimum_fee={} wei ({on_below})", check.floor));
+        }
+        match self.priority_fee {
+            PriorityFee::Fixed(DEFAULT_PRIORITY_FEE) => {}
+            PriorityFee::Fixed(fee) => rules.push(format!("priority_fee={fee} wei")),
+            PriorityFee::Dynamic { min, max } => {
+                rules.push(format!("priority_fee=dynamic {min}..={max} wei"));
+            }
+        }
+        if self.honor_lower_gas_limit {
+            rules.push("honor_lower_gas_limit".to_string());
+        }
+        if let Some(probe) = self.value_requirement_probe {
+            rules.push(format!("value_requirement_probe={probe} wei"));
+        }
+        if let Some(timeout) = self.permit_acquire_timeout {
+            rules.push(format!("permit_acquire_timeout={}ms", timeout.as_millis()));
+        }
+        if let Some(fee_data) = &self.fee_data_max_age {
+            let on_stale = match fee_data.on_stale {
+                StaleFeeDataPolicy::Reject => "reject".to_string(),
+                StaleFeeDataPolicy::Buffer { percent } => format!("buffer {percent}%"),
+            };
+            rules.push(format!("fee_data_max_age={}s ({on_stale})", fee_data.max_age.as_secs()));
+        }
+        for method in [WalletMethod::SendTransaction, WalletMethod::EstimateSponsorship] {
+            if !self.methods.is_enabled(method) {
+                rules.push(format!("disabled_method={}", method.name()));
+            }
+        }
+
+        rules
+    }
+
+    /// Ensures the settings are consistent.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if let (Some(whitelist), DuplicateWhitelistEntries::Reject) =
+            (&self.delegation_whitelist, self.duplicate_whitelist_entries)
+        {
+            let duplicates = duplicates(&whitelist.addresses);
+            if !duplicates.is_empty() {
+                return Err(ConfigError::DuplicateWhitelistEntries(duplicates));
+            }
+        }
+        if let Some(adaptive) = &self.adaptive_gas_ceiling {
+            if !(1..=100).contains(&adaptive.percentile) {
+                return Err(ConfigError::InvalidPercentile(adaptive.percentile));
+            }
+            if adaptive.floor > adaptive.cap {
+                return Err(ConfigError::FloorAboveCap {
+                    floor: adaptive.floor,
+                    cap: adaptive.cap,
+                });
+            }
+        }
+        for rate_limit in
+            [&self.connection_rate_limit, &self.account_rate_limit].into_iter().flatten()
+        {
+            if rate_limit.max_requests == 0
+                || rate_limit.window.is_zero()
+                || rate_limit.max_tracked == 0
+            {
+                return Err(ConfigError::EmptyRateLimit);
+            }
+        }
+        if self
+            .global_rate_limit
+            .is_some_and(|rate_limit| rate_limit.requests_per_second == 0 || rate_limit.burst == 0)
+        {
+            return Err(ConfigError::EmptyGlobalRateLimit);
+        }
+        if self.estimation_limit.is_some_and(|limit| limit.max_concurrent == 0) {
+            return Err(ConfigError::EmptyEstimationLimit);
+        }
+        if let PriorityFee::Dynamic { min, max } = self.priority_fee {
+            if min > max {
+                return Err(ConfigError::PriorityFeeMinAboveMax { min, max });
+            }
+        }
+        if self.max_signer_nonces_per_minute == Some(0) {
+            return Err(ConfigError::EmptySignerNonceRate);
+        }
+        if self.delegation_cohort.as_ref().is_some_and(RangeInclusive::is_empty) {
+            return Err(ConfigError::EmptyCohort);
+        }
+        if self.gas_per_calldata_byte.as_ref().is_some_and(RangeInclusive::is_empty) {
+            return Err(ConfigError::EmptyGasProfileBand);
+        }
+        if let Some(window) = self
+            .maintenance
+            .iter()
+            .flat_map(|maintenance| &maintenance.windows)
+            .find(|window| !window.is_valid())
+        {
+            return Err(ConfigError::InvalidMaintenanceWindow(*window));
+        }
+        if self.spending_cap.is_some_and(|cap| cap.window.is_zero()) {
+            return Err(ConfigError::ZeroSpendingCapWindow);
+        }
+        if self.whitelist_registry.is_some_and(|registry| registry.refresh_interval.is_zero()) {
+            return Err(ConfigError::ZeroRegistryRefreshInterval);
+        }
+        if self.confirmation_tracking.is_some_and(|tracking| tracking.poll_interval.is_zero()) {
+            return Err(ConfigError::ZeroConfirmationPollInterval);
+        }
+        if self
+            .pending_delegation_tracking
+            .is_some_and(|tracking| tracking.expiry.is_zero() || tracking.max_tracked == 0)
+        {
+            return Err(ConfigError::EmptyPendingDelegationTracking);
+        }
+        if let Some(shadow) = &self.shadow_config {
+            shadow.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// Errors returned for an inconsistent [`OdysseyWalletConfig`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ConfigError {
+    /// The delegation whitelist contains duplicate entries.
+    #[error("delegation whitelist contains duplicates: {0:?}")]
+    DuplicateWhitelistEntries(Vec<Address>),
+    /// The percentile of the adaptive gas ceiling is not in `1..=100`.
+    #[error("adaptive gas ceiling percentile {0} is not in 1..=100")]
+    InvalidPercentile(u8),
+    /// The floor of the adaptive gas ceiling exceeds its cap.
+    #[error("adaptive gas ceiling floor {floor} exceeds its cap {cap}")]
+    FloorAboveCap {
+        /// The configured floor.
+        floor: u64,
+        /// The configured cap.
+        cap: u64,
+    },
+    /// The connection or account rate limit does not allow any requests.
+    #[error("rate limit must allow requests over a non-zero window")]
+    EmptyRateLimit,
+    /// The global rate limit does not allow any requests.
+    #[error("global rate limit must allow requests")]
+    EmptyGlobalRateLimit,
+    /// The estimation limit does not allow any estimations.
+    #[error("estimation limit must allow estimations")]
+    EmptyEstimationLimit,
+    /// The minimum of the dynamic priority fee exceeds its maximum.
+    #[error("dynamic priority fee minimum {min} exceeds its maximum {max}")]
+    PriorityFeeMinAboveMax {
+        /// The configured minimum.
+        min: u128,
+        /// The configured maximum.
+        max: u128,
+    },
+    /// The signer nonce rate limit does not allow any nonces.
+    #[error("signer nonce rate limit must allow nonces")]
+    EmptySignerNonceRate,
+    /// The delegation cohort contains no blocks.
+    #[error("delegation cohort is empty")]
+    EmptyCohort,
+    /// The band of accepted gas per calldata byte is empty.
+    #[error("gas per calldata byte band is empty")]
+    EmptyGasProfileBand,
+    /// The whitelist registry is set to be read continuously.
+    #[error("whitelist registry refresh interval must be non-zero")]
+    ZeroRegistryRefreshInterval,
+    /// Receipts of tracked transactions are set to be requested continuously.
+    #[error("confirmation tracking poll interval must be non-zero")]
+    ZeroConfirmationPollInterval,
+    /// Pending delegations are set to not be tracked at all.
+    #[error("pending delegation tracking must track delegations for a non-zero expiry")]
+    EmptyPendingDelegationTracking,
+    /// The spending cap is set to reset continuously.
+    #[error("spending cap window must be non-zero")]
+    ZeroSpendingCapWindow,
+    /// A maintenance window is empty, or recurs before it ends.
+    #[error("invalid maintenance window: {0:?}")]
+    InvalidMaintenanceWindow(MaintenanceWindow),
+}
+
+/// The configuration in effect, and the state derived from it.
+///
+/// Requests operate on a snapshot of this, so a reconfiguration never takes effect halfway
+/// through a request.
+#[derive(Debug)]
+pub(crate) struct ActiveConfig {
+    /// The wallet configuration.
+    pub(crate) config: OdysseyWalletConfig,
+    /// The deduplicated delegation whitelist, if enabled.
+    pub(crate) whitelist: Option<Arc<DelegationWhitelist>>,
+    /// The whitelist read from the registry, if enabled.
+    pub(crate) registry_whitelist: Option<Arc<RegistryWhitelist>>,
+    /// The adaptive gas ceiling, if enabled.
+    pub(crate) adaptive_gas_ceiling: Option<Arc<AdaptiveGasCeiling>>,
+    /// Cached results of the destroyed delegation target check, if enabled.
+    pub(crate) destroyed_targets: Option<Arc<DestroyedTargets>>,
+    /// Delegation targets known to be old enough, if enabled.
+    pub(crate) target_ages: Option<Arc<TargetAges>>,
+    /// The sponsorship budget, if enabled.
+    pub(crate) budget: Option<Arc<SponsorshipBudget>>,
+    /// The funds spent within the current window of the spending cap, if enabled.
+    pub(crate) spending_cap: Option<Arc<SpendingCap>>,
+    /// The calls counted against the destination quotas, if enabled.
+    pub(crate) destination_quotas: Option<Arc<DestinationQuotas>>,
+    /// Limits requests per client connection, if enabled.
+    pub(crate) connection_rate_limiter: Option<Arc<RateLimiter<ConnectionId>>>,
+    /// Limits requests per sponsored account, if enabled.
+    pub(crate) account_rate_limiter: Option<Arc<RateLimiter<Address>>>,
+    /// Limits requests across all callers, if enabled.
+    pub(crate) global_rate_limiter: Option<Arc<GlobalRateLimiter>>,
+    /// Bounds concurrent gas estimations, if enabled.
+    pub(crate) estimation_limiter: Option<Arc<EstimationLimiter>>,
+    /// The pending delegation of each authority, if enabled.
+    pub(crate) pending_delegations: Option<Arc<PendingDelegations>>,
+    /// Limits how fast the nonce of the signer advances, if enabled.
+    pub(crate) signer_nonce_rate: Option<Arc<SignerNonceRate>>,
+    /// Batches submissions, if enabled. Spawned on first use.
+    pub(crate) batcher: Arc<OnceLock<SubmissionBatcher>>,
+    /// The active shadow configuration, if set.
+    pub(crate) shadow: Option<Arc<Self>>,
+}
+
+impl ActiveConfig {
+    /// Returns the delegation whitelist in effect, i.e. the list read from the registry if
+    /// enabled, combined with the configured whitelist if set, and the configured whitelist
+    /// otherwise.
+    pub(crate) fn current_whitelist(&self) -> Option<Arc<DelegationWhitelist>> {
+        match (&self.registry_whitelist, &self.config.delegation_whitelist) {
+            (Some(registry), Some(configured)) => Some(Arc::new(DelegationWhitelist::merged(
+                &self
+                    .config
+                    .whitelist_sources
+                    .merge(configured, registry.loaded_capability().as_ref()),
+            ))),
+            (Some(registry), None) => Some(registry.whitelist()),
+            (None, _) => self.whitelist.clone(),
+        }
+    }
+
+    /// Returns the delegation targets in effect, in the order of their sources, see
+    /// [`Self::current_whitelist`].
+    pub(crate) fn current_capability(&self) -> DelegationCapability {
+        match (&self.registry_whitelist, &self.config.delegation_whitelist) {
+            (Some(registry), Some(configured)) => self
+                .config
+                .whitelist_sources
+                .merge(configured, registry.loaded_capability().as_ref()),
+            (Some(registry), None) => registry.capability(),
+            (None, configured) => configured.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Activates the configuration.
+    ///
+    /// Stateful components whose settings are unchanged from the `previous` configuration are
+    /// carried over, so e.g. the remaining sponsorship budget is not reset by an unrelated change.
+    pub(crate) fn new(config: OdysseyWalletConfig, previous: Option<&Self>, now: Instant) -> Self {
+        let whitelist = config
+            .delegation_whitelist
+            .as_ref()
+            .map(|capability| Arc::new(DelegationWhitelist::new(capability)));
+        // the list is kept if only the refresh interval changes
+        let registry_address = config.whitelist_registry.map(|registry| registry.address);
+        let registry_whitelist = match previous {
+            Some(previous)
+                if previous.config.whitelist_registry.map(|registry| registry.address)
+                    == registry_address =>
+            {
+                previous.registry_whitelist.clone()
+            }
+            _ => registry_address.map(|_| Default::default()),
+        };
+        let adaptive_gas_ceiling = carry_over(
+            previous.map(|previous| {
+                (&previous.config.adaptive_gas_ceiling, &previous.adaptive_gas_ceiling)
+            }),
+            &config.adaptive_gas_ceiling,
+            |config| Arc::new(AdaptiveGasCeiling::new(*config, now)),
+        );
+        let destroyed_targets = carry_over(
+            previous.map(|previous| {
+                (&previous.config.destroyed_target_check, &previous.destroyed_targets)
+            }),
+            &config.destroyed_target_check,
+            |config| Arc::new(DestroyedTargets::new(*config)),
+        );
+        let target_ages = carry_over(
+            previous.map(|previous| {
+                (&previous.config.min_delegation_target_age, &previous.target_ages)
+            }),
+            &config.min_delegation_target_age,
+            |min_age_blocks| Arc::new(TargetAges::new(*min_age_blocks)),
+        );
+        let budget = carry_over(
+            previous.map(|previous| (&previous.config.sponsorship_budget, &previous.budget)),
+            &config.sponsorship_budget,
+            |config| Arc::new(SponsorshipBudget::new(*config, now)),
+        );
+        let spending_cap = carry_over(
+            previous.map(|previous| (&previous.config.spending_cap, &previous.spending_cap)),
+            &config.spending_cap,
+            |config| Arc::new(SpendingCap::new(*config, now)),
+        );
+        let destination_quotas = carry_over(
+            previous.map(|previous| {
+                (&previous.config.destination_quotas, &previous.destination_quotas)
+            }),
+            &config.destination_quotas,
+            |quotas| Arc::new(DestinationQuotas::new(quotas.clone())),
+        );
+        let connection_rate_limiter = carry_over(
+            previous.map(|previous| {
+                (&previous.config.connection_rate_limit, &previous.connection_rate_limiter)
+            }),
+            &config.connection_rate_limit,
+            |config| Arc::new(RateLimiter::new(*config)),
+        );
+        let account_rate_limiter = carry_over(
+            previous.map(|previous| {
+                (&previous.config.account_rate_limit, &previous.account_rate_limiter)
+            }),
+            &config.account_rate_limit,
+            |config| Arc::new(RateLimiter::new(*config)),
+        );
+        let global_rate_limiter = carry_over(
+            previous.map(|previous| {
+                (&previous.config.global_rate_limit, &previous.global_rate_limiter)
+            }),
+            &config.global_rate_limit,
+            |config| Arc::new(GlobalRateLimiter::new(*config, now)),
+        );
+        let estimation_limiter = carry_over(
+            previous
+                .map(|previous| (&previous.config.estimation_limit, &previous.estimation_limiter)),
+            &config.estimation_limit,
+            |limit| Arc::new(EstimationLimiter::new(*limit)),
+        );
+        let pending_delegations = carry_over(
+            previous.map(|previous| {
+                (&previous.config.pending_delegation_tracking, &previous.pending_delegations)
+            }),
+            &config.pending_delegation_tracking,
+            |settings| Arc::new(PendingDelegations::new(*settings)),
+        );
+        let signer_nonce_rate = carry_over(
+            previous.map(|previous| {
+                (&previous.config.max_signer_nonces_per_minute, &previous.signer_nonce_rate)
+            }),
+            &config.max_signer_nonces_per_minute,
+            |max| Arc::new(SignerNonceRate::new(*max)),
+        );
+        let batcher = match previous {
+            Some(previous)
+                if previous.config.submission_batch_window == config.submission_batch_window =>
+            {
+                previous.batcher.clone()
+            }
+            _ => Default::default(),
+        };
+        // the state of the shadow configuration is kept separately from the live state
+        let shadow = config.shadow_config.as_deref().map(|shadow| {
+            let previous = previous.and_then(|previous| previous.shadow.as_deref());
+            Arc::new(Self::new(
+                OdysseyWalletConfig { shadow_config: None, ..shadow.clone() },
+                previous,
+                now,
+            ))
+        });
+
+        Self {
+            config,
+            whitelist,
+            registry_whitelist,
+            adaptive_gas_ceiling,
+            destroyed_targets,
+            target_ages,
+            budget,
+            spending_cap,
+            destination_quotas,
+            connection_rate_limiter,
+            account_rate_limiter,
+            global_rate_limiter,
+            estimation_limiter,
+            pending_delegations,
+            signer_nonce_rate,
+            batcher,
+            shadow,
+        }
+    }
+}
+
+/// Returns the previous component if its settings are unchanged, and builds a new one otherwise.
+fn carry_over<S: PartialEq, T: Clone>(
+    previous: Option<(&Option<S>, &Option<T>)>,
+    settings: &Option<S>,
+    build: impl FnOnce(&S) -> T,
+) -> Option<T> {
+    match previous {
+        Some((previous_settings, previous)) if previous_settings == settings => previous.clone(),
+        _ => settings.as_ref().map(build),
+    }
+}
+
+/// The methods of the `wallet_` namespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WalletMethod {
+    /// `wallet_sendTransaction`
+    SendTransaction,
+    /// `wallet_estimateSponsorship`
+    EstimateSponsorship,
+}
+
+impl WalletMethod {
+    /// Returns the name of the method.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::SendTransaction => "wallet_sendTransaction",
+            Self::EstimateSponsorship => "wallet_estimateSponsorship",
+        }
+    }
+}
+
+/// Controls which methods of the `wallet_` namespace are enabled.
+///
+/// Disabled methods are still registered, but reject all calls with
+/// [`OdysseyWalletError::MethodDisabled`](crate::OdysseyWalletError::MethodDisabled).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MethodFlags {
+    /// Whether `wallet_sendTransaction` is enabled.
+    pub send_transaction: bool,
+    /// Whether `wallet_estimateSponsorship` is enabled.
+    pub estimate_sponsorship: bool,
+}
+
+impl MethodFlags {
+    /// All methods enabled.
+    pub const fn all() -> Self {
+        Self { send_transaction: true, estimate_sponsorship: true }
+    }
+
+    /// All methods disabled.
+    pub const fn none() -> Self {
+        Self { send_transaction: false, estimate_sponsorship: false }
+    }
+
+    /// Enables or disables the given method.
+    pub const fn with_method(mut self, method: WalletMethod, enabled: bool) -> Self {
+        match method {
+            WalletMethod::SendTransaction => self.send_transaction = enabled,
+            WalletMethod::EstimateSponsorship => self.estimate_sponsorship = enabled,
+        }
+        self
+    }
+
+    /// Returns `true` if the given method is enabled.
+    pub const fn is_enabled(&self, method: WalletMethod) -> bool {
+        match method {
+            WalletMethod::SendTransaction => self.send_transaction,
+            WalletMethod::EstimateSponsorship => self.estimate_sponsorship,
+        }
+    }
+}
+
+impl Default for MethodFlags {
+    fn default() -> Self {
+        Self::all()
+    }
+}
@@ -0,0 +1,133 @@
+//! Tracking of sponsored transactions until they are included.
+//!
+//! The receipt of an included transaction tells how much gas it actually used, which is compared
+//! to the estimate it was submitted with. Consistent over-estimation wastes the headroom of the
+//! gas ceiling, while under-estimation risks transactions running out of gas.
+
+use alloy_network::ReceiptResponse;
+use metrics::Histogram;
+use std::{future::Future, time::Duration};
+
+/// Settings for tracking submitted transactions until they are included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfirmationTracking {
+    /// How often the receipt of a transaction is requested.
+    pub poll_interval: Duration,
+    /// How long a transaction is tracked before it is given up on.
+    pub timeout: Duration,
+}
+
+impl ConfirmationTracking {
+    /// Polls `fetch` for the receipt of a transaction until it is available, or the timeout
+    /// elapsed.
+    ///
+    /// Failed requests are retried at the next poll.
+    pub(crate) async fn await_receipt<F, Fut, R, E>(&self, fetch: F) -> Option<R>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<Option<R>, E>>,
+    {
+        let poll = async {
+            loop {
+                if let Ok(Some(receipt)) = fetch().await {
+                    return receipt;
+                }
+                tokio::time::sleep(self.poll_interval).await;
+            }
+        };
+        tokio::time::timeout(self.timeout, poll).await.ok()
+    }
+}
+
+/// Records the gas estimate minus the gas actually used by the transaction of the receipt.
+///
+/// Positive values are over-estimations, negative values under-estimations.
+pub(crate) fn record_gas_estimate_error<R: ReceiptResponse>(
+    histogram: &Histogram,
+    estimate: u64,
+    receipt: &R,
+) {
+    histogram.record(estimate as f64 - receipt.gas_used() as f64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_rpc_types::TransactionReceipt;
+    use metrics::HistogramFn;
+    use parking_lot::Mutex;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[derive(Debug, Default)]
+    struct RecordedValues(Mutex<Vec<f64>>);
+
+    impl HistogramFn for RecordedValues {
+        fn record(&self, value: f64) {
+            self.0.lock().push(value);
+        }
+    }
+
+    fn receipt(gas_used: u64) -> TransactionReceipt {
+        serde_json::from_value(serde_json::json!({
+            "type": "0x2",
+            "status": "0x1",
+            "cumulativeGasUsed": "0x5208",
+            "logs": [],
+            "logsBloom": format!("0x{}", "00".repeat(256)),
+            "transactionHash": format!("0x{}", "11".repeat(32)),
+            "transactionIndex": "0x0",
+            "blockHash": format!("0x{}", "22".repeat(32)),
+            "blockNumber": "0x1",
+            "gasUsed": format!("{gas_used:#x}"),
+            "effectiveGasPrice": "0x3b9aca00",
+            "from": "0x0000000000000000000000000000000000000001",
+            "to": "0x0000000000000000000000000000000000000002",
+            "contractAddress": null
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn records_gas_estimate_error() {
+        let recorded = Arc::new(RecordedValues::default());
+        let histogram = Histogram::from_arc(recorded.clone());
+
+        record_gas_estimate_error(&histogram, 60_000, &receipt(46_000));
+        record_gas_estimate_error(&histogram, 21_000, &receipt(21_000));
+        record_gas_estimate_error(&histogram, 40_000, &receipt(45_000));
+        assert_eq!(*recorded.0.lock(), vec![14_000.0, 0.0, -5_000.0]);
+    }
+
+    #[tokio::test]
+    async fn awaits_receipt() {
+        let tracking = ConfirmationTracking {
+            poll_interval: Duration::from_millis(1),
+            timeout: Duration::from_secs(5),
+        };
+
+        // pending, then failing, then included
+        let polls = AtomicUsize::new(0);
+        let receipt = tracking
+            .await_receipt(|| {
+                let poll = polls.fetch_add(1, Ordering::Relaxed);
+                async move {
+                    match poll {
+                        0 => Ok(None),
+                        1 => Err(()),
+                        _ => Ok(Some(receipt(46_000))),
+                    }
+                }
+            })
+            .await;
+        assert_eq!(receipt.map(|receipt| receipt.gas_used), Some(46_000));
+        assert_eq!(polls.load(Ordering::Relaxed), 3);
+
+        // never included
+        let tracking = ConfirmationTracking { timeout: Duration::from_millis(10), ..tracking };
+        let receipt = tracking.await_receipt(|| async { Ok::<Option<()>, ()>(None) }).await;
+        assert_eq!(receipt, None);
+    }
+}
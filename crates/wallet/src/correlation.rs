@@ -0,0 +1,61 @@
+//! Correlation ids that tie the log lines of a request to its response.
+
+use crate::OdysseyWalletError;
+use alloy_primitives::hex;
+use rand::Rng;
+
+/// The maximum length of a correlation id provided by a client.
+pub const MAX_CORRELATION_ID_LEN: usize = 64;
+
+/// Returns the correlation id provided by the client, or generates one if none was provided.
+///
+/// Provided ids must be at most [`MAX_CORRELATION_ID_LEN`] characters of ASCII letters, digits,
+/// `-`, `_` or `.`, so they can be safely logged and grepped.
+pub(crate) fn resolve_correlation_id(
+    provided: Option<String>,
+) -> Result<String, OdysseyWalletError> {
+    let Some(id) = provided else {
+        return Ok(generate_correlation_id());
+    };
+    let valid = !id.is_empty()
+        && id.len() <= MAX_CORRELATION_ID_LEN
+        && id.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'));
+    if !valid {
+        return Err(OdysseyWalletError::InvalidCorrelationId);
+    }
+    Ok(id)
+}
+
+/// Generates a random (version 4) UUID.
+fn generate_correlation_id() -> String {
+    let mut bytes: [u8; 16] = rand::thread_rng().gen();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex::encode(bytes);
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_correlation_ids() {
+        // provided ids are echoed
+        assert_eq!(resolve_correlation_id(Some("req-42.a_b".into())), Ok("req-42.a_b".into()));
+
+        // otherwise a uuid is generated
+        let generated = resolve_correlation_id(None).unwrap();
+        assert_eq!(generated.len(), 36);
+        assert_eq!(generated.as_bytes()[14], b'4');
+        assert_ne!(generated, resolve_correlation_id(None).unwrap());
+
+        // invalid ids are rejected
+        for invalid in ["", "a b", "a\nb", &"a".repeat(MAX_CORRELATION_ID_LEN + 1)] {
+            assert_eq!(
+                resolve_correlation_id(Some(invalid.into())),
+                Err(OdysseyWalletError::InvalidCorrelationId)
+            );
+        }
+    }
+}
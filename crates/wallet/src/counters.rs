@@ -0,0 +1,104 @@
+//! Totals of the wallet's counters, observable without a metrics exporter.
+//!
+//! Each counter is wrapped so increments are forwarded to the registered counter and added to a
+//! local total. This works whether the metrics were registered or not, so embedders and tests can
+//! assert on the counters through
+//! [`OdysseyWallet::metrics_snapshot`](crate::OdysseyWallet::metrics_snapshot).
+
+use metrics::{Counter, CounterFn};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+/// The totals of the wallet's counters since it was created.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    /// Number of valid calls to `wallet_sendTransaction`.
+    pub valid_send_transaction_calls: u64,
+    /// Number of invalid calls to `wallet_sendTransaction`.
+    pub invalid_send_transaction_calls: u64,
+    /// Number of submitted transactions delegating accounts for the first time.
+    pub delegation_setup_transactions: u64,
+    /// Number of submitted transactions calling delegated accounts.
+    pub delegated_call_transactions: u64,
+    /// Number of submitted transactions changing the delegation of accounts.
+    pub delegation_change_transactions: u64,
+    /// Number of accepted requests the shadow configuration rejects.
+    pub shadow_rejections: u64,
+    /// Number of rejected requests the shadow configuration accepts.
+    pub shadow_acceptances: u64,
+}
+
+/// The local totals of the wallet's counters.
+#[derive(Debug, Default)]
+pub(crate) struct CounterTotals {
+    pub(crate) valid_send_transaction_calls: Arc<AtomicU64>,
+    pub(crate) invalid_send_transaction_calls: Arc<AtomicU64>,
+    pub(crate) delegation_setup_transactions: Arc<AtomicU64>,
+    pub(crate) delegated_call_transactions: Arc<AtomicU64>,
+    pub(crate) delegation_change_transactions: Arc<AtomicU64>,
+    pub(crate) shadow_rejections: Arc<AtomicU64>,
+    pub(crate) shadow_acceptances: Arc<AtomicU64>,
+}
+
+impl CounterTotals {
+    /// Returns the current totals.
+    pub(crate) fn snapshot(&self) -> MetricsSnapshot {
+        let load = |total: &AtomicU64| total.load(Ordering::Relaxed);
+        MetricsSnapshot {
+            valid_send_transaction_calls: load(&self.valid_send_transaction_calls),
+            invalid_send_transaction_calls: load(&self.invalid_send_transaction_calls),
+            delegation_setup_transactions: load(&self.delegation_setup_transactions),
+            delegated_call_transactions: load(&self.delegated_call_transactions),
+            delegation_change_transactions: load(&self.delegation_change_transactions),
+            shadow_rejections: load(&self.shadow_rejections),
+            shadow_acceptances: load(&self.shadow_acceptances),
+        }
+    }
+}
+
+/// Wraps the counter, so its increments are also added to `total`.
+pub(crate) fn observed(counter: Counter, total: &Arc<AtomicU64>) -> Counter {
+    Counter::from_arc(Arc::new(ObservedCounter { counter, total: total.clone() }))
+}
+
+/// A counter that forwards to the registered counter, and keeps a local total.
+struct ObservedCounter {
+    counter: Counter,
+    total: Arc<AtomicU64>,
+}
+
+impl CounterFn for ObservedCounter {
+    fn increment(&self, value: u64) {
+        self.counter.increment(value);
+        self.total.fetch_add(value, Ordering::Relaxed);
+    }
+
+    fn absolute(&self, value: u64) {
+        self.counter.absolute(value);
+        self.total.fetch_max(value, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn observes_increments() {
+        let totals = CounterTotals::default();
+        let counter = observed(Counter::noop(), &totals.invalid_send_transaction_calls);
+
+        counter.increment(1);
+        counter.increment(2);
+        assert_eq!(totals.snapshot().invalid_send_transaction_calls, 3);
+        counter.absolute(2);
+        assert_eq!(totals.snapshot().invalid_send_transaction_calls, 3);
+        counter.absolute(5);
+        assert_eq!(
+            totals.snapshot(),
+            MetricsSnapshot { invalid_send_transaction_calls: 5, ..Default::default() }
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! Introspection of the delegation of an account.
+
+use crate::whitelist::DelegationWhitelist;
+use alloy_primitives::{Address, B256};
+use revm_primitives::Bytecode;
+use serde::{Deserialize, Serialize};
+
+/// The delegation status of an account, as returned by `wallet_delegationInfo`.
+///
+/// The block in which a delegation was established is not indexed, so it is not reported.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DelegationInfo {
+    /// Whether the account exists in the latest state.
+    pub exists: bool,
+    /// The contract the account delegates to, if it is delegated.
+    pub target: Option<Address>,
+    /// Whether the wallet sponsors calls to the account because of its target, i.e. the target is
+    /// whitelisted, or no whitelist is configured.
+    ///
+    /// Always `false` if the account is not delegated.
+    pub whitelisted: bool,
+    /// The code hash of the target, if it has code.
+    pub target_code_hash: Option<B256>,
+}
+
+impl DelegationInfo {
+    /// Derives the delegation status of an account from whether it exists and its code.
+    ///
+    /// `target_code` returns the code of the target, if the account is delegated.
+    pub(crate) fn new<E>(
+        exists: bool,
+        code: Option<&Bytecode>,
+        whitelist: Option<&DelegationWhitelist>,
+        target_code: impl FnOnce(Address) -> Result<Option<Bytecode>, E>,
+    ) -> Result<Self, E> {
+        let target = code.and_then(|code| match code {
+            Bytecode::Eip7702(code) => Some(code.address()),
+            _ => None,
+        });
+        let Some(target) = target else {
+            return Ok(Self { exists, target: None, whitelisted: false, target_code_hash: None });
+        };
+
+        Ok(Self {
+            exists,
+            target: Some(target),
+            whitelisted: whitelist.is_none_or(|whitelist| whitelist.contains(&target)),
+            target_code_hash: target_code(target)?
+                .filter(|code| !code.is_empty())
+                .map(|code| code.hash_slow()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DelegationCapability;
+    use alloy_primitives::Bytes;
+    use revm_primitives::Eip7702Bytecode;
+    use std::convert::Infallible;
+
+    #[test]
+    fn describes_delegation() {
+        let target = Address::with_last_byte(1);
+        let target_code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00]));
+        let delegated = Bytecode::Eip7702(Eip7702Bytecode::new(target));
+        let whitelist = DelegationWhitelist::new(&DelegationCapability { addresses: vec![target] });
+        let lookup = |address| {
+            assert_eq!(address, target);
+            Ok::<_, Infallible>(Some(target_code.clone()))
+        };
+
+        // a delegated account
+        assert_eq!(
+            DelegationInfo::new(true, Some(&delegated), Some(&whitelist), lookup),
+            Ok(DelegationInfo {
+                exists: true,
+                target: Some(target),
+                whitelisted: true,
+                target_code_hash: Some(target_code.hash_slow()),
+            })
+        );
+        let other = DelegationWhitelist::new(&DelegationCapability::default());
+        assert!(
+            !DelegationInfo::new(true, Some(&delegated), Some(&other), lookup).unwrap().whitelisted
+        );
+        assert!(DelegationInfo::new(true, Some(&delegated), None, lookup).unwrap().whitelisted);
+
+        // a contract and an account without code are not delegated
+        let unrelated = |_| -> Result<_, Infallible> { unreachable!() };
+        let not_delegated = DelegationInfo {
+            exists: true,
+            target: None,
+            whitelisted: false,
+            target_code_hash: None,
+        };
+        assert_eq!(
+            DelegationInfo::new(true, Some(&target_code), None, unrelated),
+            Ok(not_delegated.clone())
+        );
+        assert_eq!(DelegationInfo::new(true, None, None, unrelated), Ok(not_delegated));
+
+        // an unknown account
+        assert_eq!(
+            DelegationInfo::new(false, None, Some(&whitelist), unrelated),
+            Ok(DelegationInfo {
+                exists: false,
+                target: None,
+                whitelisted: false,
+                target_code_hash: None
+            })
+        );
+    }
+}
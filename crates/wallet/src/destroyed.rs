@@ -0,0 +1,124 @@
+//! Detection of delegation targets whose code has been removed.
+
+use alloy_primitives::Address;
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// Settings for rejecting delegation targets whose code has been removed, e.g. by a
+/// `SELFDESTRUCT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DestroyedTargetCheck {
+    /// How many blocks to look back for the code of the target.
+    ///
+    /// A target is considered destroyed if it had code at `latest - lookback_blocks`, but has no
+    /// code at the latest block.
+    pub lookback_blocks: u64,
+    /// How long the result of a check is cached.
+    pub cache_ttl: Duration,
+}
+
+impl Default for DestroyedTargetCheck {
+    fn default() -> Self {
+        Self { lookback_blocks: 64, cache_ttl: Duration::from_secs(12) }
+    }
+}
+
+/// The maximum number of cached results before expired entries are evicted.
+const MAX_CACHED_TARGETS: usize = 1_024;
+
+/// Caches whether delegation targets have been destroyed.
+#[derive(Debug)]
+pub(crate) struct DestroyedTargets {
+    config: DestroyedTargetCheck,
+    cache: Mutex<HashMap<Address, (bool, Instant)>>,
+}
+
+impl DestroyedTargets {
+    /// Creates a new, empty cache.
+    pub(crate) fn new(config: DestroyedTargetCheck) -> Self {
+        Self { config, cache: Default::default() }
+    }
+
+    /// Returns the configured lookback.
+    pub(crate) const fn lookback_blocks(&self) -> u64 {
+        self.config.lookback_blocks
+    }
+
+    /// Returns whether the target has been destroyed.
+    ///
+    /// If there is no fresh cached result, `has_code` is used to determine whether the target has
+    /// code at the latest block and at the lookback block, respectively.
+    pub(crate) fn is_destroyed<E>(
+        &self,
+        target: Address,
+        now: Instant,
+        has_code: impl FnOnce() -> Result<(bool, bool), E>,
+    ) -> Result<bool, E> {
+        if let Some((destroyed, checked_at)) = self.cache.lock().get(&target).copied() {
+            if now.saturating_duration_since(checked_at) < self.config.cache_ttl {
+                return Ok(destroyed);
+            }
+        }
+
+        let (has_code_now, had_code) = has_code()?;
+        let destroyed = had_code && !has_code_now;
+
+        let mut cache = self.cache.lock();
+        if cache.len() >= MAX_CACHED_TARGETS {
+            let ttl = self.config.cache_ttl;
+            cache.retain(|_, (_, checked_at)| now.saturating_duration_since(*checked_at) < ttl);
+        }
+        cache.insert(target, (destroyed, now));
+
+        Ok(destroyed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+
+    #[test]
+    fn detects_removed_code() {
+        let targets = DestroyedTargets::new(DestroyedTargetCheck {
+            lookback_blocks: 10,
+            cache_ttl: Duration::from_secs(12),
+        });
+        let target = Address::with_last_byte(1);
+        let now = Instant::now();
+
+        // code is present
+        assert_eq!(
+            targets.is_destroyed(target, now, || Ok::<_, Infallible>((true, true))),
+            Ok(false)
+        );
+
+        // result is cached
+        assert_eq!(
+            targets.is_destroyed(target, now + Duration::from_secs(1), || Ok::<_, Infallible>((
+                false, true
+            ))),
+            Ok(false)
+        );
+
+        // code was present, but is absent now
+        assert_eq!(
+            targets.is_destroyed(target, now + Duration::from_secs(12), || Ok::<_, Infallible>((
+                false, true
+            ))),
+            Ok(true)
+        );
+
+        // code was never present
+        assert_eq!(
+            targets.is_destroyed(Address::with_last_byte(2), now, || Ok::<_, Infallible>((
+                false, false
+            ))),
+            Ok(false)
+        );
+    }
+}
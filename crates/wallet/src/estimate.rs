@@ -0,0 +1,247 @@
+//! Estimation of the cost of sponsoring a transaction.
+
+use crate::gas::with_gas_buffer;
+use alloy_primitives::{address, Address, Bytes, U256};
+use alloy_rpc_types::TransactionRequest;
+use serde::{Deserialize, Serialize};
+
+/// The OP stack `GasPriceOracle` predeploy, used to compute the L1 data fee of a transaction.
+///
+/// See also <https://specs.optimism.io/protocol/predeploys.html#gaspriceoracle>
+pub const GAS_PRICE_ORACLE: Address = address!("420000000000000000000000000000000000000F");
+
+/// The selector of `GasPriceOracle.getL1Fee(bytes)`.
+const GET_L1_FEE_SELECTOR: [u8; 4] = [0x49, 0x94, 0x8e, 0x0e];
+
+/// The estimated cost of sponsoring a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SponsorshipEstimate {
+    /// The breakdown of the cost.
+    pub cost: GasBreakdown,
+    /// How the gas limit was arrived at.
+    pub details: GasEstimateDetails,
+}
+
+/// How the gas limit of a sponsored transaction was derived from the node's estimate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasEstimateDetails {
+    /// The gas estimated by the node.
+    pub raw_estimate: u64,
+    /// The buffer added to the raw estimate, in percent.
+    pub buffer_applied: u64,
+    /// The raw estimate plus the buffer, which the transaction is signed with unless the client
+    /// requested a lower limit.
+    pub buffered_estimate: u64,
+    /// The gas ceiling the buffered estimate must stay below.
+    pub ceiling: u64,
+    /// The base fee the max fee per gas was computed from.
+    pub base_fee: u128,
+    /// Whether the request carried an access list, which the estimate accounts for.
+    pub access_list_used: bool,
+}
+
+impl GasEstimateDetails {
+    /// Creates new details, computing the buffered estimate from the raw estimate.
+    pub const fn new(
+        raw_estimate: u64,
+        buffer_applied: u64,
+        ceiling: u64,
+        base_fee: u128,
+        access_list_used: bool,
+    ) -> Self {
+        Self {
+            raw_estimate,
+            buffer_applied,
+            buffered_estimate: with_gas_buffer(raw_estimate, buffer_applied),
+            ceiling,
+            base_fee,
+            access_list_used,
+        }
+    }
+}
+
+/// The cost of a sponsored transaction, broken down into its L2 execution, L1 data and value
+/// components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasBreakdown {
+    /// The amount of gas the transaction is estimated to consume on L2.
+    pub l2_execution_gas: u64,
+    /// The max fee per gas the transaction is signed with.
+    pub max_fee_per_gas: u128,
+    /// The L1 data fee in wei.
+    ///
+    /// This is `None` if the L1 data fee is not available, e.g. on chains that are not OP stack
+    /// chains.
+    pub l1_data_fee_wei: Option<U256>,
+    /// The value the transaction sends in wei, which is paid by the sponsor.
+    #[serde(default)]
+    pub value_wei: U256,
+    /// The maximum total cost in wei, i.e. the L2 execution cost at the max fee per gas plus the
+    /// L1 data fee and the value.
+    pub total_cost_wei: U256,
+}
+
+impl GasBreakdown {
+    /// Creates a new breakdown, computing the total cost from its components.
+    pub fn new(
+        l2_execution_gas: u64,
+        max_fee_per_gas: u128,
+        l1_data_fee_wei: Option<U256>,
+        value_wei: U256,
+    ) -> Self {
+        let l2_execution_cost = U256::from(l2_execution_gas) * U256::from(max_fee_per_gas);
+        Self {
+            l2_execution_gas,
+            max_fee_per_gas,
+            l1_data_fee_wei,
+            value_wei,
+            total_cost_wei: (l2_execution_cost + l1_data_fee_wei.unwrap_or_default())
+                .saturating_add(value_wei),
+        }
+    }
+}
+
+/// A call of a batch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Call {
+    /// The account the call is sent to.
+    pub to: Address,
+    /// The calldata of the call.
+    #[serde(default)]
+    pub data: Bytes,
+    /// The value of the call, which must be 0 if set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<U256>,
+}
+
+impl Call {
+    /// Returns the call as a transaction request, subject to the same rules as any other request.
+    pub(crate) fn into_request(self) -> TransactionRequest {
+        TransactionRequest { value: self.value, ..Default::default() }
+            .to(self.to)
+            .input(self.data.into())
+    }
+}
+
+/// Returns the transactions a batch of calls is submitted as by `wallet_sendCalls`, one per call.
+pub(crate) fn batch_requests(calls: Vec<Call>) -> Vec<TransactionRequest> {
+    calls.into_iter().map(Call::into_request).collect()
+}
+
+/// The estimated gas of each call of a batch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallsEstimate {
+    /// The amount of gas each call is estimated to consume, in the order of the batch.
+    pub calls: Vec<u64>,
+    /// The sum of the estimates of all calls.
+    pub total_gas: u64,
+}
+
+impl CallsEstimate {
+    /// Creates a new estimate, computing the total from the estimates of the calls.
+    pub fn new(calls: Vec<u64>) -> Self {
+        let total_gas = calls.iter().fold(0u64, |total, gas| total.saturating_add(*gas));
+        Self { calls, total_gas }
+    }
+}
+
+/// ABI-encodes a call to `GasPriceOracle.getL1Fee(bytes)` for the given encoded transaction.
+pub(crate) fn get_l1_fee_calldata(tx: &[u8]) -> Bytes {
+    let padding = (32 - tx.len() % 32) % 32;
+    let mut calldata = Vec::with_capacity(4 + 64 + tx.len() + padding);
+    calldata.extend_from_slice(&GET_L1_FEE_SELECTOR);
+    // offset of the `bytes` argument
+    calldata.extend_from_slice(&U256::from(32).to_be_bytes::<32>());
+    calldata.extend_from_slice(&U256::from(tx.len()).to_be_bytes::<32>());
+    calldata.extend_from_slice(tx);
+    calldata.resize(calldata.len() + padding, 0);
+    calldata.into()
+}
+
+/// Decodes the output of `GasPriceOracle.getL1Fee(bytes)`.
+///
+/// Returns `None` if the output is not a single word, e.g. if there is no oracle deployed.
+pub(crate) fn decode_l1_fee(output: &[u8]) -> Option<U256> {
+    (output.len() == 32).then(|| U256::from_be_slice(output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::keccak256;
+
+    #[test]
+    fn get_l1_fee_selector() {
+        assert_eq!(keccak256("getL1Fee(bytes)")[..4], GET_L1_FEE_SELECTOR);
+    }
+
+    #[test]
+    fn encodes_get_l1_fee_call() {
+        let calldata = get_l1_fee_calldata(&[0xaa; 33]);
+
+        assert_eq!(calldata.len(), 4 + 32 + 32 + 64);
+        assert_eq!(calldata[..4], GET_L1_FEE_SELECTOR);
+        assert_eq!(U256::from_be_slice(&calldata[4..36]), U256::from(32));
+        assert_eq!(U256::from_be_slice(&calldata[36..68]), U256::from(33));
+        assert_eq!(calldata[68..101], [0xaa; 33]);
+        assert!(calldata[101..].iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn decodes_l1_fee() {
+        assert_eq!(decode_l1_fee(&U256::from(1234).to_be_bytes::<32>()), Some(U256::from(1234)));
+        assert_eq!(decode_l1_fee(&[]), None);
+    }
+
+    #[test]
+    fn breakdown_includes_both_components() {
+        let breakdown =
+            GasBreakdown::new(100_000, 2_000_000_000, Some(U256::from(5_000)), U256::ZERO);
+        assert_eq!(breakdown.l2_execution_gas, 100_000);
+        assert_eq!(breakdown.l1_data_fee_wei, Some(U256::from(5_000)));
+        assert_eq!(breakdown.total_cost_wei, U256::from(200_000_000_005_000u128));
+
+        // falls back to the L2 cost only
+        let breakdown = GasBreakdown::new(100_000, 2_000_000_000, None, U256::ZERO);
+        assert_eq!(breakdown.total_cost_wei, U256::from(200_000_000_000_000u128));
+
+        // the value is paid by the sponsor too
+        let breakdown = GasBreakdown::new(100_000, 2_000_000_000, None, U256::from(7));
+        assert_eq!(breakdown.value_wei, U256::from(7));
+        assert_eq!(breakdown.total_cost_wei, U256::from(200_000_000_000_007u128));
+    }
+
+    #[test]
+    fn details_reflect_buffer_and_ceiling() {
+        let details = GasEstimateDetails::new(100_000, 10, 350_000, 1_000_000_000, true);
+        assert_eq!(details.raw_estimate, 100_000);
+        assert_eq!(details.buffer_applied, 10);
+        assert_eq!(details.buffered_estimate, 110_000);
+        assert_eq!(details.ceiling, 350_000);
+
+        assert_eq!(
+            GasEstimateDetails::new(100_000, 0, 350_000, 0, false).buffered_estimate,
+            100_000
+        );
+    }
+
+    #[test]
+    fn attributes_gas_per_call() {
+        let batch: Vec<Call> = serde_json::from_str(
+            r#"[
+                {"to":"0x0000000000000000000000000000000000000001","data":"0xa9059cbb"},
+                {"to":"0x0000000000000000000000000000000000000002"}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            batch[1],
+            Call { to: Address::with_last_byte(2), data: Bytes::new(), value: None }
+        );
+
+        let estimate = CallsEstimate::new(vec![46_000, 21_000, 120_000]);
+        assert_eq!(estimate.calls, vec![46_000, 21_000, 120_000]);
+        assert_eq!(estimate.total_gas, 187_000);
+        assert_eq!(CallsEstimate::new(vec![u64::MAX, 1]).total_gas, u64::MAX);
+    }
+}
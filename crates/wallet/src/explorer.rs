@@ -0,0 +1,99 @@
+//! A [`VerificationOracle`] backed by an Etherscan-compatible block explorer API.
+
+use crate::VerificationOracle;
+use alloy_primitives::Address;
+use jsonrpsee::core::async_trait;
+use serde::Deserialize;
+use tracing::warn;
+
+/// A [`VerificationOracle`] that queries the `getsourcecode` action of an Etherscan-compatible
+/// block explorer API, e.g. Etherscan or Blockscout.
+///
+/// A target is considered verified if the explorer returns source code for it. Failed lookups
+/// are treated as unverified. Lookups are not cached, so this should be wrapped in a
+/// [`CachedVerificationOracle`](crate::CachedVerificationOracle).
+#[derive(Debug, Clone)]
+pub struct ExplorerVerificationOracle {
+    client: reqwest::Client,
+    api_url: String,
+    api_key: Option<String>,
+}
+
+impl ExplorerVerificationOracle {
+    /// Creates a new oracle for the explorer API at `api_url`, e.g. `https://api.etherscan.io/api`.
+    pub fn new(api_url: impl Into<String>, api_key: Option<String>) -> Self {
+        Self { client: reqwest::Client::new(), api_url: api_url.into(), api_key }
+    }
+
+    /// Fetches whether the explorer has verified source code for the target.
+    async fn fetch_verified(&self, target: Address) -> reqwest::Result<bool> {
+        let mut query = vec![
+            ("module", "contract".to_string()),
+            ("action", "getsourcecode".to_string()),
+            ("address", target.to_string()),
+        ];
+        if let Some(api_key) = &self.api_key {
+            query.push(("apikey", api_key.clone()));
+        }
+
+        let response: SourceCodeResponse = self
+            .client
+            .get(&self.api_url)
+            .query(&query)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(response.result.iter().any(|source| !source.source_code.is_empty()))
+    }
+}
+
+#[async_trait]
+impl VerificationOracle for ExplorerVerificationOracle {
+    async fn is_verified(&self, target: Address) -> bool {
+        self.fetch_verified(target)
+            .await
+            .inspect_err(
+                |err| warn!(target: "rpc::wallet", ?err, %target, "Error fetching verification status"),
+            )
+            .unwrap_or(false)
+    }
+}
+
+/// The response of the `getsourcecode` action.
+#[derive(Debug, Deserialize)]
+struct SourceCodeResponse {
+    /// The source code entries of the contract.
+    ///
+    /// This is a string on errors, which is treated as no entries.
+    #[serde(default, deserialize_with = "deserialize_results")]
+    result: Vec<SourceCode>,
+}
+
+/// A source code entry of the `getsourcecode` action.
+#[derive(Debug, Deserialize)]
+struct SourceCode {
+    /// The source code, which is empty if the contract is not verified.
+    #[serde(rename = "SourceCode", default)]
+    source_code: String,
+}
+
+/// Deserializes the `result` field, which is either a list of entries or an error message.
+fn deserialize_results<'de, D>(deserializer: D) -> Result<Vec<SourceCode>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Results {
+        Entries(Vec<SourceCode>),
+        Message(serde::de::IgnoredAny),
+    }
+
+    Ok(match Results::deserialize(deserializer)? {
+        Results::Entries(entries) => entries,
+        Results::Message(_) => Vec::new(),
+    })
+}
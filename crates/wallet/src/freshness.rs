@@ -0,0 +1,81 @@
+//! Enforcement of a maximum age of the fetched fee data.
+//!
+//! The fees are derived from the latest block, so their age is the age of that block. If the node
+//! falls behind, e.g. because it lost its peers, the base fee may be outdated and the sponsored
+//! transaction underpriced.
+
+use crate::OdysseyWalletError;
+use std::time::Duration;
+
+/// How fee data older than the configured maximum age is handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StaleFeeDataPolicy {
+    /// Requests are rejected.
+    Reject,
+    /// The base fee is increased by the given percentage.
+    Buffer {
+        /// The percentage added to the base fee.
+        percent: u64,
+    },
+}
+
+/// Settings for the maximum age of the fetched fee data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeDataMaxAge {
+    /// The maximum age of the latest block.
+    pub max_age: Duration,
+    /// How stale fee data is handled.
+    pub on_stale: StaleFeeDataPolicy,
+}
+
+impl FeeDataMaxAge {
+    /// Returns the base fee to use, given the timestamp of the block it was derived from and the
+    /// current time, both in seconds since the unix epoch.
+    pub(crate) const fn apply(
+        &self,
+        base_fee: u128,
+        block_timestamp: u64,
+        now: u64,
+    ) -> Result<u128, OdysseyWalletError> {
+        let age = now.saturating_sub(block_timestamp);
+        if age <= self.max_age.as_secs() {
+            return Ok(base_fee);
+        }
+
+        match self.on_stale {
+            StaleFeeDataPolicy::Reject => Err(OdysseyWalletError::StaleFeeData { age }),
+            StaleFeeDataPolicy::Buffer { percent } => {
+                Ok(base_fee.saturating_add(base_fee.saturating_mul(percent as u128) / 100))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handles_stale_fee_data() {
+        let reject = FeeDataMaxAge {
+            max_age: Duration::from_secs(60),
+            on_stale: StaleFeeDataPolicy::Reject,
+        };
+        let buffer =
+            FeeDataMaxAge { on_stale: StaleFeeDataPolicy::Buffer { percent: 50 }, ..reject };
+        let now = 1_000;
+
+        // fresh fee data is used as is
+        assert_eq!(reject.apply(100, now - 60, now), Ok(100));
+        assert_eq!(buffer.apply(100, now - 60, now), Ok(100));
+        // blocks from the future are not stale
+        assert_eq!(reject.apply(100, now + 10, now), Ok(100));
+
+        // stale fee data is rejected or buffered
+        assert_eq!(
+            reject.apply(100, now - 61, now),
+            Err(OdysseyWalletError::StaleFeeData { age: 61 })
+        );
+        assert_eq!(buffer.apply(100, now - 61, now), Ok(150));
+    }
+}
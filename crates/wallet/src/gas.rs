@@ -0,0 +1,296 @@
+//! Gas ceiling enforcement for sponsored transactions.
+
+use alloy_primitives::Address;
+use alloy_rpc_types::TransactionRequest;
+use jsonrpsee::core::async_trait;
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Debug,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Gas ceilings that override the global ceiling for specific destinations and functions.
+///
+/// The most specific ceiling applies: a ceiling for the destination and selector of a request
+/// takes precedence over a ceiling for its destination, which takes precedence over the global
+/// ceiling.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GasCeilingOverrides {
+    /// Ceilings per destination.
+    pub destinations: HashMap<Address, u64>,
+    /// Ceilings per destination and function selector.
+    pub selectors: HashMap<(Address, [u8; 4]), u64>,
+}
+
+impl GasCeilingOverrides {
+    /// Sets the ceiling for all calls to the destination.
+    pub fn with_destination(mut self, destination: Address, ceiling: u64) -> Self {
+        self.destinations.insert(destination, ceiling);
+        self
+    }
+
+    /// Sets the ceiling for calls to the given function of the destination.
+    pub fn with_selector(mut self, destination: Address, selector: [u8; 4], ceiling: u64) -> Self {
+        self.selectors.insert((destination, selector), ceiling);
+        self
+    }
+
+    /// Returns the most specific ceiling for a call to `to` with the given input.
+    pub fn resolve(&self, to: Option<Address>, input: &[u8], global: u64) -> u64 {
+        let Some(to) = to else {
+            return global;
+        };
+
+        let selector = input.get(..4).and_then(|selector| selector.try_into().ok());
+        selector
+            .and_then(|selector| self.selectors.get(&(to, selector)))
+            .or_else(|| self.destinations.get(&to))
+            .copied()
+            .unwrap_or(global)
+    }
+}
+
+/// Settings for the adaptive gas ceiling.
+///
+/// Instead of enforcing a fixed ceiling, the adaptive ceiling observes the gas estimates of
+/// accepted requests and periodically sets the ceiling to a percentile of that distribution plus a
+/// margin. The result is always clamped to `floor..=cap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdaptiveGasCeilingConfig {
+    /// The percentile of observed estimates to base the ceiling on, in `1..=100`.
+    pub percentile: u8,
+    /// The margin added on top of the percentile, in percent.
+    pub margin_percent: u64,
+    /// The lowest value the ceiling can be tuned to.
+    pub floor: u64,
+    /// The highest value the ceiling can be tuned to.
+    ///
+    /// This is also the ceiling used until enough samples have been observed.
+    pub cap: u64,
+    /// The number of most recent estimates to keep.
+    pub sample_size: usize,
+    /// The minimum number of estimates that must be observed before the ceiling is tuned.
+    pub min_samples: usize,
+    /// How often the ceiling is recomputed.
+    pub recompute_interval: Duration,
+}
+
+impl Default for AdaptiveGasCeilingConfig {
+    fn default() -> Self {
+        Self {
+            percentile: 99,
+            margin_percent: 10,
+            floor: 100_000,
+            cap: crate::DEFAULT_GAS_CEILING,
+            sample_size: 1_000,
+            min_samples: 100,
+            recompute_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+/// A gas ceiling that is periodically tuned based on observed estimates.
+#[derive(Debug)]
+pub(crate) struct AdaptiveGasCeiling {
+    config: AdaptiveGasCeilingConfig,
+    state: Mutex<AdaptiveGasCeilingState>,
+}
+
+#[derive(Debug)]
+struct AdaptiveGasCeilingState {
+    /// The most recent accepted estimates.
+    samples: VecDeque<u64>,
+    /// The currently enforced ceiling.
+    ceiling: u64,
+    /// When the ceiling was last recomputed.
+    last_recompute: Instant,
+}
+
+impl AdaptiveGasCeiling {
+    /// Creates a new adaptive gas ceiling, starting at the configured cap.
+    pub(crate) fn new(config: AdaptiveGasCeilingConfig, now: Instant) -> Self {
+        let state = AdaptiveGasCeilingState {
+            samples: VecDeque::with_capacity(config.sample_size),
+            ceiling: config.cap,
+            last_recompute: now,
+        };
+        Self { config, state: Mutex::new(state) }
+    }
+
+    /// Records the estimate of an accepted request.
+    pub(crate) fn record(&self, estimate: u64) {
+        let mut state = self.state.lock();
+        if state.samples.len() >= self.config.sample_size {
+            state.samples.pop_front();
+        }
+        state.samples.push_back(estimate);
+    }
+
+    /// Returns the current ceiling, recomputing it if the recompute interval has elapsed.
+    pub(crate) fn ceiling(&self, now: Instant) -> u64 {
+        let mut state = self.state.lock();
+        if now.saturating_duration_since(state.last_recompute) >= self.config.recompute_interval {
+            state.last_recompute = now;
+            if state.samples.len() >= self.config.min_samples {
+                let samples = state.samples.iter().copied().collect();
+                state.ceiling = compute_ceiling(samples, &self.config);
+            }
+        }
+        state.ceiling
+    }
+}
+
+/// Computes the ceiling for the given samples.
+///
+/// The percentile is computed using the nearest-rank method, the margin is added on top and the
+/// result is clamped to the configured floor and cap.
+fn compute_ceiling(mut samples: Vec<u64>, config: &AdaptiveGasCeilingConfig) -> u64 {
+    if samples.is_empty() {
+        return config.cap;
+    }
+    samples.sort_unstable();
+
+    let percentile = config.percentile.clamp(1, 100) as usize;
+    let rank = (percentile * samples.len()).div_ceil(100).max(1);
+    let value = samples[rank - 1];
+    let ceiling = value.saturating_add(value.saturating_mul(config.margin_percent) / 100);
+
+    ceiling.clamp(config.floor, config.cap.max(config.floor))
+}
+
+/// Reports how deep the call stack of a request gets, e.g. by tracing its execution.
+///
+/// Deep call stacks may indicate a delegation loop, where a delegated contract calls into
+/// another delegated account that calls back into the first.
+#[async_trait]
+pub trait CallDepthProbe: Debug + Send + Sync {
+    /// Returns the maximum call depth reached when executing the request, or `None` if it cannot
+    /// be determined.
+    async fn max_call_depth(&self, request: &TransactionRequest) -> Option<u64>;
+}
+
+/// Settings for a tighter gas ceiling for requests with deep call stacks.
+///
+/// This is best-effort protection: the depth is only as accurate as the [`CallDepthProbe`], and a
+/// loop that reverts within the regular ceiling during estimation is not detected.
+#[derive(Debug, Clone)]
+pub struct DeepCallGasCeiling {
+    /// The probe reporting the call depth of requests.
+    pub probe: Arc<dyn CallDepthProbe>,
+    /// The call depth above which the tighter ceiling applies.
+    pub max_depth: u64,
+    /// The gas ceiling for requests with deeper call stacks.
+    pub ceiling: u64,
+}
+
+impl DeepCallGasCeiling {
+    /// Returns the ceiling for a request that reached `depth`, given its regular ceiling.
+    pub(crate) fn resolve(&self, depth: Option<u64>, ceiling: u64) -> u64 {
+        match depth {
+            Some(depth) if depth > self.max_depth => ceiling.min(self.ceiling),
+            _ => ceiling,
+        }
+    }
+}
+
+/// Returns the gas estimate with a buffer of `percent` on top.
+///
+/// Execution may consume slightly more gas than estimated, e.g. because the state changed in
+/// between, so transactions are signed with some headroom.
+pub(crate) const fn with_gas_buffer(estimate: u64, percent: u64) -> u64 {
+    estimate.saturating_add(estimate.saturating_mul(percent) / 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn most_specific_override_applies() {
+        let destination = Address::with_last_byte(1);
+        let other = Address::with_last_byte(2);
+        let overrides = GasCeilingOverrides::default()
+            .with_destination(destination, 200_000)
+            .with_selector(destination, [1, 2, 3, 4], 500_000);
+
+        // selector
+        assert_eq!(overrides.resolve(Some(destination), &[1, 2, 3, 4, 5], 350_000), 500_000);
+
+        // destination
+        assert_eq!(overrides.resolve(Some(destination), &[4, 3, 2, 1], 350_000), 200_000);
+        assert_eq!(overrides.resolve(Some(destination), &[1, 2, 3], 350_000), 200_000);
+
+        // global
+        assert_eq!(overrides.resolve(Some(other), &[1, 2, 3, 4], 350_000), 350_000);
+        assert_eq!(overrides.resolve(None, &[1, 2, 3, 4], 350_000), 350_000);
+    }
+
+    #[test]
+    fn buffers_estimate() {
+        assert_eq!(with_gas_buffer(50_000, 10), 55_000);
+        assert_eq!(with_gas_buffer(21_000, 0), 21_000);
+        assert_eq!(with_gas_buffer(u64::MAX, 10), u64::MAX);
+    }
+
+    #[test]
+    fn computes_percentile_with_margin() {
+        let config = AdaptiveGasCeilingConfig {
+            percentile: 99,
+            margin_percent: 10,
+            floor: 0,
+            cap: 1_000_000,
+            ..Default::default()
+        };
+        let samples = (1..=100).map(|i| i * 1_000).collect();
+
+        // p99 is 99_000, plus a 10% margin
+        assert_eq!(compute_ceiling(samples, &config), 108_900);
+    }
+
+    #[test]
+    fn clamps_to_floor_and_cap() {
+        let config = AdaptiveGasCeilingConfig {
+            percentile: 50,
+            margin_percent: 0,
+            floor: 30_000,
+            cap: 60_000,
+            ..Default::default()
+        };
+
+        assert_eq!(compute_ceiling(vec![21_000; 10], &config), 30_000);
+        assert_eq!(compute_ceiling(vec![100_000; 10], &config), 60_000);
+        assert_eq!(compute_ceiling(vec![], &config), 60_000);
+    }
+
+    #[test]
+    fn recomputes_periodically() {
+        let config = AdaptiveGasCeilingConfig {
+            percentile: 100,
+            margin_percent: 0,
+            floor: 0,
+            cap: 350_000,
+            sample_size: 10,
+            min_samples: 5,
+            recompute_interval: Duration::from_secs(60),
+        };
+        let start = Instant::now();
+        let ceiling = AdaptiveGasCeiling::new(config, start);
+
+        for estimate in [50_000, 60_000, 70_000, 80_000, 90_000] {
+            ceiling.record(estimate);
+        }
+
+        // not recomputed until the interval elapsed
+        assert_eq!(ceiling.ceiling(start + Duration::from_secs(30)), 350_000);
+        assert_eq!(ceiling.ceiling(start + Duration::from_secs(60)), 90_000);
+
+        // old samples are evicted once the sample size is exceeded
+        for _ in 0..10 {
+            ceiling.record(40_000);
+        }
+        assert_eq!(ceiling.ceiling(start + Duration::from_secs(90)), 90_000);
+        assert_eq!(ceiling.ceiling(start + Duration::from_secs(120)), 40_000);
+    }
+}
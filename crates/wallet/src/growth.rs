@@ -0,0 +1,80 @@
+//! Limits on the state growth caused by sponsored transactions.
+//!
+//! Writing new storage slots grows the state every node keeps forever, a cost the gas ceiling
+//! does not capture well. The number of new slots is only known by executing the request, so it
+//! is reported by a [`StateGrowthProbe`], e.g. backed by a prestate trace in diff mode.
+
+use crate::OdysseyWalletError;
+use alloy_rpc_types::TransactionRequest;
+use jsonrpsee::core::async_trait;
+use std::{fmt::Debug, sync::Arc};
+
+/// Reports how many new storage slots a request writes, e.g. by tracing its execution.
+#[async_trait]
+pub trait StateGrowthProbe: Debug + Send + Sync {
+    /// Returns the number of storage slots that are empty before and set after executing the
+    /// request, or `None` if it cannot be determined.
+    async fn new_storage_slots(&self, request: &TransactionRequest) -> Option<u64>;
+}
+
+/// Settings for rejecting requests that create too many new storage slots.
+///
+/// This is best-effort protection: the count is only as accurate as the [`StateGrowthProbe`], and
+/// requests it cannot trace are not limited.
+#[derive(Debug, Clone)]
+pub struct StateGrowthLimit {
+    /// The probe reporting the new storage slots of requests.
+    pub probe: Arc<dyn StateGrowthProbe>,
+    /// The maximum number of new storage slots a request may create.
+    pub max_new_slots: u64,
+}
+
+impl StateGrowthLimit {
+    /// Ensures the request creates at most the maximum number of new storage slots.
+    pub(crate) async fn check(
+        &self,
+        request: &TransactionRequest,
+    ) -> Result<(), OdysseyWalletError> {
+        match self.probe.new_storage_slots(request).await {
+            Some(slots) if slots > self.max_new_slots => {
+                Err(OdysseyWalletError::ExcessiveStateGrowth { slots, max: self.max_new_slots })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reports the number of slots encoded in the first byte of the calldata.
+    #[derive(Debug)]
+    struct MockStateGrowthProbe;
+
+    #[async_trait]
+    impl StateGrowthProbe for MockStateGrowthProbe {
+        async fn new_storage_slots(&self, request: &TransactionRequest) -> Option<u64> {
+            request.input.input().and_then(|input| input.first()).map(|slots| *slots as u64)
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_excessive_state_growth() {
+        let limit = StateGrowthLimit { probe: Arc::new(MockStateGrowthProbe), max_new_slots: 16 };
+        let request = |slots: Option<u8>| {
+            TransactionRequest::default()
+                .input(slots.map(|slots| vec![slots]).unwrap_or_default().into())
+        };
+
+        // requests within the limit, and requests that cannot be traced, are allowed
+        for slots in [Some(0), Some(16), None] {
+            assert_eq!(limit.check(&request(slots)).await, Ok(()));
+        }
+
+        assert_eq!(
+            limit.check(&request(Some(200))).await,
+            Err(OdysseyWalletError::ExcessiveStateGrowth { slots: 200, max: 16 })
+        );
+    }
+}
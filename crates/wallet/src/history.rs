@@ -0,0 +1,119 @@
+//! The recently sponsored transactions of each account.
+//!
+//! This lets clients show the recent sponsored transactions of an account right after their
+//! submission, without indexing the chain.
+
+use alloy_primitives::{Address, TxHash};
+use parking_lot::Mutex;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// The maximum number of transactions kept per account, the oldest are forgotten first.
+pub const MAX_RECENT_TRANSACTIONS: usize = 32;
+
+/// The maximum number of tracked accounts, the least recently sponsored are forgotten first.
+pub const MAX_TRACKED_ACCOUNTS: usize = 10_000;
+
+/// The recently sponsored transactions of each account.
+#[derive(Debug, Default)]
+pub(crate) struct RecentTransactions {
+    inner: Mutex<TrackedAccounts>,
+}
+
+#[derive(Debug, Default)]
+struct TrackedAccounts {
+    /// The transactions of each account, oldest first, and when the account was last sponsored.
+    transactions: HashMap<Address, (u64, VecDeque<TxHash>)>,
+    /// The tracked accounts by when they were last sponsored.
+    last_sponsored: BTreeMap<u64, Address>,
+    /// Increases with every recorded transaction.
+    sequence: u64,
+}
+
+impl RecentTransactions {
+    /// Records a transaction sponsored for the accounts.
+    pub(crate) fn record(&self, accounts: impl IntoIterator<Item = Address>, hash: TxHash) {
+        let mut inner = self.inner.lock();
+        let inner = &mut *inner;
+        for account in accounts {
+            inner.sequence += 1;
+            let (last, transactions) = inner.transactions.entry(account).or_default();
+            inner.last_sponsored.remove(last);
+            *last = inner.sequence;
+            inner.last_sponsored.insert(*last, account);
+
+            if transactions.back() != Some(&hash) {
+                transactions.push_back(hash);
+            }
+            if transactions.len() > MAX_RECENT_TRANSACTIONS {
+                transactions.pop_front();
+            }
+        }
+
+        while inner.transactions.len() > MAX_TRACKED_ACCOUNTS {
+            let Some((_, account)) = inner.last_sponsored.pop_first() else {
+                break;
+            };
+            inner.transactions.remove(&account);
+        }
+    }
+
+    /// Returns up to `limit` of the most recent transactions of the account, most recent first.
+    pub(crate) fn get(&self, account: &Address, limit: usize) -> Vec<TxHash> {
+        self.inner.lock().transactions.get(account).map_or_else(Vec::new, |(_, transactions)| {
+            transactions.iter().rev().take(limit).copied().collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{B256, U256};
+
+    #[test]
+    fn lists_recent_transactions() {
+        let alice = Address::with_last_byte(0xa);
+        let bob = Address::with_last_byte(0xb);
+        let recent = RecentTransactions::default();
+
+        let hashes: Vec<_> = (1..=3).map(B256::with_last_byte).collect();
+        for hash in &hashes {
+            recent.record([alice], *hash);
+        }
+        recent.record([alice, bob], B256::with_last_byte(4));
+
+        // most recent first
+        let expected: Vec<_> = (1..=4).rev().map(B256::with_last_byte).collect();
+        assert_eq!(recent.get(&alice, 10), expected);
+        assert_eq!(recent.get(&alice, 2), expected[..2]);
+        assert_eq!(recent.get(&bob, 10), vec![B256::with_last_byte(4)]);
+        assert!(recent.get(&Address::ZERO, 10).is_empty());
+
+        // the list of each account is bounded
+        for i in 0..MAX_RECENT_TRANSACTIONS as u64 {
+            recent.record([alice], B256::from(U256::from(i + 100)));
+        }
+        let transactions = recent.get(&alice, usize::MAX);
+        assert_eq!(transactions.len(), MAX_RECENT_TRANSACTIONS);
+        assert!(!transactions.contains(&B256::with_last_byte(4)));
+    }
+
+    #[test]
+    fn forgets_least_recently_sponsored_accounts() {
+        let recent = RecentTransactions::default();
+        let hash = B256::with_last_byte(1);
+        let first = Address::with_last_byte(1);
+        let second = Address::with_last_byte(2);
+
+        recent.record([first], hash);
+        recent.record([second], hash);
+        // the first account is sponsored again, so the second is the least recently sponsored
+        recent.record([first], B256::with_last_byte(2));
+        for i in 0..MAX_TRACKED_ACCOUNTS as u64 - 1 {
+            recent.record([Address::from_word(B256::from(U256::from(i + 3)))], hash);
+        }
+
+        assert_eq!(recent.get(&first, 1), vec![B256::with_last_byte(2)]);
+        assert!(recent.get(&second, 1).is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+//! Heuristic estimation of when a sponsored transaction is included.
+
+use alloy_rpc_types::FeeHistory;
+
+/// The number of recent blocks the estimate is based on.
+pub(crate) const INCLUSION_FEE_HISTORY_BLOCKS: u64 = 10;
+
+/// The reward percentile the priority fee is compared against.
+pub(crate) const INCLUSION_REWARD_PERCENTILE: f64 = 50.0;
+
+/// The gas used ratio above which blocks are considered congested.
+const CONGESTED_GAS_USED_RATIO: f64 = 0.9;
+
+/// The maximum number of blocks estimated.
+const MAX_INCLUSION_BLOCKS: u64 = 64;
+
+/// Estimates in how many blocks a transaction with the given fees is included.
+///
+/// This is a best-effort heuristic based on recent fee history, and not a guarantee:
+///
+/// - If the max fee does not cover the next base fee, the estimate assumes the base fee decreases
+///   by the maximum of 12.5% per block until it is covered.
+/// - If the priority fee is below the average median priority fee of recent blocks, or recent
+///   blocks are congested, one additional block is assumed for each.
+///
+/// Returns `None` if the fee history is empty.
+pub(crate) fn estimate_inclusion_blocks(
+    fee_history: &FeeHistory,
+    max_fee_per_gas: u128,
+    max_priority_fee_per_gas: u128,
+) -> Option<u64> {
+    let mut base_fee = *fee_history.base_fee_per_gas.last()?;
+    let mut blocks = 1;
+
+    // wait for the base fee to drop far enough
+    while base_fee.saturating_add(max_priority_fee_per_gas) > max_fee_per_gas {
+        if blocks >= MAX_INCLUSION_BLOCKS || base_fee == 0 {
+            return Some(MAX_INCLUSION_BLOCKS);
+        }
+        base_fee -= base_fee / 8;
+        blocks += 1;
+    }
+
+    // compete with the median priority fee of recent blocks
+    let rewards: Vec<u128> = fee_history
+        .reward
+        .iter()
+        .flatten()
+        .filter_map(|percentiles| percentiles.first().copied())
+        .collect();
+    if !rewards.is_empty() {
+        let average = rewards.iter().sum::<u128>() / rewards.len() as u128;
+        if max_priority_fee_per_gas < average {
+            blocks += 1;
+        }
+    }
+
+    // account for congestion
+    if !fee_history.gas_used_ratio.is_empty() {
+        let average = fee_history.gas_used_ratio.iter().sum::<f64>()
+            / fee_history.gas_used_ratio.len() as f64;
+        if average > CONGESTED_GAS_USED_RATIO {
+            blocks += 1;
+        }
+    }
+
+    Some(blocks.min(MAX_INCLUSION_BLOCKS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GWEI: u128 = 1_000_000_000;
+
+    fn fee_history(base_fee: u128, reward: u128, gas_used_ratio: f64) -> FeeHistory {
+        FeeHistory {
+            base_fee_per_gas: vec![base_fee; 11],
+            gas_used_ratio: vec![gas_used_ratio; 10],
+            reward: Some(vec![vec![reward]; 10]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn estimates_plausible_inclusion() {
+        // fees cover the base fee and the median priority fee
+        let history = fee_history(10 * GWEI, GWEI, 0.5);
+        assert_eq!(estimate_inclusion_blocks(&history, 11 * GWEI, GWEI), Some(1));
+
+        // priority fee below the median
+        let history = fee_history(10 * GWEI, 2 * GWEI, 0.5);
+        assert_eq!(estimate_inclusion_blocks(&history, 11 * GWEI, GWEI), Some(2));
+
+        // congested blocks
+        let history = fee_history(10 * GWEI, GWEI, 0.95);
+        assert_eq!(estimate_inclusion_blocks(&history, 11 * GWEI, GWEI), Some(2));
+
+        // the base fee has to drop first
+        let history = fee_history(10 * GWEI, GWEI, 0.5);
+        assert_eq!(estimate_inclusion_blocks(&history, 10 * GWEI, GWEI), Some(2));
+        assert_eq!(estimate_inclusion_blocks(&history, GWEI, GWEI), Some(MAX_INCLUSION_BLOCKS));
+
+        // no history
+        assert_eq!(estimate_inclusion_blocks(&FeeHistory::default(), 11 * GWEI, GWEI), None);
+    }
+}
@@ -0,0 +1,86 @@
+//! Randomized delay of submissions that preserves their order.
+
+use parking_lot::Mutex;
+use rand::Rng;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// Hands out [`SubmissionTicket`]s that let submissions proceed in the order the tickets were
+/// taken, regardless of how long each submission is delayed.
+#[derive(Debug, Default)]
+pub(crate) struct OrderedSubmissions {
+    /// Resolves once the submission of the most recently issued ticket is done.
+    last: Mutex<Option<oneshot::Receiver<()>>>,
+}
+
+impl OrderedSubmissions {
+    /// Takes the next ticket.
+    ///
+    /// This should be called while holding the signing permit, so tickets are taken in nonce
+    /// order.
+    pub(crate) fn ticket(&self) -> SubmissionTicket {
+        let (done, next) = oneshot::channel();
+        let previous = self.last.lock().replace(next);
+        SubmissionTicket { previous, _done: done }
+    }
+}
+
+/// A place in the submission order.
+///
+/// The next ticket may submit once this ticket is dropped.
+#[derive(Debug)]
+pub(crate) struct SubmissionTicket {
+    previous: Option<oneshot::Receiver<()>>,
+    _done: oneshot::Sender<()>,
+}
+
+impl SubmissionTicket {
+    /// Waits until all previously issued tickets are done.
+    pub(crate) async fn wait_turn(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            // the sender is dropped once the previous submission is done
+            let _ = previous.await;
+        }
+    }
+}
+
+/// Returns a random delay in `0..=max`.
+pub(crate) fn random_jitter(max: Duration) -> Duration {
+    max.mul_f64(rand::thread_rng().gen_range(0.0..=1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn preserves_order_despite_jitter() {
+        let submissions = OrderedSubmissions::default();
+        let submitted = Arc::new(Mutex::new(Vec::new()));
+
+        let tasks: Vec<_> = (0..10)
+            .map(|nonce| {
+                let mut ticket = submissions.ticket();
+                let submitted = submitted.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(random_jitter(Duration::from_millis(20))).await;
+                    ticket.wait_turn().await;
+                    submitted.lock().push(nonce);
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert_eq!(*submitted.lock(), (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn jitter_is_bounded() {
+        let max = Duration::from_millis(10);
+        assert!((0..100).all(|_| random_jitter(max) <= max));
+        assert_eq!(random_jitter(Duration::ZERO), Duration::ZERO);
+    }
+}
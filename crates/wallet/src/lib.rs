@@ -5,6 +5,27 @@
 //! - `odyssey_sendTransaction` that can perform sequencer-sponsored [EIP-7702][eip-7702]
 //!   delegations and send other sequencer-sponsored transactions on behalf of EOAs with delegated
 //!   code.
+//! - `wallet_getCapabilities` that reports the capabilities of the wallet, including the version of
+//!   the wallet protocol and the delegation contracts the sequencer sponsors.
+//! - `wallet_health` that reports whether the node is synced.
+//! - `wallet_sendTransactionWithRaw`, or `wallet_sendTransactionVerbose`, that behaves like
+//!   `odyssey_sendTransaction`, but also returns the signed transaction exactly as it was
+//!   submitted, with the nonce, gas limit and max fee per gas it was signed with.
+//! - `wallet_estimateSponsorship` that estimates the cost of sponsoring a transaction, including
+//!   the L1 data fee on OP stack chains.
+//! - `wallet_estimateCalls` that estimates the gas of each call of a batch individually.
+//! - `wallet_sendCalls` that submits a batch of calls as a bundle of sponsored transactions, see
+//!   [EIP-5792][eip-5792].
+//! - `wallet_recentTransactions` that lists the transactions recently sponsored for an account.
+//! - `wallet_getCallsStatus` that reports whether the transactions of a bundle, or a single
+//!   transaction, are included.
+//! - `wallet_batchExecution` that reports whether a batch of calls is executed atomically.
+//! - `wallet_errorCatalog` that lists every error the wallet may return.
+//! - `wallet_sponsorAddress` that returns the address of the account paying for sponsored
+//!   transactions.
+//! - `wallet_delegationInfo` that reports whether and to what an account is delegated.
+//! - `wallet_auditLog` that lets operators page through the recent requests to sponsor
+//!   transactions.
 //!
 //! # Restrictions
 //!
@@ -12,32 +33,209 @@
 //! rudimentary abuse of the sequencer's funds. For example, transactions cannot contain any
 //! `value`.
 //!
+//! Only [EIP-1559][eip-1559] and [EIP-7702][eip-7702] transactions are sponsored, both may carry
+//! an access list. Legacy and [EIP-2930][eip-2930] requests, i.e. requests with a gas price or an
+//! explicit type of `0x0` or `0x1`, are rejected, as are requests with any blob field or another
+//! explicit type.
+//!
+//! [eip-1559]: https://eips.ethereum.org/EIPS/eip-1559
+//! [eip-2930]: https://eips.ethereum.org/EIPS/eip-2930
 //! [eip-5792]: https://eips.ethereum.org/EIPS/eip-5792
 //! [eip-7702]: https://eips.ethereum.org/EIPS/eip-7702
 
 #![cfg_attr(not(test), warn(unused_crate_dependencies))]
 
-use alloy_eips::BlockId;
+use alloy_eips::{eip7702::SignedAuthorization, BlockId, BlockNumberOrTag};
 use alloy_network::{
     eip2718::Encodable2718, Ethereum, EthereumWallet, NetworkWallet, TransactionBuilder,
+    TransactionBuilderError,
 };
-use alloy_primitives::{Address, ChainId, TxHash, TxKind, U256};
-use alloy_rpc_types::TransactionRequest;
+use alloy_primitives::{hex, Address, Bytes, ChainId, TxHash, TxKind, B256, U256, U64};
+use alloy_rpc_types::{state::EvmOverrides, TransactionRequest};
 use jsonrpsee::{
-    core::{async_trait, RpcResult},
+    core::{async_trait, RpcResult, SubscriptionResult},
     proc_macros::rpc,
+    ConnectionId, Extensions, PendingSubscriptionSink, SubscriptionMessage,
 };
-use metrics::Counter;
+use metrics::{Counter, Gauge, Histogram};
 use metrics_derive::Metrics;
-use reth_rpc_eth_api::helpers::{EthCall, EthTransactions, FullEthApi, LoadFee, LoadState};
-use reth_storage_api::{StateProvider, StateProviderFactory};
+use reth_rpc_eth_api::helpers::{
+    EthApiSpec, EthCall, EthFees, EthTransactions, FullEthApi, LoadFee, LoadState,
+};
+use reth_storage_api::{HeaderProvider, StateProvider, StateProviderBox, StateProviderFactory};
 use revm_primitives::Bytecode;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use tracing::{trace, warn};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::RangeInclusive,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tracing::{debug, info, info_span, trace, warn, Instrument};
 
+use parking_lot::RwLock;
 use reth_optimism_rpc as _;
-use tokio::sync::Mutex;
+
+mod age;
+
+mod alert;
+pub use alert::{BudgetAlert, BudgetAlertSource, BudgetAlerts, MAX_PENDING_BUDGET_ALERTS};
+
+mod audit;
+use audit::AuditLog;
+pub use audit::{AuditEntry, AuditLogPage, OperatorApiKey, MAX_AUDIT_ENTRIES, MAX_AUDIT_PAGE_SIZE};
+
+mod authorization;
+use authorization::resolve_contradictory_authorizations;
+pub use authorization::ContradictoryAuthorizations;
+
+mod batch;
+use batch::SubmissionBatcher;
+
+mod balance;
+pub use balance::{balance_monitor, BalanceAlertLevel, BalanceMonitorConfig, BalanceThreshold};
+
+mod bundle;
+use bundle::{Bundles, TransactionState};
+pub use bundle::{CallReceipt, CallStatus, CallsStatus, SendCallsParams, MAX_TRACKED_BUNDLES};
+
+mod catalog;
+pub use catalog::{error_catalog, ErrorCatalogEntry, ErrorField};
+
+mod confirmation;
+use confirmation::record_gas_estimate_error;
+pub use confirmation::ConfirmationTracking;
+
+mod counters;
+pub use counters::MetricsSnapshot;
+use counters::{observed, CounterTotals};
+
+mod correlation;
+use correlation::resolve_correlation_id;
+pub use correlation::MAX_CORRELATION_ID_LEN;
+
+mod cohort;
+use cohort::is_in_cohort;
+
+mod concurrency;
+pub use concurrency::{EstimationLimit, SaturatedEstimations};
+
+mod budget;
+pub use budget::{SpendingCapConfig, SpendingCapStatus, SponsorshipBudgetConfig};
+
+mod config;
+use config::ActiveConfig;
+pub use config::{
+    ConfigError, MethodFlags, OdysseyWalletConfig, WalletMethod, DEFAULT_GAS_BUFFER_PERCENT,
+    DEFAULT_GAS_CEILING, DEFAULT_MAX_CALLDATA_BYTES,
+};
+
+mod delegation;
+pub use delegation::DelegationInfo;
+
+mod destroyed;
+pub use destroyed::DestroyedTargetCheck;
+
+mod estimate;
+use estimate::{batch_requests, decode_l1_fee, get_l1_fee_calldata};
+pub use estimate::{
+    Call, CallsEstimate, GasBreakdown, GasEstimateDetails, SponsorshipEstimate, GAS_PRICE_ORACLE,
+};
+
+mod freshness;
+pub use freshness::{FeeDataMaxAge, StaleFeeDataPolicy};
+
+mod gas;
+use gas::with_gas_buffer;
+pub use gas::{AdaptiveGasCeilingConfig, CallDepthProbe, DeepCallGasCeiling, GasCeilingOverrides};
+
+mod growth;
+pub use growth::{StateGrowthLimit, StateGrowthProbe};
+
+mod history;
+use history::RecentTransactions;
+pub use history::{MAX_RECENT_TRANSACTIONS, MAX_TRACKED_ACCOUNTS};
+
+mod inclusion;
+use inclusion::{
+    estimate_inclusion_blocks, INCLUSION_FEE_HISTORY_BLOCKS, INCLUSION_REWARD_PERCENTILE,
+};
+
+mod jitter;
+use jitter::{random_jitter, OrderedSubmissions};
+
+mod pending;
+use pending::PendingReservation;
+pub use pending::{ConflictingDelegations, PendingDelegationTracking};
+
+mod persist;
+pub use persist::{
+    FileStateStore, RateLimitWindowSnapshot, SpendingCapSnapshot, StateSnapshot, StateStore,
+    STATE_SNAPSHOT_VERSION,
+};
+
+mod pattern;
+pub use pattern::{PatternDetection, PatternDetector, RepeatedCalldataDetector, RequestMetadata};
+
+mod maintenance;
+pub use maintenance::{MaintenanceSchedule, MaintenanceWindow};
+
+mod min_fee;
+pub use min_fee::{BelowMinimumFee, MinimumFeeCheck};
+
+mod nonce;
+use nonce::{is_nonce_too_low, SignerNonces, SignerPermit};
+
+mod oracle;
+pub use oracle::{
+    AttestationVerifier, CachedVerificationOracle, InMemoryRiskOracle, NoopAttestationVerifier,
+    NoopRiskOracle, NoopVerificationOracle, RiskOracle, VerificationOracle,
+};
+
+#[cfg(feature = "explorer")]
+mod explorer;
+#[cfg(feature = "explorer")]
+pub use explorer::ExplorerVerificationOracle;
+
+mod priority_fee;
+pub use priority_fee::{PriorityFee, DEFAULT_PRIORITY_FEE};
+
+mod proxy;
+pub use proxy::{ProxyDelegationPolicy, EIP1967_IMPLEMENTATION_SLOT};
+
+mod quota;
+use quota::QuotaReservation;
+
+mod registry;
+pub use registry::{WhitelistRegistryConfig, GET_APPROVED_TARGETS_SELECTOR};
+
+mod rate_limit;
+pub use rate_limit::{GlobalRateLimitConfig, RateLimitBackoff, RateLimitConfig};
+use rate_limit::{RateLimitExceeded, Window};
+
+mod response;
+
+mod shadow;
+pub use response::{
+    BatchExecution, RoutingReason, SendTransactionResponse, SponsoredTxKind, SubmissionOutcome,
+    WalletHealth,
+};
+use shadow::{Divergence, MAX_SHADOW_EVALUATIONS};
+
+mod value;
+use value::explain_estimate_error;
+
+mod whitelist;
+use whitelist::DelegationWhitelist;
+pub use whitelist::{DuplicateWhitelistEntries, WhitelistSources};
+
+mod system;
+pub use system::{
+    SystemAddresses, BEACON_ROOTS_ADDRESS, OP_L1_ATTRIBUTES_DEPOSITOR, OP_PREDEPLOY_ADDRESSES,
+    PRECOMPILE_ADDRESSES,
+};
 
 /// The capability to perform [EIP-7702][eip-7702] delegations, sponsored by the sequencer.
 ///
@@ -45,16 +243,91 @@ use tokio::sync::Mutex;
 /// account delegates to one of the addresses specified within this capability.
 ///
 /// [eip-7702]: https://eips.ethereum.org/EIPS/eip-7702
-#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Eq, PartialEq, Deserialize, Serialize)]
 pub struct DelegationCapability {
     /// A list of valid delegation contracts.
     pub addresses: Vec<Address>,
 }
 
+impl FromStr for DelegationCapability {
+    type Err = hex::FromHexError;
+
+    /// Parses a comma-separated list of addresses, e.g. from the command line.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let addresses = s
+            .split(',')
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .map(Address::from_str)
+            .collect::<Result<_, _>>()?;
+        Ok(Self { addresses })
+    }
+}
+
+/// The version of the Odyssey wallet protocol implemented by this crate.
+///
+/// This is independent of the crate version, and is bumped whenever the sponsorship rules or the
+/// shape of a response change, so clients can feature-detect across deployments.
+pub const WALLET_PROTOCOL_VERSION: &str = "1.6.0";
+
+/// The capabilities of the wallet on a single chain.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct Capabilities {
+    /// The version of the Odyssey wallet protocol, see [`WALLET_PROTOCOL_VERSION`].
+    pub version: String,
+    /// The delegation contracts the sequencer sponsors.
+    pub delegation: DelegationCapability,
+}
+
+/// The capabilities of the wallet, keyed by chain id.
+///
+/// See also [EIP-5792][eip-5792].
+///
+/// [eip-5792]: https://eips.ethereum.org/EIPS/eip-5792
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct WalletCapabilities(pub HashMap<U64, Capabilities>);
+
+impl WalletCapabilities {
+    /// Returns the capabilities of this implementation on the given chain, sponsoring delegations
+    /// to the contracts of `delegation`.
+    pub fn new(chain_id: ChainId, delegation: DelegationCapability) -> Self {
+        Self(HashMap::from([(
+            U64::from(chain_id),
+            Capabilities { version: WALLET_PROTOCOL_VERSION.to_string(), delegation },
+        )]))
+    }
+}
+
 /// Odyssey `wallet_` RPC namespace.
 #[cfg_attr(not(test), rpc(server, namespace = "wallet"))]
 #[cfg_attr(test, rpc(server, client, namespace = "wallet"))]
 pub trait OdysseyWalletApi {
+    /// Get the capabilities of the wallet.
+    ///
+    /// See also [EIP-5792][eip-5792].
+    ///
+    /// [eip-5792]: https://eips.ethereum.org/EIPS/eip-5792
+    #[method(name = "getCapabilities")]
+    fn get_capabilities(&self) -> RpcResult<WalletCapabilities>;
+
+    /// Get the health of the wallet.
+    #[method(name = "health")]
+    fn health(&self) -> RpcResult<WalletHealth>;
+
+    /// Get the catalog of all errors the wallet may return, with their codes, message templates
+    /// and the fields they carry.
+    #[method(name = "errorCatalog")]
+    fn error_catalog(&self) -> RpcResult<Vec<ErrorCatalogEntry>>;
+
+    /// Get the address of the account that pays for all sponsored transactions.
+    #[method(name = "sponsorAddress")]
+    fn sponsor_address(&self) -> RpcResult<Address>;
+
+    /// Get the delegation status of an account in the latest state: whether it is delegated, to
+    /// what target, whether the target is whitelisted, and the code hash of the target.
+    #[method(name = "delegationInfo")]
+    fn delegation_info(&self, address: Address) -> RpcResult<DelegationInfo>;
+
     /// Send a sequencer-sponsored transaction.
     ///
     /// The transaction will only be processed if:
@@ -67,10 +340,141 @@ pub trait OdysseyWalletApi {
     /// The sequencer will sign the transaction and inject it into the transaction pool, provided it
     /// is valid. The nonce is managed by the sequencer.
     ///
+    /// The request is logged with a correlation id, which is either provided by the client or
+    /// generated by the wallet. Provided ids must be at most [`MAX_CORRELATION_ID_LEN`]
+    /// characters of ASCII letters, digits, `-`, `_` or `.`.
+    ///
+    /// If an [`AttestationVerifier`] is configured, the client must attach an attestation token
+    /// that is valid for the accounts the transaction is sponsored for.
+    ///
     /// [eip-7702]: https://eips.ethereum.org/EIPS/eip-7702
     /// [eip-1559]: https://eips.ethereum.org/EIPS/eip-1559
-    #[method(name = "sendTransaction", aliases = ["odyssey_sendTransaction"])]
-    async fn send_transaction(&self, request: TransactionRequest) -> RpcResult<TxHash>;
+    #[method(name = "sendTransaction", aliases = ["odyssey_sendTransaction"], with_extensions)]
+    async fn send_transaction(
+        &self,
+        request: TransactionRequest,
+        correlation_id: Option<String>,
+        attestation: Option<String>,
+    ) -> RpcResult<TxHash>;
+
+    /// Send a sequencer-sponsored transaction, returning the signed transaction alongside its
+    /// hash.
+    ///
+    /// This behaves exactly like `wallet_sendTransaction`, but additionally returns the EIP-2718
+    /// encoded transaction as it was submitted, so clients can archive it, the nonce, gas limit
+    /// and max fee per gas the sequencer signed it with, a best-effort estimate of the number of
+    /// blocks until the transaction is included, and the correlation id of the request.
+    #[method(
+        name = "sendTransactionWithRaw",
+        aliases = ["wallet_sendTransactionVerbose"],
+        with_extensions
+    )]
+    async fn send_transaction_with_raw(
+        &self,
+        request: TransactionRequest,
+        correlation_id: Option<String>,
+        attestation: Option<String>,
+    ) -> RpcResult<SendTransactionResponse>;
+
+    /// Estimate the cost of sponsoring a transaction.
+    ///
+    /// The request is subject to the same rules as in `wallet_sendTransaction`, but the
+    /// transaction is neither signed nor submitted, and it neither waits for the signing permit
+    /// nor reserves a nonce. This makes it a dry run of `wallet_sendTransaction`, which is why it
+    /// is also served as `wallet_validateTransaction`: dapps can show whether a request will be
+    /// sponsored, and at what gas and fees, before sending it.
+    ///
+    /// On OP stack chains, the cost includes the L1 data fee as reported by the
+    /// [`GAS_PRICE_ORACLE`]. If the L1 data fee is not available, only the L2 execution cost is
+    /// reported.
+    #[method(name = "estimateSponsorship", aliases = ["wallet_validateTransaction"])]
+    async fn estimate_sponsorship(
+        &self,
+        request: TransactionRequest,
+    ) -> RpcResult<SponsorshipEstimate>;
+
+    /// Estimate the gas of each call of a batch individually.
+    ///
+    /// Each call is subject to the same rules as in `wallet_sendTransaction`, and the combined
+    /// calldata is subject to the batch calldata limit. This is enabled alongside
+    /// `wallet_estimateSponsorship`.
+    ///
+    /// Each call is estimated on its own against the latest state. If a call depends on state
+    /// changed by an earlier call of the batch, its estimate is only approximate, and the sum of
+    /// the estimates may differ from the gas of the batch when executed as a whole.
+    #[method(name = "estimateCalls")]
+    async fn estimate_calls(&self, calls: Vec<Call>) -> RpcResult<CallsEstimate>;
+
+    /// Send a batch of sequencer-sponsored calls, returning the id of the bundle.
+    ///
+    /// Each call is subject to the same rules as in `wallet_sendTransaction`, the combined
+    /// calldata is subject to the batch calldata limit, and the combined buffered gas estimate is
+    /// subject to the gas ceiling. If `from` is set, every call must be sent to it.
+    ///
+    /// The calls are submitted in order, each as its own transaction, so some may succeed while
+    /// others fail. Calls that must execute atomically should be executed by the delegated code
+    /// of the account within a single call. If a call fails to be submitted, the remaining calls
+    /// are not submitted. If earlier calls were already submitted, they are tracked as a bundle,
+    /// and its id and their hashes are returned with the error.
+    ///
+    /// See also [EIP-5792][eip-5792].
+    ///
+    /// [eip-5792]: https://eips.ethereum.org/EIPS/eip-5792
+    #[method(name = "sendCalls", with_extensions)]
+    async fn send_calls(&self, params: SendCallsParams) -> RpcResult<B256>;
+
+    /// Get up to `limit` of the transactions recently sponsored for an account, most recent
+    /// first.
+    ///
+    /// Transactions are sponsored for the account they are sent to, and for the authorities of
+    /// their authorizations. At most [`MAX_RECENT_TRANSACTIONS`] transactions are kept for each
+    /// of the [`MAX_TRACKED_ACCOUNTS`] most recently sponsored accounts.
+    #[method(name = "recentTransactions")]
+    fn recent_transactions(&self, account: Address, limit: Option<usize>)
+        -> RpcResult<Vec<TxHash>>;
+
+    /// Get up to `limit` of the recent requests to sponsor a transaction, accepted or rejected,
+    /// most recent first.
+    ///
+    /// Pass the returned cursor to get the next page of older requests. Entries only summarize
+    /// requests, and at most [`MAX_AUDIT_ENTRIES`] are kept. This requires the operator api key,
+    /// and is disabled if none is configured.
+    #[method(name = "auditLog")]
+    fn audit_log(
+        &self,
+        api_key: String,
+        cursor: Option<String>,
+        limit: Option<usize>,
+    ) -> RpcResult<AuditLogPage>;
+
+    /// Get the status of a bundle submitted by `wallet_sendCalls`, or of a single transaction
+    /// given its hash, with the receipts of the included transactions.
+    ///
+    /// Only the [`MAX_TRACKED_BUNDLES`] most recent bundles are tracked.
+    ///
+    /// See also [EIP-5792][eip-5792].
+    ///
+    /// [eip-5792]: https://eips.ethereum.org/EIPS/eip-5792
+    #[method(name = "getCallsStatus")]
+    async fn get_calls_status(&self, id: B256) -> RpcResult<CallsStatus>;
+
+    /// Get whether a batch of calls is executed atomically, or sequentially with possible partial
+    /// success.
+    #[method(name = "batchExecution")]
+    fn batch_execution(&self, calls: Vec<Call>) -> RpcResult<BatchExecution>;
+
+    /// Subscribe to alerts on the sponsorship budget and the balance of the signer crossing the
+    /// configured thresholds, and recovering above them.
+    ///
+    /// This is only enabled if [budget alerts](OdysseyWalletConfig::with_budget_alerts) are
+    /// configured. Subscribers that fall more than [`MAX_PENDING_BUDGET_ALERTS`] alerts behind
+    /// miss the oldest ones.
+    #[subscription(
+        name = "subscribeBudget" => "budget",
+        unsubscribe = "unsubscribeBudget",
+        item = BudgetAlert
+    )]
+    async fn subscribe_budget(&self) -> SubscriptionResult;
 }
 
 /// Errors returned by the wallet API.
@@ -92,6 +496,15 @@ pub enum OdysseyWalletError {
     /// Requests with the nonce field set are rejected, as this is managed by the sequencer.
     #[error("tx nonce is set")]
     NonceSet,
+    /// The request is for a legacy, [EIP-2930][eip-2930] or any other transaction type than
+    /// EIP-1559 and EIP-7702, which are not sponsored.
+    ///
+    /// [eip-2930]: https://eips.ethereum.org/EIPS/eip-2930
+    #[error("unsupported transaction type {tx_type}")]
+    UnsupportedTransactionType {
+        /// The type of the requested transaction.
+        tx_type: u8,
+    },
     /// The to field of the transaction was invalid.
     ///
     /// The destination is invalid if:
@@ -100,19 +513,364 @@ pub enum OdysseyWalletError {
     /// - The bytecode is not an EIP-7702 delegation designator
     #[error("the destination of the transaction is not a delegated account")]
     IllegalDestination,
+    /// The transaction targets a precompile or a system contract.
+    ///
+    /// This applies to both the destination of the transaction and the address it delegates to.
+    #[error("the transaction targets a system address: {address}")]
+    SystemAddressNotAllowed {
+        /// The system address that was targeted.
+        address: Address,
+    },
+    /// The transaction is sent to, or authorized by, an account that is banned from sponsorship.
+    #[error("address {address} is blocked")]
+    AddressBlocked {
+        /// The blocked account.
+        address: Address,
+    },
+    /// The signature of an [EIP-7702][eip-7702] authorization is invalid.
+    ///
+    /// [eip-7702]: https://eips.ethereum.org/EIPS/eip-7702
+    #[error("invalid authorization signature")]
+    InvalidAuthorizationSignature,
+    /// The nonce of an [EIP-7702][eip-7702] authorization is outside of the accepted window.
+    ///
+    /// The nonce must be at least the current nonce of the authority, and at most the current
+    /// nonce plus the configured tolerance. Authorizations with a nonce outside of this window
+    /// can never be or are unlikely to ever be valid.
+    ///
+    /// [eip-7702]: https://eips.ethereum.org/EIPS/eip-7702
+    #[error(
+        "authorization nonce {nonce} for {authority} is out of range, current nonce is {current}"
+    )]
+    AuthorizationNonceOutOfRange {
+        /// The authority that signed the authorization.
+        authority: Address,
+        /// The nonce of the authorization.
+        nonce: u64,
+        /// The current nonce of the authority.
+        current: u64,
+    },
+    /// The transaction calls into, or delegates to, a target that is flagged by the
+    /// [`RiskOracle`].
+    #[error("the delegation target {target} is flagged")]
+    DestinationFlagged {
+        /// The flagged delegation target.
+        target: Address,
+    },
+    /// The transaction calls into, or delegates to, a target whose code has been removed.
+    #[error("the delegation target {target} has been destroyed")]
+    DestinationDestroyed {
+        /// The destroyed delegation target.
+        target: Address,
+    },
+    /// The transaction calls into, or delegates to, a target that was deployed too recently.
+    #[error("the delegation target {target} was deployed too recently")]
+    DelegationTargetTooNew {
+        /// The delegation target.
+        target: Address,
+    },
+    /// The destination was not first delegated within the configured cohort.
+    #[error("the delegation of {address} is not eligible for sponsorship")]
+    CohortNotEligible {
+        /// The destination of the transaction.
+        address: Address,
+    },
+    /// The blob fields of the transaction are inconsistent.
+    ///
+    /// A max fee per blob gas was set without any blobs, or blobs were attached without a max fee
+    /// per blob gas.
+    #[error("blob fee and blobs must be set together")]
+    InconsistentBlobFields,
+    /// The transaction carries blobs, or blob fields.
+    ///
+    /// Only EIP-1559 and EIP-7702 transactions are sponsored, as the blob gas of EIP-4844
+    /// transactions is not charged against the budget.
+    #[error("blob transactions are not sponsored")]
+    BlobsNotSponsored,
+    /// The transaction calls into, or delegates to, a target whose source is not verified by the
+    /// [`VerificationOracle`].
+    #[error("the delegation target {target} is not verified")]
+    DestinationUnverified {
+        /// The unverified delegation target.
+        target: Address,
+    },
+    /// The transaction calls into, or delegates to, an upgradeable proxy whose current
+    /// implementation is not whitelisted.
+    ///
+    /// This catches whitelisted proxies that were upgraded to other logic.
+    #[error("the implementation {implementation} of the proxy {proxy} is not whitelisted")]
+    ProxyImplementationNotWhitelisted {
+        /// The proxy the account delegates to.
+        proxy: Address,
+        /// The current implementation of the proxy.
+        implementation: Address,
+    },
+    /// The delegated account has transactions pending in the pool, which may change the state the
+    /// sponsored call executes against.
+    ///
+    /// This is a heuristic to avoid sponsoring calls that are likely to fail, e.g. because a
+    /// pending transaction consumes a nonce kept by the delegation contract. It may reject calls
+    /// that would have succeeded, and does not detect conflicts that are not visible in the pool.
+    #[error("the delegated account {account} has pending transactions that may conflict")]
+    DelegatedStateConflict {
+        /// The delegated account.
+        account: Address,
+    },
+    /// An authorization conflicts with a recently submitted delegation of the same authority to
+    /// another target, which is likely still pending.
+    #[error("{authority} has a pending delegation to {pending}")]
+    ConflictingPendingDelegation {
+        /// The authority of the authorization.
+        authority: Address,
+        /// The target of the pending delegation.
+        pending: Address,
+    },
+    /// The node is not fully synced, so sponsorship decisions would be based on stale state.
+    #[error("node is not synced")]
+    NodeNotSynced,
+    /// The chain has not reached the block sponsorship starts at.
+    #[error("sponsorship starts at block {start}, current block is {current}")]
+    SponsorshipNotStarted {
+        /// The current block number.
+        current: u64,
+        /// The block number sponsorship starts at.
+        start: u64,
+    },
+    /// Sponsorship is paused for a scheduled maintenance window.
+    #[error("sponsorship is paused for maintenance until {ends_at}")]
+    MaintenanceWindow {
+        /// When the window ends, in unix seconds.
+        ends_at: u64,
+    },
+    /// The combined calldata of the calls in a batch exceeds the configured maximum.
+    #[error("batch calldata of {size} bytes exceeds the maximum of {max} bytes")]
+    BatchCalldataTooLarge {
+        /// The combined calldata of the batch in bytes.
+        size: usize,
+        /// The maximum combined calldata in bytes.
+        max: usize,
+    },
+    /// The calldata of the request exceeds the configured maximum.
+    #[error("calldata of {size} bytes exceeds the maximum of {max} bytes")]
+    CalldataTooLarge {
+        /// The calldata of the request in bytes.
+        size: usize,
+        /// The maximum calldata in bytes.
+        max: usize,
+    },
+    /// The request calls a function that is not allowed.
+    #[error("function selector {selector} is not allowed")]
+    SelectorNotAllowed {
+        /// The selector of the function, or all of the calldata if shorter than a selector.
+        selector: Bytes,
+    },
+    /// The [`AttestationVerifier`] rejected the attestation token of the request, or the request
+    /// has none.
+    #[error("a valid attestation is required")]
+    AttestationRequired,
+    /// The transaction carries an authorization list, but [EIP-7702][eip-7702] is not active on
+    /// the chain.
+    ///
+    /// [eip-7702]: https://eips.ethereum.org/EIPS/eip-7702
+    #[error("EIP-7702 is not supported on this chain")]
+    Eip7702NotSupported,
+    /// The authorization list of an EIP-7702 transaction is empty.
+    #[error("authorization list is empty")]
+    EmptyAuthorizationList,
+    /// An authorization is only valid on another chain.
+    #[error("authorization for chain {chain_id} is not valid on this chain")]
+    AuthorizationChainIdMismatch {
+        /// The chain id of the authorization.
+        chain_id: u64,
+    },
+    /// An authorization is universal, i.e. valid on any chain, which is not allowed.
+    #[error("universal authorizations with chain id 0 are not allowed")]
+    UniversalAuthorizationNotAllowed,
+    /// The authorization list delegates an authority to different targets.
+    ///
+    /// Only the last authorization of an authority is effective, so the earlier ones only waste
+    /// gas.
+    #[error("{authority} delegates to different targets in the same authorization list")]
+    ContradictoryAuthorizations {
+        /// The authority of the authorizations.
+        authority: Address,
+    },
+    /// The request is for another chain.
+    #[error("the request for chain {chain_id} is not valid on this chain")]
+    ChainIdMismatch {
+        /// The chain id of the request.
+        chain_id: u64,
+    },
+    /// The delegation target reached its quota of sponsored calls within the last 24 hours.
+    #[error("the delegation target {target} reached its daily quota")]
+    DestinationQuotaExceeded {
+        /// The delegation target.
+        target: Address,
+    },
+    /// The sponsorship budget, or what is left of the spending cap, does not cover the cost of the
+    /// transaction.
+    #[error("sponsorship budget exhausted")]
+    BudgetExhausted,
+    /// The balance of the sponsor is below the configured minimum, so sponsorship is paused
+    /// until it is topped up.
+    #[error("sponsor balance {balance} is below the minimum of {minimum}")]
+    SponsorInsufficientFunds {
+        /// The balance of the sponsor in wei.
+        balance: U256,
+        /// The configured minimum balance in wei.
+        minimum: U256,
+    },
+    /// The request matches a suspicious pattern, e.g. that of a scripted sybil farm.
+    #[error("suspicious activity")]
+    SuspiciousActivity,
+    /// The encoded transaction exceeds the configured maximum size.
+    #[error("transaction of {size} bytes exceeds the maximum of {max} bytes")]
+    TransactionTooLarge {
+        /// The size of the encoded transaction in bytes.
+        size: usize,
+        /// The maximum size in bytes.
+        max: usize,
+    },
+    /// The RLP encoded authorization list exceeds the configured maximum size.
+    #[error("authorization list of {size} bytes exceeds the maximum of {max} bytes")]
+    AuthorizationListTooLarge {
+        /// The size of the encoded authorization list in bytes.
+        size: usize,
+        /// The maximum size in bytes.
+        max: usize,
+    },
     /// The transaction request was invalid.
     ///
     /// This is likely an internal error, as most of the request is built by the sequencer.
     #[error("invalid tx request")]
     InvalidTransactionRequest,
+    /// The transaction could not be built, as fields required by its type are not set.
+    ///
+    /// Like [`InvalidTransactionRequest`](Self::InvalidTransactionRequest), this is likely an
+    /// internal error.
+    #[error("{tx_type} transaction is missing fields: {}", fields.join(", "))]
+    MissingTransactionFields {
+        /// The type of the transaction.
+        tx_type: String,
+        /// The names of the missing fields.
+        fields: Vec<&'static str>,
+    },
+    /// The signer of the wallet failed to sign the transaction.
+    #[error("failed to sign transaction: {reason}")]
+    SigningFailed {
+        /// The error reported by the signer.
+        reason: String,
+    },
+    /// The correlation id provided by the client is too long or contains invalid characters.
+    #[error("invalid correlation id")]
+    InvalidCorrelationId,
+    /// The cursor of a `wallet_auditLog` request was not returned by a previous request.
+    #[error("invalid audit log cursor")]
+    InvalidAuditCursor,
+    /// The operator api key of the request is invalid.
+    #[error("invalid operator api key")]
+    Unauthorized,
+    /// Neither a tracked bundle nor a known transaction has the given id.
+    #[error("unknown bundle or transaction {id}")]
+    UnknownBundle {
+        /// The id of the bundle or hash of the transaction.
+        id: B256,
+    },
+    /// A call of a `wallet_sendCalls` bundle was rejected after earlier calls were submitted.
+    ///
+    /// The submitted calls are tracked under the bundle id, so their status can be queried with
+    /// `wallet_getCallsStatus`.
+    #[error("bundle {id} aborted after submitting some of its calls: {reason}")]
+    BundlePartiallySubmitted {
+        /// The id of the bundle.
+        id: B256,
+        /// The hashes of the submitted calls.
+        hashes: Vec<TxHash>,
+        /// The message of the error the call was rejected with.
+        reason: String,
+    },
+    /// The destination reverts unless it is called with a non-zero value, which is never
+    /// sponsored.
+    #[error("the destination requires a non-zero value, which is not sponsored")]
+    DestinationRequiresValue,
     /// The request was estimated to consume too much gas.
     ///
     /// The gas usage by each request is limited to counteract draining the sequencers funds.
-    #[error("request would use too much gas: estimated {estimate}")]
+    #[error("request would use too much gas: estimated {estimate}, limit is {limit}")]
     GasEstimateTooHigh {
-        /// The amount of gas the request was estimated to consume.
+        /// The amount of gas the request was estimated to consume, including the configured
+        /// buffer.
+        estimate: u64,
+        /// The gas ceiling that applies to the request.
+        limit: u64,
+    },
+    /// The gas estimate per byte of calldata is outside the configured band.
+    ///
+    /// This does not fit the profile of a regular call to a delegated account, e.g. because the
+    /// calldata is used for data availability, or the execution is disproportionately expensive.
+    #[error("anomalous gas profile: {gas_per_byte} gas per calldata byte")]
+    AnomalousGasProfile {
+        /// The gas estimate per byte of calldata.
+        gas_per_byte: u64,
+    },
+    /// The transaction creates more new storage slots than allowed, see
+    /// [`StateGrowthLimit`].
+    #[error("transaction creates {slots} new storage slots, exceeding the maximum of {max}")]
+    ExcessiveStateGrowth {
+        /// The number of new storage slots.
+        slots: u64,
+        /// The maximum number of new storage slots.
+        max: u64,
+    },
+    /// The gas limit set by the client exceeds the estimate.
+    #[error("gas limit {gas_limit} exceeds the estimate of {estimate}")]
+    GasLimitAboveEstimate {
+        /// The gas limit set by the client.
+        gas_limit: u64,
+        /// The estimated gas.
         estimate: u64,
     },
+    /// The max fee per gas is below the minimum accepted by the pool.
+    #[error("max fee per gas {max_fee_per_gas} is below the minimum of {minimum}")]
+    FeeBelowMinimum {
+        /// The max fee per gas of the transaction.
+        max_fee_per_gas: u128,
+        /// The minimum max fee per gas accepted by the pool.
+        minimum: u128,
+    },
+    /// The fee data was derived from a block older than the configured maximum age.
+    #[error("fee data is stale: latest block is {age}s old")]
+    StaleFeeData {
+        /// The age of the latest block, in seconds.
+        age: u64,
+    },
+    /// The nonce of the signer advanced faster than the configured rate.
+    #[error("signer nonce rate limit exceeded")]
+    SignerNonceRateLimited,
+    /// The limit on requests across all callers was exceeded.
+    #[error("global rate limit exceeded")]
+    GlobalRateLimited,
+    /// The client connection, or an account the request is sponsored for, exceeded its rate
+    /// limit.
+    ///
+    /// The advised delay and the escalation level are also returned as the error data.
+    #[error("rate limited, retry after {retry_after}s (escalation {escalation})")]
+    RateLimited {
+        /// How long the client is advised to wait before retrying, in seconds.
+        retry_after: u64,
+        /// The number of consecutive times the client exceeded its limit.
+        escalation: u32,
+    },
+    /// The wallet is handling too many requests, e.g. the request timed out waiting for its turn,
+    /// or too many transactions are waiting to be submitted.
+    #[error("wallet is overloaded")]
+    Overloaded,
+    /// The method is disabled on this deployment.
+    #[error("method {method} is disabled")]
+    MethodDisabled {
+        /// The name of the disabled method.
+        method: &'static str,
+    },
     /// An internal error occurred.
     #[error("internal error")]
     InternalError,
@@ -120,226 +878,4232 @@ pub enum OdysseyWalletError {
 
 impl From<OdysseyWalletError> for jsonrpsee::types::error::ErrorObject<'static> {
     fn from(error: OdysseyWalletError) -> Self {
-        jsonrpsee::types::error::ErrorObject::owned::<()>(
-            jsonrpsee::types::error::INVALID_PARAMS_CODE,
-            error.to_string(),
-            None,
-        )
+        let (code, message) = (error.code(), error.to_string());
+        match error {
+            OdysseyWalletError::RateLimited { retry_after, escalation } => {
+                jsonrpsee::types::error::ErrorObject::owned(
+                    code,
+                    message,
+                    Some(RateLimitedData { retry_after, escalation }),
+                )
+            }
+            OdysseyWalletError::GasEstimateTooHigh { estimate, limit } => {
+                jsonrpsee::types::error::ErrorObject::owned(
+                    code,
+                    message,
+                    Some(GasEstimateTooHighData { estimate, limit }),
+                )
+            }
+            OdysseyWalletError::BundlePartiallySubmitted { id, hashes, .. } => {
+                jsonrpsee::types::error::ErrorObject::owned(
+                    code,
+                    message,
+                    Some(BundlePartiallySubmittedData { id, hashes }),
+                )
+            }
+            _ => jsonrpsee::types::error::ErrorObject::owned::<()>(code, message, None),
+        }
     }
 }
 
+/// The data of an [`OdysseyWalletError::RateLimited`] error.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RateLimitedData {
+    retry_after: u64,
+    escalation: u32,
+}
+
+/// The data of an [`OdysseyWalletError::BundlePartiallySubmitted`] error.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BundlePartiallySubmittedData {
+    id: B256,
+    hashes: Vec<TxHash>,
+}
+
+/// The data of an [`OdysseyWalletError::GasEstimateTooHigh`] error.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GasEstimateTooHighData {
+    estimate: u64,
+    limit: u64,
+}
+
+/// Odyssey `admin_` RPC namespace, for operating the wallet at runtime.
+///
+/// The methods are rejected unless [`OdysseyWalletConfig::admin_api`] is set.
+#[cfg_attr(not(test), rpc(server, namespace = "admin"))]
+#[cfg_attr(test, rpc(server, client, namespace = "admin"))]
+pub trait OdysseyWalletAdminApi {
+    /// Replace the configured delegation whitelist, returning the new whitelist.
+    ///
+    /// This takes effect for all subsequent requests, including `wallet_getCapabilities`.
+    #[method(name = "setDelegationWhitelist")]
+    fn set_delegation_whitelist(&self, addresses: Vec<Address>) -> RpcResult<DelegationCapability>;
+
+    /// Append to the configured delegation whitelist, returning the new whitelist.
+    ///
+    /// Targets that are already whitelisted are skipped. If no whitelist is configured, this sets
+    /// one, so only the appended targets are sponsored.
+    #[method(name = "addDelegationTargets")]
+    fn add_delegation_targets(&self, addresses: Vec<Address>) -> RpcResult<DelegationCapability>;
+}
+
 /// Implementation of the Odyssey `wallet_` namespace.
 #[derive(Debug)]
 pub struct OdysseyWallet<Provider, Eth> {
     inner: Arc<OdysseyWalletInner<Provider, Eth>>,
 }
 
+impl<Provider, Eth> Clone for OdysseyWallet<Provider, Eth> {
+    fn clone(&self) -> Self {
+        Self { inner: self.inner.clone() }
+    }
+}
+
 impl<Provider, Eth> OdysseyWallet<Provider, Eth> {
-    /// Create a new Odyssey wallet module.
+    /// Create a new Odyssey wallet module that only sponsors accounts delegating to one of the
+    /// addresses of the capability.
+    ///
+    /// The remaining settings are the [defaults](OdysseyWalletConfig::default), which are not
+    /// validated, since only the duplicate handling applies to the whitelist and duplicates are
+    /// not rejected by default.
     pub fn new(
         provider: Provider,
         wallet: EthereumWallet,
         eth_api: Eth,
         chain_id: ChainId,
+        delegation_whitelist: DelegationCapability,
+    ) -> Self {
+        Self::from_config(
+            provider,
+            wallet,
+            eth_api,
+            chain_id,
+            OdysseyWalletConfig::default().with_delegation_whitelist(delegation_whitelist),
+        )
+    }
+
+    /// Create a new Odyssey wallet module with the given [`OdysseyWalletConfig`].
+    ///
+    /// Like [`update_config`](Self::update_config), an invalid configuration is rejected.
+    pub fn with_config(
+        provider: Provider,
+        wallet: EthereumWallet,
+        eth_api: Eth,
+        chain_id: ChainId,
+        config: OdysseyWalletConfig,
+    ) -> Result<Self, ConfigError> {
+        config.validate()?;
+        Ok(Self::from_config(provider, wallet, eth_api, chain_id, config))
+    }
+
+    /// Creates the wallet module with the given configuration, which is not validated.
+    fn from_config(
+        provider: Provider,
+        wallet: EthereumWallet,
+        eth_api: Eth,
+        chain_id: ChainId,
+        config: OdysseyWalletConfig,
     ) -> Self {
+        let signer_address = NetworkWallet::<Ethereum>::default_signer_address(&wallet);
+        let active = ActiveConfig::new(config, None, Instant::now());
+        if let Some(store) = &active.config.state_store {
+            match store.load() {
+                Ok(Some(snapshot)) => restore_state(&active, snapshot),
+                Ok(None) => {}
+                Err(err) => {
+                    warn!(target: "rpc::wallet", %err, "Error loading state snapshot");
+                }
+            }
+        }
+        let metrics =
+            if active.config.metrics_enabled { WalletMetrics::try_register() } else { None };
+        let counter_totals = CounterTotals::default();
+        let metrics_active = metrics.is_some();
+        let metrics = metrics.unwrap_or_else(WalletMetrics::noop).observed(&counter_totals);
         let inner = OdysseyWalletInner {
             provider,
             wallet,
+            signer_address,
             eth_api,
             chain_id,
-            permit: Default::default(),
-            metrics: WalletMetrics::default(),
+            config: RwLock::new(Arc::new(active)),
+            nonces: SignerNonces::new([signer_address]),
+            ordered_submissions: OrderedSubmissions::default(),
+            shadow_evaluations: Arc::new(Semaphore::new(MAX_SHADOW_EVALUATIONS)),
+            bundles: Bundles::default(),
+            recent_transactions: RecentTransactions::default(),
+            audit_log: AuditLog::default(),
+            metrics,
+            metrics_active,
+            counter_totals,
         };
         Self { inner: Arc::new(inner) }
     }
 
-    fn chain_id(&self) -> ChainId {
-        self.inner.chain_id
+    /// Atomically replaces the configuration.
+    ///
+    /// Requests in flight complete with the configuration they started with. Stateful components
+    /// whose settings are unchanged, e.g. the sponsorship budget, retain their state. If the new
+    /// configuration is invalid, it is rejected and the current configuration is kept.
+    pub fn update_config(&self, config: OdysseyWalletConfig) -> Result<(), ConfigError> {
+        config.validate()?;
+
+        let mut active = self.inner.config.write();
+        *active = Arc::new(ActiveConfig::new(config, Some(&active), Instant::now()));
+        info!(target: "rpc::wallet", "Updated wallet configuration");
+        Ok(())
     }
-}
 
-#[async_trait]
-impl<Provider, Eth> OdysseyWalletApiServer for OdysseyWallet<Provider, Eth>
-where
-    Provider: StateProviderFactory + Send + Sync + 'static,
-    Eth: FullEthApi + Send + Sync + 'static,
-{
-    async fn send_transaction(&self, mut request: TransactionRequest) -> RpcResult<TxHash> {
-        trace!(target: "rpc::wallet", ?request, "Serving odyssey_sendTransaction");
+    /// Replaces the configured delegation whitelist, or appends to it, returning the new
+    /// whitelist.
+    ///
+    /// Like [`update_config`](Self::update_config), this takes effect for subsequent requests,
+    /// and the rest of the configuration and its state are kept. When appending, targets that are
+    /// already whitelisted are skipped.
+    pub fn update_delegation_whitelist(
+        &self,
+        addresses: Vec<Address>,
+        append: bool,
+    ) -> Result<DelegationCapability, ConfigError> {
+        let mut active = self.inner.config.write();
+        let mut config = active.config.clone();
+        let whitelist = match config.delegation_whitelist.take() {
+            Some(mut whitelist) if append => {
+                let new = addresses
+                    .into_iter()
+                    .filter(|address| !whitelist.addresses.contains(address))
+                    .collect::<Vec<_>>();
+                whitelist.addresses.extend(new);
+                whitelist
+            }
+            _ => DelegationCapability { addresses },
+        };
+        config.delegation_whitelist = Some(whitelist.clone());
+        config.validate()?;
 
-        // validate fields common to eip-7702 and eip-1559
-        if let Err(err) = validate_tx_request(&request) {
-            self.inner.metrics.invalid_send_transaction_calls.increment(1);
-            return Err(err.into());
-        }
+        *active = Arc::new(ActiveConfig::new(config, Some(&active), Instant::now()));
+        info!(target: "rpc::wallet", targets = whitelist.addresses.len(), "Updated delegation whitelist");
+        Ok(whitelist)
+    }
 
-        // validate destination
-        match (request.authorization_list.is_some(), request.to) {
-            // if this is an eip-1559 tx, ensure that it is an account that delegates to a
-            // whitelisted address
-            (false, Some(TxKind::Call(addr))) => {
-                let state = self.inner.provider.latest().map_err(|_| {
-                    self.inner.metrics.invalid_send_transaction_calls.increment(1);
-                    OdysseyWalletError::InternalError
-                })?;
-                let delegated_address = state
-                    .account_code(addr)
-                    .ok()
-                    .flatten()
-                    .and_then(|code| match code.0 {
-                        Bytecode::Eip7702(code) => Some(code.address()),
-                        _ => None,
-                    })
-                    .unwrap_or_default();
+    /// Returns `true` if the metrics of the wallet were registered with the metrics recorder.
+    ///
+    /// This is `false` if metrics are disabled, or if registering them failed. If no recorder is
+    /// installed, registration succeeds, but the recorded values are discarded.
+    pub fn metrics_active(&self) -> bool {
+        self.inner.metrics_active
+    }
 
-                // not eip-7702 bytecode
-                if delegated_address == Address::ZERO {
-                    self.inner.metrics.invalid_send_transaction_calls.increment(1);
-                    return Err(OdysseyWalletError::IllegalDestination.into());
+    /// Returns the totals of the wallet's counters since it was created.
+    ///
+    /// The totals are kept whether the metrics are active or not.
+    pub fn metrics_snapshot(&self) -> MetricsSnapshot {
+        self.inner.counter_totals.snapshot()
+    }
+
+    /// Returns the number of sponsored calls left for the delegation target within the rolling 24
+    /// hour window, or `None` if the target has no quota.
+    pub fn remaining_destination_quota(&self, target: Address) -> Option<u32> {
+        self.active_config().destination_quotas.as_ref()?.remaining(&target, Instant::now())
+    }
+
+    /// Returns the configured spending cap and the amount left within its current window, or
+    /// `None` if there is no cap.
+    pub fn spending_cap(&self) -> Option<SpendingCapStatus> {
+        Some(self.active_config().spending_cap.as_ref()?.status(Instant::now()))
+    }
+
+    /// Returns a snapshot of the sponsorship budget, the spending cap, the destination quotas and
+    /// the account rate limits.
+    pub fn snapshot_state(&self) -> StateSnapshot {
+        let config = self.active_config();
+        let now = Instant::now();
+        let now_unix = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        StateSnapshot {
+            version: STATE_SNAPSHOT_VERSION,
+            taken_at: now_unix,
+            budget: config.budget.as_ref().map(|budget| budget.level(now)),
+            destination_quotas: config
+                .destination_quotas
+                .as_ref()
+                .map(|quotas| {
+                    quotas
+                        .calls(now)
+                        .into_iter()
+                        .map(|(target, calls)| {
+                            let calls =
+                                calls.into_iter().map(|at| persist::to_unix(at, now, now_unix));
+                            (target, calls.collect())
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            spending_cap: config.spending_cap.as_ref().map(|cap| {
+                let (spent, window_start) = cap.spending(now);
+                SpendingCapSnapshot {
+                    spent,
+                    window_start: persist::to_unix(window_start, now, now_unix),
                 }
-            }
-            // if it's an eip-7702 tx, let it through
-            (true, _) => (),
-            // create tx's disallowed
-            _ => {
-                self.inner.metrics.invalid_send_transaction_calls.increment(1);
-                return Err(OdysseyWalletError::IllegalDestination.into());
+            }),
+            account_rate_limits: config
+                .account_rate_limiter
+                .as_ref()
+                .map(|limiter| {
+                    limiter
+                        .windows(now)
+                        .into_iter()
+                        .map(|(account, window)| {
+                            let window = RateLimitWindowSnapshot {
+                                started_at: persist::to_unix(window.started_at, now, now_unix),
+                                requests: window.requests,
+                                violations: window.violations.map(|(violations, at)| {
+                                    (violations, persist::to_unix(at, now, now_unix))
+                                }),
+                            };
+                            (account, window)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Saves a snapshot of the sponsorship budget, the spending cap, the destination quotas and the
+    /// account rate limits to the configured [`StateStore`], if any.
+    pub fn persist_state(&self) -> std::io::Result<()> {
+        let Some(store) = self.active_config().config.state_store.clone() else {
+            return Ok(());
+        };
+        store.save(&self.snapshot_state())
+    }
+
+    /// Periodically persists the state, see [`persist_state`](Self::persist_state).
+    ///
+    /// This runs until the future is dropped, so it should be spawned on an executor that cancels
+    /// it on shutdown.
+    pub async fn persist_state_periodically(&self, interval: Duration) {
+        let mut interval_timer = tokio::time::interval(interval);
+        loop {
+            interval_timer.tick().await;
+            if let Err(err) = self.persist_state() {
+                warn!(target: "rpc::wallet", %err, "Error persisting state snapshot");
             }
         }
+    }
 
-        // we acquire the permit here so that all following operations are performed exclusively
-        let _permit = self.inner.permit.lock().await;
+    /// Returns the capabilities of the wallet, sponsoring delegations to the contracts of the
+    /// current whitelist, without duplicates.
+    pub fn capabilities(&self) -> WalletCapabilities {
+        let config = self.active_config();
+        let mut delegation = config.current_capability();
+        let mut seen = HashSet::with_capacity(delegation.addresses.len());
+        delegation.addresses.retain(|address| seen.insert(*address));
+        WalletCapabilities::new(self.chain_id(), delegation)
+    }
 
-        // set nonce
-        let next_nonce = LoadState::next_available_nonce(
-            &self.inner.eth_api,
-            NetworkWallet::<Ethereum>::default_signer_address(&self.inner.wallet),
-        )
+    /// Returns the validation rules currently enforced, with their key parameters.
+    ///
+    /// See [`OdysseyWalletConfig::active_rules`].
+    pub fn active_rules(&self) -> Vec<String> {
+        self.active_config().config.active_rules()
+    }
+
+    /// Returns the address of the account that pays for all sponsored transactions, i.e. the
+    /// default signer of the wallet.
+    pub fn sponsor_address(&self) -> Address {
+        self.signer_address()
+    }
+
+    /// Returns a snapshot of the configuration in effect.
+    fn active_config(&self) -> Arc<ActiveConfig> {
+        self.inner.config.read().clone()
+    }
+
+    fn chain_id(&self) -> ChainId {
+        self.inner.chain_id
+    }
+
+    /// Returns the address of the signer, which sponsors all transactions.
+    fn signer_address(&self) -> Address {
+        self.inner.signer_address
+    }
+
+    /// Acquires the signing permit of the signer, waiting at most the configured timeout.
+    async fn acquire_permit(
+        &self,
+        config: &ActiveConfig,
+    ) -> Result<SignerPermit<'_>, OdysseyWalletError> {
+        let start = Instant::now();
+        let lock = self.inner.nonces.lock(&self.inner.signer_address);
+        let permit = match config.config.permit_acquire_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, lock).await.map_err(|_| {
+                warn!(target: "rpc::wallet", "Timed out waiting for the signing permit");
+                OdysseyWalletError::Overloaded
+            })?,
+            None => lock.await,
+        };
+        self.inner.metrics.permit_wait_time.record(start.elapsed().as_secs_f64());
+
+        permit.ok_or(OdysseyWalletError::InternalError)
+    }
+
+    /// Consults the configured oracles for each delegate.
+    async fn validate_delegates(
+        &self,
+        config: &ActiveConfig,
+        delegates: &[Address],
+    ) -> Result<(), OdysseyWalletError> {
+        validate_risk(config.config.risk_oracle.as_ref(), delegates).await?;
+        validate_verification(config.config.verification_oracle.as_ref(), delegates).await
+    }
+
+    /// Counts a request against the rate limit of the client connection, if enabled.
+    ///
+    /// Requests without a connection identity are not limited.
+    fn check_connection_rate_limit(
+        &self,
+        config: &ActiveConfig,
+        connection: Option<ConnectionId>,
+    ) -> Result<(), OdysseyWalletError> {
+        let (Some(limiter), Some(connection)) = (&config.connection_rate_limiter, connection)
+        else {
+            return Ok(());
+        };
+        limiter.check(connection, Instant::now()).map_err(rate_limited)
+    }
+
+    /// Counts a request against the rate limit of each account it is sponsored for, if enabled.
+    fn check_account_rate_limit(
+        &self,
+        config: &ActiveConfig,
+        destination: &ResolvedDestination,
+    ) -> Result<(), OdysseyWalletError> {
+        let Some(limiter) = &config.account_rate_limiter else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        destination
+            .accounts()
+            .try_for_each(|account| limiter.check(account, now))
+            .map_err(rate_limited)
+    }
+
+    /// Admits the request under the global rate limit, if enabled.
+    fn check_global_rate_limit(&self, config: &ActiveConfig) -> Result<(), OdysseyWalletError> {
+        let Some(limiter) = &config.global_rate_limiter else {
+            return Ok(());
+        };
+        let rate =
+            limiter.try_acquire(Instant::now()).ok_or(OdysseyWalletError::GlobalRateLimited)?;
+        self.inner.metrics.global_request_rate.set(f64::from(rate));
+        Ok(())
+    }
+
+    /// Rejects the request during a maintenance window, warning as the next window approaches.
+    fn check_maintenance(&self, config: &ActiveConfig) -> Result<(), OdysseyWalletError> {
+        let Some(maintenance) = &config.config.maintenance else {
+            return Ok(());
+        };
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let Some(starts_in) = maintenance.check(now)? else {
+            return Ok(());
+        };
+        self.inner.metrics.maintenance_starts_in.set(starts_in as f64);
+        if starts_in <= maintenance.notice.as_secs() {
+            warn!(target: "rpc::wallet", starts_in, "Maintenance window approaching");
+        }
+        Ok(())
+    }
+
+    /// Records where the decision of the shadow configuration diverges from the live decision.
+    fn record_divergence(&self, live_accepted: bool, shadow: &Result<(), OdysseyWalletError>) {
+        match Divergence::of(live_accepted, shadow) {
+            Some(Divergence::Rejected) => {
+                self.inner.metrics.shadow_rejections.increment(1);
+                info!(target: "rpc::wallet", ?shadow, "Shadow configuration rejects accepted request");
+            }
+            Some(Divergence::Accepted) => {
+                self.inner.metrics.shadow_acceptances.increment(1);
+                info!(target: "rpc::wallet", "Shadow configuration accepts rejected request");
+            }
+            None => {}
+        }
+    }
+
+    /// Records the assignment of a nonce to the signer under the signer nonce rate limit, if
+    /// enabled.
+    fn check_signer_nonce_rate(
+        &self,
+        config: &ActiveConfig,
+        signer: Address,
+    ) -> Result<(), OdysseyWalletError> {
+        let Some(limiter) = &config.signer_nonce_rate else {
+            return Ok(());
+        };
+        let rate = limiter
+            .try_record(signer, Instant::now())
+            .ok_or(OdysseyWalletError::SignerNonceRateLimited)?;
+        self.inner.metrics.signer_nonce_rate.set(f64::from(rate));
+        Ok(())
+    }
+
+    /// Acquires a permit to run a gas estimation, if the estimation limit is enabled.
+    async fn acquire_estimation_permit<'a>(
+        &self,
+        config: &'a ActiveConfig,
+    ) -> Result<Option<SemaphorePermit<'a>>, OdysseyWalletError> {
+        let Some(limiter) = &config.estimation_limiter else {
+            return Ok(None);
+        };
+        limiter.acquire(&self.inner.metrics.estimation_queue_depth).await.map(Some)
+    }
+
+    /// Scores the request with the configured [`PatternDetector`], if any.
+    fn detect_patterns(
+        &self,
+        config: &ActiveConfig,
+        account: Option<Address>,
+        request: &TransactionRequest,
+    ) -> Result<(), OdysseyWalletError> {
+        let Some(detection) = &config.config.pattern_detection else {
+            return Ok(());
+        };
+
+        let metadata =
+            RequestMetadata { account, input: request.input.input().cloned().unwrap_or_default() };
+        if detection.detector.score(&metadata, Instant::now()) >= detection.threshold {
+            return Err(OdysseyWalletError::SuspiciousActivity);
+        }
+        Ok(())
+    }
+
+    /// Consumes the cost of a transaction from the spending cap and the sponsorship budget, if
+    /// enabled.
+    fn consume_budget(&self, config: &ActiveConfig, cost: U256) -> Result<(), OdysseyWalletError> {
+        let now = Instant::now();
+        if let Some(cap) = &config.spending_cap {
+            let spent = cap.try_spend(cost, now);
+            self.inner.metrics.spending_cap_remaining.set(f64::from(cap.status(now).remaining));
+            if !spent {
+                return Err(OdysseyWalletError::BudgetExhausted);
+            }
+        }
+        let Some(budget) = &config.budget else {
+            return Ok(());
+        };
+        let consumed = budget.try_consume(cost, now);
+        let level = budget.level(now);
+        self.inner.metrics.sponsorship_budget.set(f64::from(level));
+        if let Some(alerts) = &config.config.budget_alerts {
+            alerts.observe_budget(level);
+        }
+        if !consumed {
+            // the transaction is not sponsored, so it does not count towards the cap
+            if let Some(cap) = &config.spending_cap {
+                cap.refund(cost, now);
+            }
+            return Err(OdysseyWalletError::BudgetExhausted);
+        }
+        Ok(())
+    }
+
+    /// Charges a sponsored transaction of the maximum `cost` against the quotas of its delegation
+    /// targets, the pending delegations and the budget.
+    ///
+    /// The quotas, pending delegations and budget are released unless the returned charges are
+    /// committed once the transaction is submitted.
+    fn charge<'a>(
+        &'a self,
+        config: &'a ActiveConfig,
+        destination: &ResolvedDestination,
+        cost: U256,
+    ) -> Result<Charges<'a>, OdysseyWalletError> {
+        // count the call against the quotas of its delegation targets
+        let quota = consume_destination_quotas(config, &destination.delegates)?;
+
+        // track the delegations, which must not conflict with pending ones
+        let pending = config
+            .pending_delegations
+            .as_ref()
+            .map(|pending| pending.try_track(&destination.delegations, Instant::now()))
+            .transpose()?;
+
+        // consume the maximum cost of the transaction from the budget
+        self.consume_budget(config, cost)?;
+        Ok(Charges { config, metrics: &self.inner.metrics, cost, quota, pending, committed: false })
+    }
+
+    /// Returns the gas ceiling that applies to the request.
+    ///
+    /// Ceilings for the destination and function of the request take precedence over the global
+    /// ceiling.
+    fn gas_ceiling(&self, config: &ActiveConfig, request: &TransactionRequest) -> u64 {
+        let to = request.to.and_then(|to| to.to().copied());
+        let input = request.input.input().map(|input| input.as_ref()).unwrap_or_default();
+        config.config.gas_ceiling_overrides.resolve(to, input, self.global_gas_ceiling(config))
+    }
+
+    /// Returns the gas ceiling that applies to the request, taking its call depth into account.
+    async fn effective_gas_ceiling(
+        &self,
+        config: &ActiveConfig,
+        request: &TransactionRequest,
+    ) -> u64 {
+        let ceiling = self.gas_ceiling(config, request);
+        let Some(deep) = &config.config.deep_call_gas_ceiling else {
+            return ceiling;
+        };
+        deep.resolve(deep.probe.max_call_depth(request).await, ceiling)
+    }
+
+    /// Returns the transactions of a bundle submitted by `wallet_sendCalls`, if it is among the
+    /// [`MAX_TRACKED_BUNDLES`] most recent bundles.
+    pub fn bundle_transactions(&self, id: B256) -> Option<Vec<TxHash>> {
+        self.inner.bundles.get(&id)
+    }
+
+    /// Returns up to `limit` of the transactions recently sponsored for the account, most recent
+    /// first.
+    pub fn recent_transactions(&self, account: Address, limit: usize) -> Vec<TxHash> {
+        self.inner.recent_transactions.get(&account, limit)
+    }
+
+    /// Returns up to `limit` of the recent requests to sponsor a transaction older than the
+    /// cursor, most recent first.
+    ///
+    /// At most [`MAX_AUDIT_PAGE_SIZE`] requests are returned at once.
+    pub fn audit_log(
+        &self,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<AuditLogPage, OdysseyWalletError> {
+        self.inner.audit_log.page(cursor, limit)
+    }
+
+    /// Returns the global gas ceiling that applies to the next request.
+    fn global_gas_ceiling(&self, config: &ActiveConfig) -> u64 {
+        let Some(adaptive) = &config.adaptive_gas_ceiling else {
+            return config.config.gas_ceiling;
+        };
+        let ceiling = adaptive.ceiling(Instant::now());
+        self.inner.metrics.adaptive_gas_ceiling.set(ceiling as f64);
+        ceiling
+    }
+}
+
+impl<Provider, Eth> OdysseyWallet<Provider, Eth>
+where
+    Provider: StateProviderFactory,
+{
+    /// Returns the delegation status of the account in the latest state.
+    pub fn delegation_info(&self, address: Address) -> Result<DelegationInfo, OdysseyWalletError> {
+        let state = self.inner.provider.latest().map_err(|_| OdysseyWalletError::InternalError)?;
+        let (code, nonce) = state
+            .account_code(address)
+            .and_then(|code| Ok((code, state.account_nonce(address)?)))
+            .map_err(|_| OdysseyWalletError::InternalError)?;
+        let exists = code.is_some() || nonce.is_some();
+        let config = self.active_config();
+        DelegationInfo::new(
+            exists,
+            code.as_ref().map(|code| &code.0),
+            config.current_whitelist().as_deref(),
+            |target| state.account_code(target).map(|code| code.map(|code| code.0)),
+        )
+        .map_err(|_| OdysseyWalletError::InternalError)
+    }
+
+    /// Returns the balance of the sponsor in the latest state.
+    fn sponsor_balance(&self) -> Result<U256, OdysseyWalletError> {
+        Ok(self
+            .inner
+            .provider
+            .latest()
+            .and_then(|state| state.account_balance(self.signer_address()))
+            .map_err(|_| OdysseyWalletError::InternalError)?
+            .unwrap_or_default())
+    }
+
+    /// Ensures the balance of the sponsor is not below the configured minimum, and reports it.
+    fn check_sponsor_balance(&self, config: &ActiveConfig) -> Result<(), OdysseyWalletError> {
+        let balance = self.sponsor_balance()?;
+        self.inner.metrics.sponsor_balance.set(f64::from(balance));
+        ensure_sponsor_funded(balance, config.config.min_sponsor_balance)
+    }
+
+    /// Validates and resolves the destination of the request.
+    ///
+    /// EIP-1559 transactions must be sent to an account that is delegated, and EIP-7702
+    /// transactions must carry valid authorizations. Contract creations are disallowed.
+    fn validate_destination(
+        &self,
+        config: &ActiveConfig,
+        request: &TransactionRequest,
+    ) -> Result<ResolvedDestination, OdysseyWalletError> {
+        ensure_7702_supported(request, config.config.supports_7702)?;
+        // the destination is checked before any state is read
+        if let Some(TxKind::Call(to)) = request.to {
+            validate_not_blocked([to], &config.config.blocked_addresses)?;
+        }
+
+        let mut destination = match (request.authorization_list.is_some(), request.to) {
+            // if this is an eip-1559 tx, ensure that it is an account that delegates to a
+            // whitelisted address
+            (false, Some(TxKind::Call(addr))) => {
+                let state =
+                    self.inner.provider.latest().map_err(|_| OdysseyWalletError::InternalError)?;
+                let delegated_address = state
+                    .account_code(addr)
+                    .ok()
+                    .flatten()
+                    .and_then(|code| match code.0 {
+                        Bytecode::Eip7702(code) => Some(code.address()),
+                        _ => None,
+                    })
+                    .unwrap_or_default();
+
+                // not eip-7702 bytecode
+                if delegated_address == Address::ZERO {
+                    return Err(OdysseyWalletError::IllegalDestination);
+                }
+
+                ResolvedDestination {
+                    to: Some(addr),
+                    delegates: vec![delegated_address],
+                    delegations: Vec::new(),
+                    kind: SponsoredTxKind::DelegatedCall,
+                    proxied: false,
+                }
+            }
+            // if it's an eip-7702 tx, the delegates are the addresses of the authorizations
+            (true, to) => {
+                let authorizations = request.authorization_list.as_deref().unwrap_or_default();
+                validate_authorization_chain_ids(
+                    authorizations,
+                    self.chain_id(),
+                    config.config.reject_universal_authorizations,
+                )?;
+                self.validate_authorization_nonces(config, authorizations)?;
+
+                ResolvedDestination {
+                    kind: self.classify_authorizations(authorizations)?,
+                    to: match to {
+                        Some(TxKind::Call(addr)) => Some(addr),
+                        _ => None,
+                    },
+                    delegates: authorization_delegates(
+                        authorizations,
+                        config.config.allow_revocation,
+                    ),
+                    // authorizations with an invalid signature are skipped on execution
+                    delegations: authorizations
+                        .iter()
+                        .filter_map(|authorization| {
+                            Some((authorization.recover_authority().ok()?, authorization.address))
+                        })
+                        .collect(),
+                    proxied: false,
+                }
+            }
+            // create tx's disallowed
+            _ => return Err(OdysseyWalletError::IllegalDestination),
+        };
+
+        // the authorities are only known once their authorizations are recovered
+        validate_not_blocked(destination.accounts(), &config.config.blocked_addresses)?;
+        validate_whitelisted(&destination.delegates, config.current_whitelist().as_deref())?;
+        validate_value(request, &destination.delegates, config.config.value_allowances.as_ref())?;
+        destination.proxied = self.validate_proxy_delegates(config, &destination.delegates)?;
+
+        // neither the destination nor its delegates may be a system address
+        validate_not_system_address(destination.addresses(), &config.config.system_addresses)?;
+
+        if let Some(to) = destination.to {
+            self.validate_cohort(config, to)?;
+        }
+        self.validate_not_destroyed(config, &destination.delegates)?;
+        self.validate_target_age(config, &destination.delegates)?;
+
+        Ok(destination)
+    }
+
+    /// Applies the [`ProxyDelegationPolicy`] to the delegates, if enabled.
+    ///
+    /// Returns `true` if any delegate is an upgradeable proxy.
+    fn validate_proxy_delegates(
+        &self,
+        config: &ActiveConfig,
+        delegates: &[Address],
+    ) -> Result<bool, OdysseyWalletError> {
+        let Some(policy) = &config.config.proxy_delegations else {
+            return Ok(false);
+        };
+
+        let state = self.inner.provider.latest().map_err(|_| OdysseyWalletError::InternalError)?;
+        let whitelist = config.current_whitelist();
+        let mut proxied = false;
+        for target in delegates {
+            let slot = state
+                .storage(*target, EIP1967_IMPLEMENTATION_SLOT)
+                .map_err(|_| OdysseyWalletError::InternalError)?;
+            proxied |= policy.validate(*target, slot, whitelist.as_deref())?;
+        }
+        Ok(proxied)
+    }
+
+    /// Ensures the account was first delegated within the configured cohort.
+    fn validate_cohort(
+        &self,
+        config: &ActiveConfig,
+        address: Address,
+    ) -> Result<(), OdysseyWalletError> {
+        let Some(cohort) = &config.config.delegation_cohort else {
+            return Ok(());
+        };
+
+        let provider = &self.inner.provider;
+        let latest = provider.best_block_number().map_err(|_| OdysseyWalletError::InternalError)?;
+        let eligible = is_in_cohort(cohort, latest, |block| {
+            let state = provider
+                .history_by_block_number(block)
+                .map_err(|_| OdysseyWalletError::InternalError)?;
+            let code =
+                state.account_code(address).map_err(|_| OdysseyWalletError::InternalError)?;
+            Ok(code.is_some_and(|code| matches!(code.0, Bytecode::Eip7702(_))))
+        })?;
+
+        if !eligible {
+            return Err(OdysseyWalletError::CohortNotEligible { address });
+        }
+        Ok(())
+    }
+
+    /// Ensures all delegates were deployed at least the configured minimum age ago.
+    fn validate_target_age(
+        &self,
+        config: &ActiveConfig,
+        delegates: &[Address],
+    ) -> Result<(), OdysseyWalletError> {
+        let Some(target_ages) = &config.target_ages else {
+            return Ok(());
+        };
+
+        let provider = &self.inner.provider;
+        let latest = provider.best_block_number().map_err(|_| OdysseyWalletError::InternalError)?;
+        for target in delegates {
+            let old_enough = target_ages.is_old_enough(*target, latest, |block| {
+                let state = provider
+                    .history_by_block_number(block)
+                    .map_err(|_| OdysseyWalletError::InternalError)?;
+                state
+                    .account_code(*target)
+                    .map(|code| code.is_some_and(|code| !code.0.is_empty()))
+                    .map_err(|_| OdysseyWalletError::InternalError)
+            })?;
+
+            if !old_enough {
+                return Err(OdysseyWalletError::DelegationTargetTooNew { target: *target });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Ensures none of the delegates had its code removed within the configured lookback.
+    fn validate_not_destroyed(
+        &self,
+        config: &ActiveConfig,
+        delegates: &[Address],
+    ) -> Result<(), OdysseyWalletError> {
+        let Some(destroyed_targets) = &config.destroyed_targets else {
+            return Ok(());
+        };
+
+        let now = Instant::now();
+        for target in delegates {
+            let destroyed = destroyed_targets.is_destroyed(*target, now, || {
+                let provider = &self.inner.provider;
+                let has_code = |state: StateProviderBox| {
+                    state
+                        .account_code(*target)
+                        .map(|code| code.is_some_and(|code| !code.0.is_empty()))
+                        .map_err(|_| OdysseyWalletError::InternalError)
+                };
+                let latest =
+                    provider.best_block_number().map_err(|_| OdysseyWalletError::InternalError)?;
+                let lookback = latest.saturating_sub(destroyed_targets.lookback_blocks());
+                Ok((
+                    has_code(provider.latest().map_err(|_| OdysseyWalletError::InternalError)?)?,
+                    has_code(
+                        provider
+                            .history_by_block_number(lookback)
+                            .map_err(|_| OdysseyWalletError::InternalError)?,
+                    )?,
+                ))
+            })?;
+
+            if destroyed {
+                return Err(OdysseyWalletError::DestinationDestroyed { target: *target });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Classifies an EIP-7702 transaction by whether any of its authorities is already delegated.
+    fn classify_authorizations(
+        &self,
+        authorizations: &[SignedAuthorization],
+    ) -> Result<SponsoredTxKind, OdysseyWalletError> {
+        let state = self.inner.provider.latest().map_err(|_| OdysseyWalletError::InternalError)?;
+        // authorizations with an invalid signature are skipped on execution
+        Ok(SponsoredTxKind::of_authorities(authorizations.iter().map(|authorization| {
+            let authority = authorization.recover_authority().ok()?;
+            state.account_code(authority).ok().flatten().map(|code| code.0)
+        })))
+    }
+
+    /// Ensures the nonce of each authorization is within the configured window of the
+    /// authority's current nonce.
+    fn validate_authorization_nonces(
+        &self,
+        config: &ActiveConfig,
+        authorizations: &[SignedAuthorization],
+    ) -> Result<(), OdysseyWalletError> {
+        let Some(tolerance) = config.config.authorization_nonce_tolerance else {
+            return Ok(());
+        };
+
+        let state = self.inner.provider.latest().map_err(|_| OdysseyWalletError::InternalError)?;
+        for authorization in authorizations {
+            let authority = authorization
+                .recover_authority()
+                .map_err(|_| OdysseyWalletError::InvalidAuthorizationSignature)?;
+            let current = state
+                .account_nonce(authority)
+                .map_err(|_| OdysseyWalletError::InternalError)?
+                .unwrap_or_default();
+            validate_authorization_nonce(authority, authorization.nonce, current, tolerance)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<Provider, Eth> OdysseyWallet<Provider, Eth>
+where
+    Provider: StateProviderFactory + HeaderProvider,
+{
+    /// Applies the configured maximum age of the fee data to the base fee.
+    ///
+    /// The fee data is as old as the latest block.
+    fn check_fee_data_age(
+        &self,
+        config: &ActiveConfig,
+        base_fee: u128,
+    ) -> Result<u128, OdysseyWalletError> {
+        let Some(max_age) = &config.config.fee_data_max_age else {
+            return Ok(base_fee);
+        };
+
+        let provider = &self.inner.provider;
+        let latest = provider.best_block_number().map_err(|_| OdysseyWalletError::InternalError)?;
+        let header = provider
+            .header_by_number(latest)
+            .map_err(|_| OdysseyWalletError::InternalError)?
+            .ok_or(OdysseyWalletError::InternalError)?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        max_age.apply(base_fee, header.timestamp, now)
+    }
+}
+
+impl<Provider, Eth> OdysseyWallet<Provider, Eth>
+where
+    Eth: FullEthApi,
+{
+    /// Returns the error to report for a failed estimate of the request.
+    ///
+    /// If the [`value_requirement_probe`](OdysseyWalletConfig::value_requirement_probe) is set,
+    /// reverts caused by the missing value are reported as such.
+    async fn estimate_error(
+        &self,
+        config: &ActiveConfig,
+        request: &TransactionRequest,
+        err: Eth::Error,
+    ) -> jsonrpsee::types::error::ErrorObject<'static> {
+        let err = err.into();
+        let Some(probe) = config.config.value_requirement_probe else {
+            return err;
+        };
+        explain_estimate_error(err, probe, |value| async move {
+            let request = TransactionRequest { value: Some(value), ..request.clone() };
+            EthCall::estimate_gas_at(&self.inner.eth_api, request, BlockId::latest(), None)
+                .await
+                .is_ok()
+        })
+        .await
+    }
+
+    /// Checks the max fee per gas against the minimum accepted by the pool, if enabled.
+    ///
+    /// The base fee is fetched again, as it may have risen since the request was estimated. If
+    /// that fails, only the floor is enforced.
+    async fn enforce_minimum_fee(
+        &self,
+        config: &ActiveConfig,
+        max_fee_per_gas: u128,
+    ) -> Result<u128, OdysseyWalletError> {
+        let Some(check) = &config.config.minimum_fee_check else {
+            return Ok(max_fee_per_gas);
+        };
+        let base_fee = LoadFee::eip1559_fees(&self.inner.eth_api, None, None)
+            .await
+            .map_or(0, |(base_fee, _)| base_fee.to());
+        check.apply(max_fee_per_gas, check.minimum(base_fee))
+    }
+
+    /// Reads the delegation whitelist from the registry, if enabled.
+    ///
+    /// If the read fails, the last list read successfully is kept. Returns `true` if the list was
+    /// updated.
+    pub async fn refresh_whitelist(&self) -> bool {
+        let config = self.active_config();
+        let (Some(settings), Some(registry)) =
+            (&config.config.whitelist_registry, &config.registry_whitelist)
+        else {
+            return false;
+        };
+
+        let call = TransactionRequest::default()
+            .to(settings.address)
+            .input(Bytes::from_static(&GET_APPROVED_TARGETS_SELECTOR).into());
+        registry.update(
+            EthCall::call(
+                &self.inner.eth_api,
+                call,
+                Some(BlockId::latest()),
+                EvmOverrides::default(),
+            )
+            .await,
+        )
+    }
+
+    /// Periodically reads the delegation whitelist from the registry, see
+    /// [`refresh_whitelist`](Self::refresh_whitelist).
+    ///
+    /// The first read happens immediately. This runs until the future is dropped, so it should be
+    /// spawned on an executor that cancels it on shutdown. If no registry is configured, it
+    /// returns immediately.
+    pub async fn refresh_whitelist_periodically(&self) {
+        while let Some(registry) = self.active_config().config.whitelist_registry {
+            self.refresh_whitelist().await;
+            tokio::time::sleep(registry.refresh_interval).await;
+        }
+    }
+
+    /// Returns the L1 data fee of the transaction, as reported by the [`GAS_PRICE_ORACLE`].
+    ///
+    /// The request must be complete, i.e. it must be possible to build and sign it.
+    async fn l1_data_fee(&self, request: TransactionRequest) -> Option<U256> {
+        let envelope =
+            <TransactionRequest as TransactionBuilder<Ethereum>>::build::<EthereumWallet>(
+                request,
+                &self.inner.wallet,
+            )
+            .await
+            .ok()?;
+
+        let call = TransactionRequest::default()
+            .to(GAS_PRICE_ORACLE)
+            .input(get_l1_fee_calldata(&envelope.encoded_2718()).into());
+        let output = EthCall::call(
+            &self.inner.eth_api,
+            call,
+            Some(BlockId::latest()),
+            EvmOverrides::default(),
+        )
         .await
-        .map_err(|err| {
+        .ok()?;
+
+        decode_l1_fee(&output)
+    }
+}
+
+impl<Provider, Eth> OdysseyWallet<Provider, Eth>
+where
+    Provider: StateProviderFactory + HeaderProvider + Send + Sync + 'static,
+    Eth: FullEthApi + Send + Sync + 'static,
+{
+    /// Returns the correlation id provided by the client, or generates one.
+    fn correlation_id(&self, provided: Option<String>) -> Result<String, OdysseyWalletError> {
+        resolve_correlation_id(provided).inspect_err(|_| {
             self.inner.metrics.invalid_send_transaction_calls.increment(1);
-            err.into()
-        })?;
-        request.nonce = Some(next_nonce);
+        })
+    }
+
+    /// Rejects EIP-1559 calls to delegated accounts with transactions pending in the pool, if
+    /// enabled.
+    ///
+    /// See [`OdysseyWalletError::DelegatedStateConflict`].
+    async fn check_delegated_state(
+        &self,
+        config: &ActiveConfig,
+        request: &TransactionRequest,
+        destination: &ResolvedDestination,
+    ) -> Result<(), OdysseyWalletError> {
+        if !config.config.reject_delegated_state_conflicts || request.authorization_list.is_some() {
+            return Ok(());
+        }
+        let Some(account) = destination.to else {
+            return Ok(());
+        };
+
+        let latest = self
+            .inner
+            .provider
+            .latest()
+            .and_then(|state| state.account_nonce(account))
+            .map_err(|_| OdysseyWalletError::InternalError)?
+            .unwrap_or_default();
+        let pending = LoadState::next_available_nonce(&self.inner.eth_api, account)
+            .await
+            .map_err(|_| OdysseyWalletError::InternalError)?;
+        detect_delegated_state_conflict(account, latest, pending)
+    }
+
+    /// Validates each call of a batch and estimates its gas against the latest state.
+    async fn estimate_each(
+        &self,
+        config: &ActiveConfig,
+        calls: &[Call],
+    ) -> RpcResult<Vec<EstimatedCall>> {
+        let sender = self.signer_address();
+        let mut estimates = Vec::with_capacity(calls.len());
+        for call in calls {
+            let request = call.clone().into_request();
+            let destination = validate_tx_request(&request)
+                .and_then(|_| validate_calldata_size(&request, config.config.max_calldata_bytes))
+                .and_then(|_| validate_selector(&request, &config.config))
+                .and_then(|_| self.validate_destination(config, &request))?;
+            self.validate_delegates(config, &destination.delegates).await?;
+
+            // each call is estimated against the latest state, not the state after earlier calls
+            let request = request.from(sender);
+            let limit =
+                destination.gas_ceiling(config, self.effective_gas_ceiling(config, &request).await);
+            let _estimation_permit = self.acquire_estimation_permit(config).await?;
+            let estimate =
+                EthCall::estimate_gas_at(&self.inner.eth_api, request, BlockId::latest(), None)
+                    .await
+                    .map_err(Into::into)?;
+            estimates.push(EstimatedCall { estimate: estimate.saturating_to(), limit });
+        }
+        Ok(estimates)
+    }
+
+    /// Returns whether the transaction is included, pending or unknown.
+    async fn transaction_state(&self, hash: TxHash) -> RpcResult<TransactionState> {
+        let receipt = EthTransactions::transaction_receipt(&self.inner.eth_api, hash)
+            .await
+            .map_err(Into::into)?;
+        if let Some(receipt) = receipt {
+            return Ok(TransactionState::Included(CallReceipt::new(&receipt)));
+        }
+        let transaction = EthTransactions::transaction_by_hash(&self.inner.eth_api, hash)
+            .await
+            .map_err(Into::into)?;
+        Ok(if transaction.is_some() {
+            TransactionState::Pending
+        } else {
+            TransactionState::Unknown
+        })
+    }
+
+    /// Validates, signs and submits a sponsored transaction.
+    ///
+    /// If `estimate_inclusion` is set, the response includes an estimate of when the transaction
+    /// is included. If `estimated` is set, the request is not estimated again, e.g. as it was
+    /// estimated with its batch. If a shadow configuration is set, the request is evaluated
+    /// against it in the background once the live decision is made.
+    async fn sign_and_submit(
+        &self,
+        ext: &Extensions,
+        request: TransactionRequest,
+        attestation: Option<&str>,
+        estimate_inclusion: bool,
+        estimated: Option<U256>,
+    ) -> RpcResult<SendTransactionResponse> {
+        let shadow = self
+            .active_config()
+            .shadow
+            .clone()
+            .map(|shadow| (shadow, request.clone(), attestation.map(str::to_owned)));
+        let (to, calldata_bytes) = (
+            request.to.and_then(|to| to.to().copied()),
+            request.input.input().map_or(0, |input| input.len()),
+        );
+        let response = self
+            .sign_and_submit_live(ext, request, attestation, estimate_inclusion, estimated)
+            .await;
+        self.inner.audit_log.record(AuditEntry {
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            to,
+            calldata_bytes,
+            accepted: response.is_ok(),
+            hash: response.as_ref().ok().map(|response| response.hash),
+            error_code: response.as_ref().err().map(|err| err.code()),
+        });
+
+        // the shadow evaluation is skipped if too many are in flight
+        let shadow = shadow.zip(self.inner.shadow_evaluations.clone().try_acquire_owned().ok());
+        if let Some(((shadow, request, attestation), permit)) = shadow {
+            let wallet = self.clone();
+            let connection = ext.get::<ConnectionId>().copied();
+            let live_accepted = response.is_ok();
+            tokio::task::spawn(async move {
+                let decision = wallet
+                    .evaluate_shadow(&shadow, connection, request, attestation.as_deref())
+                    .await;
+                drop(permit);
+                // the estimation yielded to live requests, so there is no decision
+                if !matches!(decision, Err(OdysseyWalletError::Overloaded)) {
+                    wallet.record_divergence(live_accepted, &decision);
+                }
+            });
+        }
+        response
+    }
+
+    /// Evaluates a request against the shadow configuration, up to the gas ceiling.
+    ///
+    /// Stateful checks, e.g. rate limits, count against the state of the shadow configuration.
+    ///
+    /// The estimation takes a permit of the live estimation limit, if enabled, but never waits for
+    /// one: if none is available, [`OdysseyWalletError::Overloaded`] is returned, and the request
+    /// is not evaluated.
+    async fn evaluate_shadow(
+        &self,
+        shadow: &ActiveConfig,
+        connection: Option<ConnectionId>,
+        mut request: TransactionRequest,
+        attestation: Option<&str>,
+    ) -> Result<(), OdysseyWalletError> {
+        ensure_enabled(&shadow.config.methods, WalletMethod::SendTransaction)?;
+        // the live gauge is not updated
+        if let Some(limiter) = &shadow.global_rate_limiter {
+            limiter.try_acquire(Instant::now()).ok_or(OdysseyWalletError::GlobalRateLimited)?;
+        }
+        self.check_connection_rate_limit(shadow, connection)?;
+        if let Some(maintenance) = &shadow.config.maintenance {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+            maintenance.check(now)?;
+        }
+        // the live gauge is not updated
+        ensure_sponsor_funded(self.sponsor_balance()?, shadow.config.min_sponsor_balance)?;
+
+        validate_tx_request(&request)?;
+        validate_calldata_size(&request, shadow.config.max_calldata_bytes)?;
+        validate_selector(&request, &shadow.config)?;
+        validate_chain_id(&request, self.chain_id())?;
+        resolve_authorization_order(shadow, &mut request)?;
+        validate_authorization_list_size(&request, shadow.config.max_authorization_list_bytes)?;
+        let destination = self.validate_destination(shadow, &request)?;
+        self.check_account_rate_limit(shadow, &destination)?;
+        validate_attestation(
+            shadow.config.attestation_verifier.as_ref(),
+            attestation,
+            destination.accounts(),
+        )
+        .await?;
+        self.validate_delegates(shadow, &destination.delegates).await?;
+
+        request.chain_id = Some(self.chain_id());
+        request.from = Some(self.signer_address());
+        request.gas = None;
+        let live = self.active_config();
+        let estimation_permit = match &live.estimation_limiter {
+            Some(limiter) => Some(limiter.try_acquire().ok_or(OdysseyWalletError::Overloaded)?),
+            None => None,
+        };
+        let estimate =
+            EthCall::estimate_gas_at(&self.inner.eth_api, request.clone(), BlockId::latest(), None)
+                .await
+                .map_err(|_| OdysseyWalletError::InternalError)?
+                .saturating_to();
+        drop(estimation_permit);
+
+        // the live gauge is not updated
+        let global = shadow
+            .adaptive_gas_ceiling
+            .as_ref()
+            .map_or(shadow.config.gas_ceiling, |adaptive| adaptive.ceiling(Instant::now()));
+        let to = request.to.and_then(|to| to.to().copied());
+        let input = request.input.input().map(|input| input.as_ref()).unwrap_or_default();
+        let mut limit = shadow.config.gas_ceiling_overrides.resolve(to, input, global);
+        if let Some(deep) = &shadow.config.deep_call_gas_ceiling {
+            limit = deep.resolve(deep.probe.max_call_depth(&request).await, limit);
+        }
+        let limit = destination.gas_ceiling(shadow, limit);
+        validate_buffered_gas(estimate, shadow.config.gas_buffer_percent, limit)?;
+        validate_gas_profile(estimate, input, shadow.config.gas_per_calldata_byte.as_ref())?;
+        if let Some(growth) = &shadow.config.state_growth_limit {
+            growth.check(&request).await?;
+        }
+        Ok(())
+    }
+
+    /// Validates, signs and submits a sponsored transaction under the live configuration.
+    async fn sign_and_submit_live(
+        &self,
+        ext: &Extensions,
+        mut request: TransactionRequest,
+        attestation: Option<&str>,
+        estimate_inclusion: bool,
+        estimated: Option<U256>,
+    ) -> RpcResult<SendTransactionResponse> {
+        // the whole request is served with the configuration it started with
+        let config = self.active_config();
+        ensure_enabled(&config.config.methods, WalletMethod::SendTransaction)?;
+
+        if let Err(err) = self.check_global_rate_limit(&config) {
+            self.inner.metrics.invalid_send_transaction_calls.increment(1);
+            return Err(err.into());
+        }
+
+        if let Err(err) =
+            ensure_synced(config.config.require_synced, EthApiSpec::is_syncing(&self.inner.eth_api))
+        {
+            self.inner.metrics.invalid_send_transaction_calls.increment(1);
+            return Err(err.into());
+        }
+
+        if let Err(err) = self.check_maintenance(&config) {
+            self.inner.metrics.invalid_send_transaction_calls.increment(1);
+            return Err(err.into());
+        }
+
+        if let Some(start) = config.config.sponsorship_start_block {
+            if let Err(err) = self
+                .inner
+                .provider
+                .best_block_number()
+                .map_err(|_| OdysseyWalletError::InternalError)
+                .and_then(|current| ensure_sponsorship_started(current, start))
+            {
+                self.inner.metrics.invalid_send_transaction_calls.increment(1);
+                return Err(err.into());
+            }
+        }
+
+        if let Err(err) =
+            self.check_connection_rate_limit(&config, ext.get::<ConnectionId>().copied())
+        {
+            self.inner.metrics.invalid_send_transaction_calls.increment(1);
+            return Err(err.into());
+        }
+
+        // stop sponsoring before the sponsor is drained
+        if let Err(err) = self.check_sponsor_balance(&config) {
+            self.inner.metrics.invalid_send_transaction_calls.increment(1);
+            return Err(err.into());
+        }
+
+        // validate fields common to eip-7702 and eip-1559, and the destination
+        let destination = match validate_tx_request(&request)
+            .and_then(|_| validate_calldata_size(&request, config.config.max_calldata_bytes))
+            .and_then(|_| validate_selector(&request, &config.config))
+            .and_then(|_| validate_chain_id(&request, self.chain_id()))
+            .and_then(|_| resolve_authorization_order(&config, &mut request))
+            .and_then(|_| {
+                validate_authorization_list_size(
+                    &request,
+                    config.config.max_authorization_list_bytes,
+                )
+            })
+            .and_then(|_| self.validate_destination(&config, &request))
+        {
+            Ok(destination) => destination,
+            Err(err) => {
+                self.inner.metrics.invalid_send_transaction_calls.increment(1);
+                return Err(err.into());
+            }
+        };
+
+        if let Err(err) = self.check_account_rate_limit(&config, &destination) {
+            self.inner.metrics.invalid_send_transaction_calls.increment(1);
+            return Err(err.into());
+        }
+
+        // ensure the client attested the accounts the transaction is sponsored for
+        if let Err(err) = validate_attestation(
+            config.config.attestation_verifier.as_ref(),
+            attestation,
+            destination.accounts(),
+        )
+        .await
+        {
+            self.inner.metrics.invalid_send_transaction_calls.increment(1);
+            return Err(err.into());
+        }
+
+        // ensure none of the delegates is flagged or unverified
+        if let Err(err) = self.validate_delegates(&config, &destination.delegates).await {
+            self.inner.metrics.invalid_send_transaction_calls.increment(1);
+            return Err(err.into());
+        }
+
+        // ensure the call is not likely to fail due to pending transactions of the account
+        if let Err(err) = self.check_delegated_state(&config, &request, &destination).await {
+            self.inner.metrics.invalid_send_transaction_calls.increment(1);
+            return Err(err.into());
+        }
+
+        // ensure the request does not match a suspicious pattern
+        if let Err(err) = self.detect_patterns(&config, destination.to, &request) {
+            self.inner.metrics.invalid_send_transaction_calls.increment(1);
+            return Err(err.into());
+        }
+
+        // the permit is only held to reserve the nonce, all following operations run concurrently
+        // with other requests
+        let permit = match self.acquire_permit(&config).await {
+            Ok(permit) => permit,
+            Err(err) => {
+                self.inner.metrics.invalid_send_transaction_calls.increment(1);
+                return Err(err.into());
+            }
+        };
+
+        // set nonce, the pool is only consulted if the counter of the signer is not synced
+        let sender = self.signer_address();
+        if let Err(err) = self.check_signer_nonce_rate(&config, sender) {
+            self.inner.metrics.invalid_send_transaction_calls.increment(1);
+            return Err(err.into());
+        }
+        let reservation = match permit.assign_cached() {
+            Some(reservation) => reservation,
+            None => {
+                let pending_nonce = LoadState::next_available_nonce(&self.inner.eth_api, sender)
+                    .await
+                    .map_err(|err| {
+                        self.inner.metrics.invalid_send_transaction_calls.increment(1);
+                        err.into()
+                    })?;
+                permit.assign(pending_nonce)
+            }
+        };
+        let nonce = reservation.nonce();
+        request.nonce = Some(nonce);
+        // the ticket is taken while holding the permit, so jittered and batched submissions are in
+        // nonce order
+        let ticket = (config.config.submission_batch_window.is_some()
+            || config.config.submission_jitter.is_some())
+        .then(|| self.inner.ordered_submissions.ticket());
+        drop(permit);
+
+        // set chain id, if it was not set by the client
+        request.chain_id = Some(self.chain_id());
+
+        // set gas limit
+        // note: we also set the `from` field here to correctly estimate for contracts that use e.g.
+        // `tx.origin`
+        request.from = Some(sender);
+        // the limit of the client would cap the estimate
+        let gas_limit = request.gas.take();
+        let estimation_permit = match estimated {
+            Some(_) => None,
+            None => match self.acquire_estimation_permit(&config).await {
+                Ok(estimation_permit) => Some(estimation_permit),
+                Err(err) => {
+                    self.inner.metrics.invalid_send_transaction_calls.increment(1);
+                    return Err(err.into());
+                }
+            },
+        };
+        let (estimate, base_fee, fee_history) = tokio::join!(
+            async {
+                match estimated {
+                    Some(estimate) => Ok(estimate),
+                    None => {
+                        EthCall::estimate_gas_at(
+                            &self.inner.eth_api,
+                            request.clone(),
+                            BlockId::latest(),
+                            None,
+                        )
+                        .await
+                    }
+                }
+            },
+            LoadFee::eip1559_fees(&self.inner.eth_api, None, None),
+            async {
+                if !estimate_inclusion {
+                    return None;
+                }
+                EthFees::fee_history(
+                    &self.inner.eth_api,
+                    INCLUSION_FEE_HISTORY_BLOCKS,
+                    BlockNumberOrTag::Latest,
+                    Some(vec![INCLUSION_REWARD_PERCENTILE]),
+                )
+                .await
+                .ok()
+            }
+        );
+        let priority_fee = config.config.priority_fee.resolve(suggested_priority_fee(&base_fee));
+        let estimate = match estimate {
+            Ok(estimate) => Ok(estimate),
+            Err(err) => Err(self.estimate_error(&config, &request, err).await),
+        };
+        drop(estimation_permit);
+        let (estimate, base_fee) =
+            resolve_gas_and_base_fee(estimate, base_fee, config.config.fallback_base_fee)
+                .inspect_err(|_| {
+                    self.inner.metrics.invalid_send_transaction_calls.increment(1);
+                })?;
+        let base_fee = match self.check_fee_data_age(&config, base_fee) {
+            Ok(base_fee) => base_fee,
+            Err(err) => {
+                self.inner.metrics.invalid_send_transaction_calls.increment(1);
+                return Err(err.into());
+            }
+        };
+
+        let limit =
+            destination.gas_ceiling(&config, self.effective_gas_ceiling(&config, &request).await);
+        let buffered = match validate_buffered_gas(
+            estimate.saturating_to(),
+            config.config.gas_buffer_percent,
+            limit,
+        ) {
+            Ok(buffered) => buffered,
+            Err(err) => {
+                self.inner.metrics.invalid_send_transaction_calls.increment(1);
+                return Err(err.into());
+            }
+        };
+        if let Err(err) = validate_gas_profile(
+            estimate.saturating_to(),
+            request.input.input().map(|input| input.as_ref()).unwrap_or_default(),
+            config.config.gas_per_calldata_byte.as_ref(),
+        ) {
+            self.inner.metrics.invalid_send_transaction_calls.increment(1);
+            return Err(err.into());
+        }
+        if let Some(growth) = &config.config.state_growth_limit {
+            if let Err(err) = growth.check(&request).await {
+                self.inner.metrics.invalid_send_transaction_calls.increment(1);
+                return Err(err.into());
+            }
+        }
+        if let Some(adaptive) = &config.adaptive_gas_ceiling {
+            adaptive.record(estimate.saturating_to());
+        }
+        let gas_limit =
+            match resolve_gas_limit(gas_limit, buffered, config.config.honor_lower_gas_limit) {
+                Ok(gas_limit) => gas_limit,
+                Err(err) => {
+                    self.inner.metrics.invalid_send_transaction_calls.increment(1);
+                    return Err(err.into());
+                }
+            };
+        request.gas = Some(gas_limit);
+
+        // set gas price
+        let max_fee_per_gas = match self.enforce_minimum_fee(&config, base_fee + priority_fee).await
+        {
+            Ok(max_fee_per_gas) => max_fee_per_gas,
+            Err(err) => {
+                self.inner.metrics.invalid_send_transaction_calls.increment(1);
+                return Err(err.into());
+            }
+        };
+        set_fees(&mut request, max_fee_per_gas, priority_fee);
+        let cost = max_cost(&request);
+
+        // build and sign
+        if let Err(err) = ensure_buildable(&request) {
+            self.inner.metrics.invalid_send_transaction_calls.increment(1);
+            warn!(target: "rpc::wallet", %err, "Failed to build sponsored transaction");
+            return Err(err.into());
+        }
+        let envelope =
+            <TransactionRequest as TransactionBuilder<Ethereum>>::build::<EthereumWallet>(
+                request,
+                &self.inner.wallet,
+            )
+            .await
+            .map_err(|err| {
+                self.inner.metrics.invalid_send_transaction_calls.increment(1);
+                warn!(target: "rpc::wallet", %err, "Failed to build sponsored transaction");
+                build_error(err)
+            })?;
+
+        let tx: Bytes = envelope.encoded_2718().into();
+        if let Err(err) = validate_tx_size(&tx, config.config.max_tx_bytes) {
+            self.inner.metrics.invalid_send_transaction_calls.increment(1);
+            return Err(err.into());
+        }
+
+        // the maximum cost of the transaction is charged until it is submitted
+        let charges = match self.charge(&config, &destination, cost) {
+            Ok(charges) => charges,
+            Err(err) => {
+                self.inner.metrics.invalid_send_transaction_calls.increment(1);
+                return Err(err.into());
+            }
+        };
+
+        // all checks passed, increment the valid calls counter
+        self.inner.metrics.valid_send_transaction_calls.increment(1);
+
+        // the nonce is released if the submission fails
+        let submitted = match (config.config.submission_batch_window, ticket) {
+            (Some(window), Some(mut ticket)) => {
+                let batcher = config.batcher.get_or_init(|| {
+                    let eth_api = self.inner.eth_api.clone();
+                    SubmissionBatcher::spawn(window, move |tx| {
+                        let eth_api = eth_api.clone();
+                        async move { submit_raw_transaction(&eth_api, tx).await }
+                    })
+                });
+                // transactions are enqueued in nonce order, the next one may be enqueued once
+                // the ticket is dropped
+                ticket.wait_turn().await;
+                let enqueued = batcher.enqueue(nonce, tx.clone());
+                drop(ticket);
+                match enqueued {
+                    Ok(result) => result
+                        .await
+                        .unwrap_or_else(|_| Err(OdysseyWalletError::InternalError.into())),
+                    Err(err) => Err(err.into()),
+                }
+            }
+            (None, Some(mut ticket)) => {
+                if let Some(max_jitter) = config.config.submission_jitter {
+                    tokio::time::sleep(random_jitter(max_jitter)).await;
+                }
+                ticket.wait_turn().await;
+                submit_raw_transaction(&self.inner.eth_api, tx.clone()).await
+            }
+            _ => submit_raw_transaction(&self.inner.eth_api, tx.clone()).await,
+        };
+        let hash = submitted.inspect_err(|err| {
+            // the signer was used outside of the wallet
+            if is_nonce_too_low(err) {
+                self.inner.nonces.resync(&sender);
+            }
+        })?;
+        reservation.commit();
+        charges.commit();
+        self.inner.metrics.submitted_transactions(destination.kind).increment(1);
+        self.inner.recent_transactions.record(destination.accounts(), hash);
+        if let Some(tracking) = config.config.confirmation_tracking {
+            let eth_api = self.inner.eth_api.clone();
+            let histogram = self.inner.metrics.gas_estimate_error.clone();
+            let estimate = estimate.saturating_to();
+            tokio::task::spawn(async move {
+                let receipt = tracking
+                    .await_receipt(|| EthTransactions::transaction_receipt(&eth_api, hash))
+                    .await;
+                match receipt {
+                    Some(receipt) => record_gas_estimate_error(&histogram, estimate, &receipt),
+                    None => debug!(target: "rpc::wallet", %hash, "Gave up tracking transaction"),
+                }
+            });
+        }
+
+        let submission = SubmissionOutcome::new(config.config.forwards_to_sequencer, hash);
+        debug!(
+            target: "rpc::wallet",
+            %hash,
+            kind = ?destination.kind,
+            ?submission,
+            reason = submission.reason().description(),
+            "Submitted sponsored transaction"
+        );
+        let mut response = SendTransactionResponse::new(
+            tx,
+            nonce,
+            gas_limit,
+            max_fee_per_gas,
+            submission,
+            destination.kind,
+        );
+        response.estimated_inclusion_blocks = fee_history.and_then(|fee_history| {
+            estimate_inclusion_blocks(&fee_history, max_fee_per_gas, priority_fee)
+        });
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl<Provider, Eth> OdysseyWalletApiServer for OdysseyWallet<Provider, Eth>
+where
+    Provider: StateProviderFactory + HeaderProvider + Send + Sync + 'static,
+    Eth: FullEthApi + Send + Sync + 'static,
+{
+    fn get_capabilities(&self) -> RpcResult<WalletCapabilities> {
+        trace!(target: "rpc::wallet", "Serving wallet_getCapabilities");
+        Ok(self.capabilities())
+    }
+
+    fn health(&self) -> RpcResult<WalletHealth> {
+        trace!(target: "rpc::wallet", "Serving wallet_health");
+        Ok(WalletHealth { synced: !EthApiSpec::is_syncing(&self.inner.eth_api) })
+    }
+
+    fn error_catalog(&self) -> RpcResult<Vec<ErrorCatalogEntry>> {
+        trace!(target: "rpc::wallet", "Serving wallet_errorCatalog");
+        Ok(error_catalog())
+    }
+
+    fn sponsor_address(&self) -> RpcResult<Address> {
+        trace!(target: "rpc::wallet", "Serving wallet_sponsorAddress");
+        Ok(Self::sponsor_address(self))
+    }
+
+    fn delegation_info(&self, address: Address) -> RpcResult<DelegationInfo> {
+        trace!(target: "rpc::wallet", %address, "Serving wallet_delegationInfo");
+        Ok(Self::delegation_info(self, address)?)
+    }
+
+    async fn send_transaction(
+        &self,
+        ext: &Extensions,
+        request: TransactionRequest,
+        correlation_id: Option<String>,
+        attestation: Option<String>,
+    ) -> RpcResult<TxHash> {
+        let correlation_id = self.correlation_id(correlation_id)?;
+        let span = info_span!(target: "rpc::wallet", "wallet_sendTransaction", %correlation_id);
+        async {
+            trace!(target: "rpc::wallet", ?request, "Serving odyssey_sendTransaction");
+            self.sign_and_submit(ext, request, attestation.as_deref(), false, None)
+                .await
+                .map(|response| response.hash)
+        }
+        .instrument(span)
+        .await
+    }
+
+    async fn send_transaction_with_raw(
+        &self,
+        ext: &Extensions,
+        request: TransactionRequest,
+        correlation_id: Option<String>,
+        attestation: Option<String>,
+    ) -> RpcResult<SendTransactionResponse> {
+        let correlation_id = self.correlation_id(correlation_id)?;
+        let span =
+            info_span!(target: "rpc::wallet", "wallet_sendTransactionWithRaw", %correlation_id);
+        let response = async {
+            trace!(target: "rpc::wallet", ?request, "Serving wallet_sendTransactionWithRaw");
+            self.sign_and_submit(ext, request, attestation.as_deref(), true, None).await
+        }
+        .instrument(span)
+        .await?;
+        Ok(SendTransactionResponse { correlation_id: Some(correlation_id), ..response })
+    }
+
+    async fn estimate_sponsorship(
+        &self,
+        mut request: TransactionRequest,
+    ) -> RpcResult<SponsorshipEstimate> {
+        trace!(target: "rpc::wallet", ?request, "Serving wallet_estimateSponsorship");
+
+        let config = self.active_config();
+        ensure_enabled(&config.config.methods, WalletMethod::EstimateSponsorship)?;
+
+        let destination = self
+            .check_maintenance(&config)
+            .and_then(|_| validate_tx_request(&request))
+            .and_then(|_| validate_calldata_size(&request, config.config.max_calldata_bytes))
+            .and_then(|_| validate_selector(&request, &config.config))
+            .and_then(|_| validate_chain_id(&request, self.chain_id()))
+            .and_then(|_| resolve_authorization_order(&config, &mut request))
+            .and_then(|_| {
+                validate_authorization_list_size(
+                    &request,
+                    config.config.max_authorization_list_bytes,
+                )
+            })
+            .and_then(|_| self.validate_destination(&config, &request))?;
+        self.validate_delegates(&config, &destination.delegates).await?;
+
+        // the nonce is not reserved, as the transaction is never submitted
+        let sender = self.signer_address();
+        request.chain_id = Some(self.chain_id());
+        request.from = Some(sender);
+        let gas_limit = request.gas.take();
+        let estimation_permit = self.acquire_estimation_permit(&config).await?;
+        let (nonce, estimate, base_fee) = tokio::join!(
+            LoadState::next_available_nonce(&self.inner.eth_api, sender),
+            EthCall::estimate_gas_at(&self.inner.eth_api, request.clone(), BlockId::latest(), None),
+            LoadFee::eip1559_fees(&self.inner.eth_api, None, None)
+        );
+        let priority_fee = config.config.priority_fee.resolve(suggested_priority_fee(&base_fee));
+        let estimate = match estimate {
+            Ok(estimate) => Ok(estimate),
+            Err(err) => Err(self.estimate_error(&config, &request, err).await),
+        };
+        drop(estimation_permit);
+        let (estimate, base_fee) =
+            resolve_gas_and_base_fee(estimate, base_fee, config.config.fallback_base_fee)?;
+        let base_fee = self.check_fee_data_age(&config, base_fee)?;
+        let limit =
+            destination.gas_ceiling(&config, self.effective_gas_ceiling(&config, &request).await);
+        let buffered = validate_buffered_gas(
+            estimate.saturating_to(),
+            config.config.gas_buffer_percent,
+            limit,
+        )?;
+        validate_gas_profile(
+            estimate.saturating_to(),
+            request.input.input().map(|input| input.as_ref()).unwrap_or_default(),
+            config.config.gas_per_calldata_byte.as_ref(),
+        )?;
+        if let Some(growth) = &config.config.state_growth_limit {
+            growth.check(&request).await?;
+        }
+        let gas_limit =
+            resolve_gas_limit(gas_limit, buffered, config.config.honor_lower_gas_limit)?;
+        let max_fee_per_gas = base_fee + priority_fee;
+        let details = GasEstimateDetails::new(
+            estimate.saturating_to(),
+            config.config.gas_buffer_percent,
+            limit,
+            base_fee,
+            request.access_list.as_ref().is_some_and(|access_list| !access_list.is_empty()),
+        );
+
+        request.nonce = Some(nonce.map_err(Into::into)?);
+        request.gas = Some(gas_limit);
+        set_fees(&mut request, max_fee_per_gas, priority_fee);
+        let value = request.value.unwrap_or_default();
+        let l1_data_fee = self.l1_data_fee(request).await;
+
+        Ok(SponsorshipEstimate {
+            cost: GasBreakdown::new(gas_limit, max_fee_per_gas, l1_data_fee, value),
+            details,
+        })
+    }
+
+    fn recent_transactions(
+        &self,
+        account: Address,
+        limit: Option<usize>,
+    ) -> RpcResult<Vec<TxHash>> {
+        trace!(target: "rpc::wallet", %account, ?limit, "Serving wallet_recentTransactions");
+        Ok(Self::recent_transactions(self, account, limit.unwrap_or(MAX_RECENT_TRANSACTIONS)))
+    }
+
+    fn audit_log(
+        &self,
+        api_key: String,
+        cursor: Option<String>,
+        limit: Option<usize>,
+    ) -> RpcResult<AuditLogPage> {
+        trace!(target: "rpc::wallet", ?cursor, ?limit, "Serving wallet_auditLog");
+        let Some(key) = self.active_config().config.operator_api_key.clone() else {
+            return Err(OdysseyWalletError::MethodDisabled { method: "wallet_auditLog" }.into());
+        };
+        key.authorize(&api_key)?;
+        Ok(Self::audit_log(self, cursor.as_deref(), limit.unwrap_or(MAX_AUDIT_PAGE_SIZE))?)
+    }
+
+    async fn get_calls_status(&self, id: B256) -> RpcResult<CallsStatus> {
+        trace!(target: "rpc::wallet", %id, "Serving wallet_getCallsStatus");
+
+        let Some(hashes) = self.inner.bundles.get(&id) else {
+            // not a bundle, so the id is the hash of a single transaction
+            return match self.transaction_state(id).await? {
+                TransactionState::Unknown => Err(OdysseyWalletError::UnknownBundle { id }.into()),
+                state => Ok(CallsStatus::of(vec![state])),
+            };
+        };
+
+        let mut states = Vec::with_capacity(hashes.len());
+        for hash in hashes {
+            states.push(self.transaction_state(hash).await?);
+        }
+        Ok(CallsStatus::of(states))
+    }
+
+    fn batch_execution(&self, calls: Vec<Call>) -> RpcResult<BatchExecution> {
+        trace!(target: "rpc::wallet", ?calls, "Serving wallet_batchExecution");
+        Ok(BatchExecution::of(&batch_requests(calls)))
+    }
+
+    async fn subscribe_budget(&self, pending: PendingSubscriptionSink) -> SubscriptionResult {
+        trace!(target: "rpc::wallet", "Serving wallet_subscribeBudget");
+
+        let Some(alerts) = self.active_config().config.budget_alerts.clone() else {
+            pending
+                .reject(OdysseyWalletError::MethodDisabled { method: "wallet_subscribeBudget" })
+                .await;
+            return Ok(());
+        };
+        // subscribe before accepting, so no alert is missed in between
+        let mut receiver = alerts.subscribe();
+        let sink = pending.accept().await?;
+
+        loop {
+            let alert = tokio::select! {
+                _ = sink.closed() => break,
+                alert = receiver.recv() => alert,
+            };
+            match alert {
+                Ok(alert) => sink.send(SubscriptionMessage::from_json(&alert)?).await?,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(missed)) => {
+                    debug!(target: "rpc::wallet", missed, "Budget alert subscriber lagged");
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+        Ok(())
+    }
+
+    async fn estimate_calls(&self, calls: Vec<Call>) -> RpcResult<CallsEstimate> {
+        trace!(target: "rpc::wallet", ?calls, "Serving wallet_estimateCalls");
+
+        let config = self.active_config();
+        ensure_enabled(&config.config.methods, WalletMethod::EstimateSponsorship)?;
+        validate_batch_calldata(
+            calls.iter().map(|call| call.data.as_ref()),
+            config.config.max_batch_calldata_bytes,
+        )?;
+
+        let estimates = self.estimate_each(&config, &calls).await?;
+        Ok(CallsEstimate::new(estimates.into_iter().map(|call| call.estimate).collect()))
+    }
+
+    async fn send_calls(&self, ext: &Extensions, params: SendCallsParams) -> RpcResult<B256> {
+        let correlation_id = self.correlation_id(None)?;
+        let span = info_span!(target: "rpc::wallet", "wallet_sendCalls", %correlation_id);
+        async {
+            trace!(target: "rpc::wallet", ?params, "Serving wallet_sendCalls");
+
+            let config = self.active_config();
+            ensure_enabled(&config.config.methods, WalletMethod::SendTransaction)?;
+            if let Err(err) = params.validate(self.chain_id()).and_then(|_| {
+                validate_batch_calldata(
+                    params.calls.iter().map(|call| call.data.as_ref()),
+                    config.config.max_batch_calldata_bytes,
+                )
+            }) {
+                self.inner.metrics.invalid_send_transaction_calls.increment(1);
+                return Err(err.into());
+            }
+
+            // the gas of each call, and the combined gas, are checked before any call is
+            // submitted
+            let estimates = self.estimate_each(&config, &params.calls).await.inspect_err(|_| {
+                self.inner.metrics.invalid_send_transaction_calls.increment(1);
+            })?;
+            if let Err(err) = estimates
+                .iter()
+                .map(|call| {
+                    validate_buffered_gas(
+                        call.estimate,
+                        config.config.gas_buffer_percent,
+                        call.limit,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .and_then(|buffered| {
+                    validate_batch_gas(&buffered, self.global_gas_ceiling(&config))
+                })
+            {
+                self.inner.metrics.invalid_send_transaction_calls.increment(1);
+                return Err(err.into());
+            }
+
+            let mut hashes = Vec::with_capacity(params.calls.len());
+            for (request, call) in batch_requests(params.calls).into_iter().zip(estimates) {
+                let submitted = self
+                    .sign_and_submit(
+                        ext,
+                        request,
+                        params.attestation.as_deref(),
+                        false,
+                        Some(U256::from(call.estimate)),
+                    )
+                    .await;
+                match submitted {
+                    Ok(response) => hashes.push(response.hash),
+                    Err(err) if hashes.is_empty() => return Err(err),
+                    Err(err) => {
+                        // the submitted calls can still be tracked by the bundle id
+                        let id = self.inner.bundles.insert(hashes.clone());
+                        warn!(
+                            target: "rpc::wallet",
+                            %id,
+                            ?hashes,
+                            "Aborted bundle after submitting some of its calls"
+                        );
+                        return Err(OdysseyWalletError::BundlePartiallySubmitted {
+                            id,
+                            hashes,
+                            reason: err.message().to_owned(),
+                        }
+                        .into());
+                    }
+                }
+            }
+
+            let id = self.inner.bundles.insert(hashes);
+            debug!(target: "rpc::wallet", %id, "Submitted sponsored bundle");
+            Ok(id)
+        }
+        .instrument(span)
+        .await
+    }
+}
+
+impl<Provider, Eth> OdysseyWalletAdminApiServer for OdysseyWallet<Provider, Eth>
+where
+    Provider: Send + Sync + 'static,
+    Eth: Send + Sync + 'static,
+{
+    fn set_delegation_whitelist(&self, addresses: Vec<Address>) -> RpcResult<DelegationCapability> {
+        trace!(target: "rpc::wallet", ?addresses, "Serving admin_setDelegationWhitelist");
+        self.admin_update_whitelist("admin_setDelegationWhitelist", addresses, false)
+    }
+
+    fn add_delegation_targets(&self, addresses: Vec<Address>) -> RpcResult<DelegationCapability> {
+        trace!(target: "rpc::wallet", ?addresses, "Serving admin_addDelegationTargets");
+        self.admin_update_whitelist("admin_addDelegationTargets", addresses, true)
+    }
+}
+
+impl<Provider, Eth> OdysseyWallet<Provider, Eth> {
+    /// Serves an `admin_` request updating the delegation whitelist, if the namespace is enabled.
+    fn admin_update_whitelist(
+        &self,
+        method: &'static str,
+        addresses: Vec<Address>,
+        append: bool,
+    ) -> RpcResult<DelegationCapability> {
+        if !self.active_config().config.admin_api {
+            return Err(OdysseyWalletError::MethodDisabled { method }.into());
+        }
+        self.update_delegation_whitelist(addresses, append).map_err(|err| {
+            jsonrpsee::types::error::ErrorObject::owned::<()>(
+                jsonrpsee::types::error::INVALID_PARAMS_CODE,
+                err.to_string(),
+                None,
+            )
+        })
+    }
+}
+
+/// Implementation of the Odyssey `wallet_` namespace.
+#[derive(Debug)]
+struct OdysseyWalletInner<Provider, Eth> {
+    provider: Provider,
+    eth_api: Eth,
+    wallet: EthereumWallet,
+    /// The address of the default signer of the wallet, resolved once at construction.
+    signer_address: Address,
+    chain_id: ChainId,
+    /// The configuration in effect, replaced atomically on reconfiguration.
+    config: RwLock<Arc<ActiveConfig>>,
+    /// Reserves the nonces of the signer.
+    nonces: SignerNonces,
+    /// Keeps jittered submissions in nonce order.
+    ordered_submissions: OrderedSubmissions,
+    /// Bounds the number of shadow evaluations in flight.
+    shadow_evaluations: Arc<Semaphore>,
+    /// The transactions of the bundles submitted by `wallet_sendCalls`.
+    bundles: Bundles,
+    /// The recently sponsored transactions of each account.
+    recent_transactions: RecentTransactions,
+    /// The recent requests to sponsor a transaction.
+    audit_log: AuditLog,
+    /// Metrics for the `wallet_` RPC namespace.
+    metrics: WalletMetrics,
+    /// Whether the metrics were registered, or are no-ops.
+    metrics_active: bool,
+    /// The totals of the counters of the metrics.
+    counter_totals: CounterTotals,
+}
+
+/// Resolves the concurrently fetched gas estimate and fees into the estimate and base fee.
+///
+/// A failed estimate is an error, regardless of the fees. A failed fee lookup falls back to
+/// `fallback_base_fee`, if set.
+fn resolve_gas_and_base_fee<E, F>(
+    estimate: Result<U256, E>,
+    fees: Result<(U256, U256), F>,
+    fallback_base_fee: Option<u128>,
+) -> RpcResult<(U256, u128)>
+where
+    E: Into<jsonrpsee::types::error::ErrorObject<'static>>,
+    F: std::fmt::Debug,
+{
+    let estimate = estimate.map_err(Into::into)?;
+    let base_fee = match (fees, fallback_base_fee) {
+        (Ok((base_fee, _)), _) => base_fee.to(),
+        (Err(err), Some(fallback)) => {
+            warn!(target: "rpc::wallet", ?err, fallback, "Error fetching fees, using fallback base fee");
+            fallback
+        }
+        (Err(_), None) => return Err(OdysseyWalletError::InvalidTransactionRequest.into()),
+    };
+    Ok((estimate, base_fee))
+}
+
+/// Returns the priority fee suggested by the node, if the fees were fetched.
+fn suggested_priority_fee<E>(fees: &Result<(U256, U256), E>) -> Option<u128> {
+    fees.as_ref().ok().map(|(_, priority_fee)| priority_fee.saturating_to())
+}
+
+/// Sets the EIP-1559 fees the transaction is signed with.
+///
+/// Only the fee fields are overwritten, other fields set by the client, e.g. the access list, are
+/// signed as requested.
+fn set_fees(request: &mut TransactionRequest, max_fee_per_gas: u128, priority_fee: u128) {
+    request.max_fee_per_gas = Some(max_fee_per_gas);
+    request.max_priority_fee_per_gas = Some(priority_fee);
+    request.gas_price = None;
+}
+
+/// Returns the gas limit to sign the transaction with.
+///
+/// This is the estimate, unless `honor_lower` is set and the client set a lower limit. A limit
+/// above the estimate is rejected if `honor_lower` is set, and ignored otherwise.
+const fn resolve_gas_limit(
+    gas_limit: Option<u64>,
+    estimate: u64,
+    honor_lower: bool,
+) -> Result<u64, OdysseyWalletError> {
+    match gas_limit {
+        Some(gas_limit) if honor_lower && gas_limit > estimate => {
+            Err(OdysseyWalletError::GasLimitAboveEstimate { gas_limit, estimate })
+        }
+        Some(gas_limit) if honor_lower => Ok(gas_limit),
+        _ => Ok(estimate),
+    }
+}
+
+fn validate_tx_request(request: &TransactionRequest) -> Result<(), OdysseyWalletError> {
+    // reject transactions that have from set, as this will be the sequencer.
+    if request.from.is_some() {
+        return Err(OdysseyWalletError::FromSet);
+    }
+
+    // reject transaction requests that have nonce set, as this is managed by the sequencer.
+    if request.nonce.is_some() {
+        return Err(OdysseyWalletError::NonceSet);
+    }
+
+    // reject legacy, eip-2930 and other non eip-1559 or eip-7702 requests, instead of silently
+    // signing them as eip-1559 transactions with fees set by the sequencer. an access list alone
+    // is kept, as eip-1559 transactions carry one too.
+    match request.transaction_type {
+        Some(tx_type) if tx_type != 2 && tx_type != 4 => {
+            return Err(OdysseyWalletError::UnsupportedTransactionType { tx_type })
+        }
+        _ if request.gas_price.is_some() => {
+            let tx_type = if request.access_list.is_some() { 1 } else { 0 };
+            return Err(OdysseyWalletError::UnsupportedTransactionType { tx_type });
+        }
+        _ => {}
+    }
+
+    // reject transaction requests that set a blob fee without blobs, or vice versa.
+    let has_blobs = request.blob_versioned_hashes.as_ref().is_some_and(|hashes| !hashes.is_empty())
+        || request.sidecar.is_some();
+    if has_blobs != request.max_fee_per_blob_gas.is_some() {
+        return Err(OdysseyWalletError::InconsistentBlobFields);
+    }
+
+    // reject blob transactions, even if consistent.
+    if has_blobs || request.blob_versioned_hashes.is_some() {
+        return Err(OdysseyWalletError::BlobsNotSponsored);
+    }
+
+    Ok(())
+}
+
+/// Ensures the chain id of the request, if set, is the chain id of the wallet.
+///
+/// The chain ids of authorizations are validated with the destination.
+const fn validate_chain_id(
+    request: &TransactionRequest,
+    chain_id: ChainId,
+) -> Result<(), OdysseyWalletError> {
+    match request.chain_id {
+        Some(requested) if requested != chain_id => {
+            Err(OdysseyWalletError::ChainIdMismatch { chain_id: requested })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Submits a signed transaction.
+///
+/// This uses the internal `OpEthApi` to either forward the tx to the sequencer, or add it to the
+/// txpool.
+///
+/// See: <https://github.com/paradigmxyz/reth/blob/b67f004fbe8e1b7c05f84f314c4c9f2ed9be1891/crates/optimism/rpc/src/eth/transaction.rs#L35-L57>
+async fn submit_raw_transaction<Eth>(eth_api: &Eth, tx: Bytes) -> RpcResult<TxHash>
+where
+    Eth: EthTransactions,
+{
+    EthTransactions::send_raw_transaction(eth_api, tx)
+        .await
+        .inspect_err(
+            |err| warn!(target: "rpc::wallet", ?err, "Error adding sequencer-sponsored tx to pool"),
+        )
+        .map_err(Into::into)
+}
+
+/// The destination of a request, resolved against the latest state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ResolvedDestination {
+    /// The account the transaction is sent to, if any.
+    to: Option<Address>,
+    /// The contracts the account is delegated to, or, for EIP-7702 transactions, the contracts the
+    /// authorizations delegate to.
+    delegates: Vec<Address>,
+    /// The authorities and targets of the delegations of an EIP-7702 transaction.
+    delegations: Vec<(Address, Address)>,
+    /// What the transaction does.
+    kind: SponsoredTxKind,
+    /// Whether any delegate is an upgradeable proxy, if the [`ProxyDelegationPolicy`] is enabled.
+    proxied: bool,
+}
+
+impl ResolvedDestination {
+    /// Returns the destination and all delegates.
+    fn addresses(&self) -> impl Iterator<Item = Address> + '_ {
+        self.to.into_iter().chain(self.delegates.iter().copied())
+    }
+
+    /// Returns the gas ceiling for the request, given the ceiling that applies otherwise.
+    ///
+    /// Requests to accounts delegating to a proxy may have a tighter ceiling.
+    fn gas_ceiling(&self, config: &ActiveConfig, ceiling: u64) -> u64 {
+        match &config.config.proxy_delegations {
+            Some(policy) if self.proxied => policy.resolve_ceiling(ceiling),
+            _ => ceiling,
+        }
+    }
+
+    /// Returns the accounts the transaction is sponsored for, i.e. the destination and the
+    /// authorities of the delegations.
+    fn accounts(&self) -> impl Iterator<Item = Address> + '_ {
+        self.to.into_iter().chain(
+            self.delegations
+                .iter()
+                .map(|(authority, _)| *authority)
+                .filter(|authority| Some(*authority) != self.to),
+        )
+    }
+}
+
+/// Returns the error for a request that exceeded a rate limit.
+const fn rate_limited(exceeded: RateLimitExceeded) -> OdysseyWalletError {
+    OdysseyWalletError::RateLimited {
+        retry_after: exceeded.retry_after.as_millis().div_ceil(1_000) as u64,
+        escalation: exceeded.escalation,
+    }
+}
+
+/// Ensures all of the delegates are verified by the [`VerificationOracle`].
+async fn validate_verification(
+    oracle: &dyn VerificationOracle,
+    delegates: &[Address],
+) -> Result<(), OdysseyWalletError> {
+    for target in delegates {
+        if !oracle.is_verified(*target).await {
+            return Err(OdysseyWalletError::DestinationUnverified { target: *target });
+        }
+    }
+    Ok(())
+}
+
+/// Ensures none of the delegates is flagged by the [`RiskOracle`].
+async fn validate_risk(
+    oracle: &dyn RiskOracle,
+    delegates: &[Address],
+) -> Result<(), OdysseyWalletError> {
+    for target in delegates {
+        if oracle.is_flagged(*target).await {
+            return Err(OdysseyWalletError::DestinationFlagged { target: *target });
+        }
+    }
+    Ok(())
+}
+
+/// Ensures all fields required by the type of the transaction are set, so it can be built.
+fn ensure_buildable(request: &TransactionRequest) -> Result<(), OdysseyWalletError> {
+    let tx_type = TransactionBuilder::<Ethereum>::output_tx_type(request);
+    TransactionBuilder::<Ethereum>::complete_type(request, tx_type).map_err(|fields| {
+        OdysseyWalletError::MissingTransactionFields { tx_type: tx_type.to_string(), fields }
+    })
+}
+
+/// Maps a failure to build and sign a transaction to the error returned to the client.
+fn build_error(err: TransactionBuilderError<Ethereum>) -> OdysseyWalletError {
+    match err {
+        TransactionBuilderError::InvalidTransactionRequest(tx_type, fields) => {
+            OdysseyWalletError::MissingTransactionFields { tx_type: tx_type.to_string(), fields }
+        }
+        TransactionBuilderError::UnsupportedSignatureType | TransactionBuilderError::Signer(_) => {
+            OdysseyWalletError::SigningFailed { reason: err.to_string() }
+        }
+        TransactionBuilderError::Custom(_) => OdysseyWalletError::InvalidTransactionRequest,
+    }
+}
+
+/// Ensures the [`AttestationVerifier`] accepts the token for each of the accounts.
+async fn validate_attestation(
+    verifier: &dyn AttestationVerifier,
+    token: Option<&str>,
+    accounts: impl Iterator<Item = Address>,
+) -> Result<(), OdysseyWalletError> {
+    let token = token.unwrap_or_default();
+    for account in accounts {
+        if !verifier.verify(token, account).await {
+            return Err(OdysseyWalletError::AttestationRequired);
+        }
+    }
+    Ok(())
+}
+
+/// Ensures the given method is enabled in the [`MethodFlags`].
+const fn ensure_enabled(
+    methods: &MethodFlags,
+    method: WalletMethod,
+) -> Result<(), OdysseyWalletError> {
+    if !methods.is_enabled(method) {
+        return Err(OdysseyWalletError::MethodDisabled { method: method.name() });
+    }
+    Ok(())
+}
+
+/// Ensures the calldata of the request does not exceed the maximum.
+fn validate_calldata_size(
+    request: &TransactionRequest,
+    max: usize,
+) -> Result<(), OdysseyWalletError> {
+    let size = request.input.input().map_or(0, |input| input.len());
+    if size > max {
+        return Err(OdysseyWalletError::CalldataTooLarge { size, max });
+    }
+    Ok(())
+}
+
+/// Ensures the request calls an allowed function, if selectors are restricted.
+fn validate_selector(
+    request: &TransactionRequest,
+    config: &OdysseyWalletConfig,
+) -> Result<(), OdysseyWalletError> {
+    let input = request.input.input().map(|input| input.as_ref()).unwrap_or_default();
+    let selector: Option<[u8; 4]> = input.get(..4).and_then(|selector| selector.try_into().ok());
+    let allowed = match selector {
+        Some(selector) => {
+            !config.blocked_selectors.contains(&selector)
+                && config
+                    .allowed_selectors
+                    .as_ref()
+                    .is_none_or(|allowed| allowed.contains(&selector))
+        }
+        // without a selector, no listed function can be called
+        None => config.allowed_selectors.is_none(),
+    };
+    if !allowed {
+        let selector = Bytes::copy_from_slice(&input[..input.len().min(4)]);
+        return Err(OdysseyWalletError::SelectorNotAllowed { selector });
+    }
+    Ok(())
+}
+
+/// Ensures the combined calldata of a batch does not exceed the maximum, if any.
+fn validate_batch_calldata<'a>(
+    inputs: impl IntoIterator<Item = &'a [u8]>,
+    max: Option<usize>,
+) -> Result<(), OdysseyWalletError> {
+    let Some(max) = max else {
+        return Ok(());
+    };
+    let size = inputs.into_iter().map(<[u8]>::len).sum();
+    if size > max {
+        return Err(OdysseyWalletError::BatchCalldataTooLarge { size, max });
+    }
+    Ok(())
+}
+
+/// Ensures the estimate with a buffer of `buffer_percent` on top is below the ceiling, returning
+/// the buffered estimate.
+///
+/// The buffered estimate is what the transaction is signed with.
+const fn validate_buffered_gas(
+    estimate: u64,
+    buffer_percent: u64,
+    limit: u64,
+) -> Result<u64, OdysseyWalletError> {
+    let buffered = with_gas_buffer(estimate, buffer_percent);
+    if buffered >= limit {
+        return Err(OdysseyWalletError::GasEstimateTooHigh { estimate: buffered, limit });
+    }
+    Ok(buffered)
+}
+
+/// A call of a batch, estimated against the latest state.
+#[derive(Debug, Clone, Copy)]
+struct EstimatedCall {
+    /// The gas the call is estimated to consume.
+    estimate: u64,
+    /// The gas ceiling that applies to the call.
+    limit: u64,
+}
+
+/// Ensures the combined buffered gas estimate of a batch is below the ceiling.
+fn validate_batch_gas(estimates: &[u64], limit: u64) -> Result<(), OdysseyWalletError> {
+    let estimate = estimates.iter().fold(0u64, |total, gas| total.saturating_add(*gas));
+    if estimate >= limit {
+        return Err(OdysseyWalletError::GasEstimateTooHigh { estimate, limit });
+    }
+    Ok(())
+}
+
+/// Ensures the gas estimate per byte of calldata is within the band, if any.
+///
+/// Requests without calldata are not checked.
+fn validate_gas_profile(
+    estimate: u64,
+    calldata: &[u8],
+    band: Option<&RangeInclusive<u64>>,
+) -> Result<(), OdysseyWalletError> {
+    let Some(band) = band else {
+        return Ok(());
+    };
+    if calldata.is_empty() {
+        return Ok(());
+    }
+
+    let gas_per_byte = estimate / calldata.len() as u64;
+    if !band.contains(&gas_per_byte) {
+        return Err(OdysseyWalletError::AnomalousGasProfile { gas_per_byte });
+    }
+    Ok(())
+}
+
+/// Counts a call against the quotas of the delegation targets, if enabled.
+fn consume_destination_quotas<'a>(
+    config: &'a ActiveConfig,
+    delegates: &[Address],
+) -> Result<Option<QuotaReservation<'a>>, OdysseyWalletError> {
+    let Some(quotas) = &config.destination_quotas else {
+        return Ok(None);
+    };
+    quotas
+        .try_consume(delegates, Instant::now())
+        .map(Some)
+        .map_err(|target| OdysseyWalletError::DestinationQuotaExceeded { target })
+}
+
+/// The charges of a sponsored transaction, see [`OdysseyWallet::charge`].
+///
+/// If dropped without being [committed](Self::commit), e.g. because the submission failed, the
+/// calls counted against the quotas are released, and the delegations are untracked.
+#[derive(Debug)]
+struct Charges<'a> {
+    config: &'a ActiveConfig,
+    metrics: &'a WalletMetrics,
+    cost: U256,
+    quota: Option<QuotaReservation<'a>>,
+    pending: Option<PendingReservation<'a>>,
+    committed: bool,
+}
+
+impl Charges<'_> {
+    /// Keeps the charges, as the transaction was submitted.
+    fn commit(mut self) {
+        self.committed = true;
+        if let Some(quota) = self.quota.take() {
+            quota.commit();
+        }
+        if let Some(pending) = self.pending.take() {
+            pending.commit();
+        }
+    }
+}
+
+impl Drop for Charges<'_> {
+    fn drop(&mut self) {
+        if self.committed {
+            return;
+        }
+        // the transaction is not sponsored, so its cost is returned to the cap and the budget
+        let now = Instant::now();
+        if let Some(cap) = &self.config.spending_cap {
+            cap.refund(self.cost, now);
+            self.metrics.spending_cap_remaining.set(f64::from(cap.status(now).remaining));
+        }
+        if let Some(budget) = &self.config.budget {
+            budget.refund(self.cost, now);
+            self.metrics.sponsorship_budget.set(f64::from(budget.level(now)));
+        }
+    }
+}
+
+/// Restores the sponsorship budget, the spending cap, the destination quotas and the account rate
+/// limits from a snapshot.
+///
+/// Components that are not enabled in the configuration are skipped.
+fn restore_state(config: &ActiveConfig, snapshot: StateSnapshot) {
+    let now = Instant::now();
+    let now_unix = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    if let (Some(budget), Some(level)) = (&config.budget, snapshot.budget) {
+        budget.restore(level, persist::from_unix(snapshot.taken_at, now, now_unix));
+    }
+    if let Some(quotas) = &config.destination_quotas {
+        quotas.restore(
+            snapshot
+                .destination_quotas
+                .into_iter()
+                .map(|(target, calls)| {
+                    let calls = calls.into_iter().map(|at| persist::from_unix(at, now, now_unix));
+                    (target, calls.collect())
+                })
+                .collect(),
+        );
+    }
+    if let (Some(cap), Some(spending)) = (&config.spending_cap, snapshot.spending_cap) {
+        cap.restore(spending.spent, persist::from_unix(spending.window_start, now, now_unix));
+    }
+    if let Some(limiter) = &config.account_rate_limiter {
+        limiter.restore(
+            snapshot
+                .account_rate_limits
+                .into_iter()
+                .map(|(account, window)| {
+                    let window = Window {
+                        started_at: persist::from_unix(window.started_at, now, now_unix),
+                        requests: window.requests,
+                        violations: window.violations.map(|(violations, at)| {
+                            (violations, persist::from_unix(at, now, now_unix))
+                        }),
+                    };
+                    (account, window)
+                })
+                .collect(),
+        );
+    }
+}
+
+/// Ensures the RLP encoded authorization list of the request does not exceed the maximum size, if
+/// any.
+///
+/// Unlike the size of the signed transaction, this is checked before estimating, as the
+/// authorization list is not changed by signing.
+fn validate_authorization_list_size(
+    request: &TransactionRequest,
+    max: Option<usize>,
+) -> Result<(), OdysseyWalletError> {
+    let (Some(max), Some(authorizations)) = (max, &request.authorization_list) else {
+        return Ok(());
+    };
+    let size = alloy_rlp::list_length(authorizations);
+    if size > max {
+        return Err(OdysseyWalletError::AuthorizationListTooLarge { size, max });
+    }
+    Ok(())
+}
+
+/// Ensures the encoded transaction does not exceed the maximum size, if any.
+const fn validate_tx_size(tx: &[u8], max: Option<usize>) -> Result<(), OdysseyWalletError> {
+    if let Some(max) = max {
+        if tx.len() > max {
+            return Err(OdysseyWalletError::TransactionTooLarge { size: tx.len(), max });
+        }
+    }
+    Ok(())
+}
+
+/// Ensures the request carries no authorization list if EIP-7702 is not supported.
+const fn ensure_7702_supported(
+    request: &TransactionRequest,
+    supports_7702: bool,
+) -> Result<(), OdysseyWalletError> {
+    if !supports_7702 && request.authorization_list.is_some() {
+        return Err(OdysseyWalletError::Eip7702NotSupported);
+    }
+    Ok(())
+}
+
+/// Ensures the node is synced, if required.
+const fn ensure_synced(required: bool, is_syncing: bool) -> Result<(), OdysseyWalletError> {
+    if required && is_syncing {
+        return Err(OdysseyWalletError::NodeNotSynced);
+    }
+    Ok(())
+}
+
+/// Returns the maximum cost of a signed request to the sponsor, i.e. its gas limit at its max fee
+/// per gas plus the value it sends.
+fn max_cost(request: &TransactionRequest) -> U256 {
+    let gas = U256::from(request.gas.unwrap_or_default())
+        * U256::from(request.max_fee_per_gas.unwrap_or_default());
+    gas.saturating_add(request.value.unwrap_or_default())
+}
+
+/// Ensures the request has no value, unless the value is within the allowance of each delegate.
+///
+/// Non-zero values are rejected to prevent draining the sequencer. If the delegates have different
+/// allowances, the lowest one applies, and requests without delegates may not carry any value.
+fn validate_value(
+    request: &TransactionRequest,
+    delegates: &[Address],
+    value_allowances: Option<&HashMap<Address, U256>>,
+) -> Result<(), OdysseyWalletError> {
+    let Some(value) = request.value.filter(|value| *value > U256::ZERO) else {
+        return Ok(());
+    };
+    // a delegate without an allowance allows no value
+    let allowance = delegates
+        .iter()
+        .map(|delegate| value_allowances.and_then(|allowances| allowances.get(delegate).copied()))
+        .min()
+        .flatten();
+    if allowance.is_none_or(|allowance| value > allowance) {
+        return Err(OdysseyWalletError::ValueNotZero);
+    }
+    Ok(())
+}
+
+/// Ensures all delegates are whitelisted.
+///
+/// Without a whitelist, no delegate is sponsored.
+fn validate_whitelisted(
+    delegates: &[Address],
+    whitelist: Option<&DelegationWhitelist>,
+) -> Result<(), OdysseyWalletError> {
+    if !delegates.iter().all(|delegate| whitelist.is_some_and(|w| w.contains(delegate))) {
+        return Err(OdysseyWalletError::IllegalDestination);
+    }
+    Ok(())
+}
+
+/// Ensures the account has no transactions pending in the pool, i.e. its next available nonce
+/// equals its nonce in the latest state.
+const fn detect_delegated_state_conflict(
+    account: Address,
+    latest_nonce: u64,
+    pending_nonce: u64,
+) -> Result<(), OdysseyWalletError> {
+    if pending_nonce > latest_nonce {
+        return Err(OdysseyWalletError::DelegatedStateConflict { account });
+    }
+    Ok(())
+}
+
+/// Ensures the balance of the sponsor is not below the minimum, if any.
+fn ensure_sponsor_funded(balance: U256, minimum: Option<U256>) -> Result<(), OdysseyWalletError> {
+    match minimum {
+        Some(minimum) if balance < minimum => {
+            Err(OdysseyWalletError::SponsorInsufficientFunds { balance, minimum })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Ensures the chain reached the block sponsorship starts at.
+const fn ensure_sponsorship_started(current: u64, start: u64) -> Result<(), OdysseyWalletError> {
+    if current < start {
+        return Err(OdysseyWalletError::SponsorshipNotStarted { current, start });
+    }
+    Ok(())
+}
+
+/// Ensures none of the given addresses is a precompile or system address.
+fn validate_not_system_address(
+    addresses: impl IntoIterator<Item = Address>,
+    system_addresses: &SystemAddresses,
+) -> Result<(), OdysseyWalletError> {
+    match addresses.into_iter().find(|address| system_addresses.contains(address)) {
+        Some(address) => Err(OdysseyWalletError::SystemAddressNotAllowed { address }),
+        None => Ok(()),
+    }
+}
+
+/// Returns the targets the authorizations delegate to.
+///
+/// If `allow_revocation` is set, revocations, i.e. authorizations to the zero address, are
+/// skipped, as they do not delegate to any code.
+fn authorization_delegates(
+    authorizations: &[SignedAuthorization],
+    allow_revocation: bool,
+) -> Vec<Address> {
+    authorizations
+        .iter()
+        .map(|authorization| authorization.address)
+        .filter(|address| !(allow_revocation && address.is_zero()))
+        .collect()
+}
+
+/// Ensures none of the given accounts is blocked.
+fn validate_not_blocked(
+    accounts: impl IntoIterator<Item = Address>,
+    blocked: &HashSet<Address>,
+) -> Result<(), OdysseyWalletError> {
+    match accounts.into_iter().find(|account| blocked.contains(account)) {
+        Some(address) => Err(OdysseyWalletError::AddressBlocked { address }),
+        None => Ok(()),
+    }
+}
+
+/// Ensures the authorization list is not empty, and that every authorization is valid on this
+/// chain, i.e. its chain id is `chain_id` or, unless universal authorizations are rejected, 0.
+fn validate_authorization_chain_ids(
+    authorizations: &[SignedAuthorization],
+    chain_id: ChainId,
+    reject_universal: bool,
+) -> Result<(), OdysseyWalletError> {
+    if authorizations.is_empty() {
+        return Err(OdysseyWalletError::EmptyAuthorizationList);
+    }
+    for authorization in authorizations {
+        match authorization.chain_id {
+            0 if reject_universal => {
+                return Err(OdysseyWalletError::UniversalAuthorizationNotAllowed)
+            }
+            0 => {}
+            id if id != chain_id => {
+                return Err(OdysseyWalletError::AuthorizationChainIdMismatch { chain_id: id })
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Handles authorizations that delegate the same authority to different targets, if configured.
+fn resolve_authorization_order(
+    config: &ActiveConfig,
+    request: &mut TransactionRequest,
+) -> Result<(), OdysseyWalletError> {
+    match (request.authorization_list.as_mut(), config.config.contradictory_authorizations) {
+        (Some(authorizations), Some(handling)) => {
+            resolve_contradictory_authorizations(authorizations, handling)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Ensures the nonce of an authorization is in `current..=current + tolerance`.
+fn validate_authorization_nonce(
+    authority: Address,
+    nonce: u64,
+    current: u64,
+    tolerance: u64,
+) -> Result<(), OdysseyWalletError> {
+    if !(current..=current.saturating_add(tolerance)).contains(&nonce) {
+        return Err(OdysseyWalletError::AuthorizationNonceOutOfRange { authority, nonce, current });
+    }
+    Ok(())
+}
+
+/// Metrics for the `wallet_` RPC namespace.
+#[derive(Metrics)]
+#[metrics(scope = "wallet")]
+struct WalletMetrics {
+    /// Number of invalid calls to `odyssey_sendTransaction`
+    invalid_send_transaction_calls: Counter,
+    /// Number of valid calls to `odyssey_sendTransaction`
+    valid_send_transaction_calls: Counter,
+    /// The current adaptive gas ceiling, if enabled
+    adaptive_gas_ceiling: Gauge,
+    /// The remaining sponsorship budget in wei, if enabled
+    sponsorship_budget: Gauge,
+    /// The amount of wei left within the current window of the spending cap, if enabled
+    spending_cap_remaining: Gauge,
+    /// The balance of the sponsor in wei, as of the last `wallet_sendTransaction` request
+    sponsor_balance: Gauge,
+    /// Seconds until the next maintenance window starts, if any are scheduled
+    maintenance_starts_in: Gauge,
+    /// Time spent waiting for the signing permit, in seconds
+    permit_wait_time: Histogram,
+    /// The gas estimate of included transactions minus the gas they used, if confirmations are
+    /// tracked
+    gas_estimate_error: Histogram,
+    /// Number of `odyssey_sendTransaction` requests admitted within the current second, if the
+    /// global rate limit is enabled
+    global_request_rate: Gauge,
+    /// Number of nonces assigned to the signer within the last minute, if the signer nonce rate
+    /// limit is enabled
+    signer_nonce_rate: Gauge,
+    /// Number of requests waiting to run a gas estimation, if the estimation limit is enabled
+    estimation_queue_depth: Gauge,
+    /// Number of submitted transactions delegating accounts for the first time
+    delegation_setup_transactions: Counter,
+    /// Number of submitted transactions calling delegated accounts
+    delegated_call_transactions: Counter,
+    /// Number of submitted transactions changing the delegation of accounts
+    delegation_change_transactions: Counter,
+    /// Number of accepted requests the shadow configuration rejects, if set
+    shadow_rejections: Counter,
+    /// Number of rejected requests the shadow configuration accepts, if set
+    shadow_acceptances: Counter,
+}
+
+impl WalletMetrics {
+    /// Registers the metrics with the current recorder.
+    ///
+    /// Returns `None` if the recorder panics, e.g. because of conflicting registrations.
+    fn try_register() -> Option<Self> {
+        std::panic::catch_unwind(Self::default)
+            .inspect_err(|_| {
+                warn!(target: "rpc::wallet", "Failed to register wallet metrics, metrics are disabled")
+            })
+            .ok()
+    }
+
+    /// Returns metrics that discard all values.
+    fn noop() -> Self {
+        Self {
+            invalid_send_transaction_calls: Counter::noop(),
+            valid_send_transaction_calls: Counter::noop(),
+            adaptive_gas_ceiling: Gauge::noop(),
+            sponsorship_budget: Gauge::noop(),
+            spending_cap_remaining: Gauge::noop(),
+            sponsor_balance: Gauge::noop(),
+            maintenance_starts_in: Gauge::noop(),
+            permit_wait_time: Histogram::noop(),
+            gas_estimate_error: Histogram::noop(),
+            global_request_rate: Gauge::noop(),
+            signer_nonce_rate: Gauge::noop(),
+            estimation_queue_depth: Gauge::noop(),
+            delegation_setup_transactions: Counter::noop(),
+            delegated_call_transactions: Counter::noop(),
+            delegation_change_transactions: Counter::noop(),
+            shadow_rejections: Counter::noop(),
+            shadow_acceptances: Counter::noop(),
+        }
+    }
+
+    /// Wraps the counters, so their totals are also kept in `totals`.
+    fn observed(self, totals: &CounterTotals) -> Self {
+        Self {
+            invalid_send_transaction_calls: observed(
+                self.invalid_send_transaction_calls,
+                &totals.invalid_send_transaction_calls,
+            ),
+            valid_send_transaction_calls: observed(
+                self.valid_send_transaction_calls,
+                &totals.valid_send_transaction_calls,
+            ),
+            delegation_setup_transactions: observed(
+                self.delegation_setup_transactions,
+                &totals.delegation_setup_transactions,
+            ),
+            delegated_call_transactions: observed(
+                self.delegated_call_transactions,
+                &totals.delegated_call_transactions,
+            ),
+            delegation_change_transactions: observed(
+                self.delegation_change_transactions,
+                &totals.delegation_change_transactions,
+            ),
+            shadow_rejections: observed(self.shadow_rejections, &totals.shadow_rejections),
+            shadow_acceptances: observed(self.shadow_acceptances, &totals.shadow_acceptances),
+            ..self
+        }
+    }
+
+    /// Returns the counter of submitted transactions of the given kind.
+    const fn submitted_transactions(&self, kind: SponsoredTxKind) -> &Counter {
+        match kind {
+            SponsoredTxKind::DelegationSetup => &self.delegation_setup_transactions,
+            SponsoredTxKind::DelegatedCall => &self.delegated_call_transactions,
+            SponsoredTxKind::DelegationChange => &self.delegation_change_transactions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        authorization_delegates, batch_requests, build_error, consume_destination_quotas,
+        detect_delegated_state_conflict, ensure_7702_supported, ensure_buildable, ensure_enabled,
+        ensure_sponsor_funded, ensure_sponsorship_started, ensure_synced, max_cost,
+        resolve_gas_and_base_fee, resolve_gas_limit, set_fees, suggested_priority_fee,
+        validate_attestation, validate_authorization_chain_ids, validate_authorization_list_size,
+        validate_authorization_nonce, validate_batch_calldata, validate_batch_gas,
+        validate_buffered_gas, validate_calldata_size, validate_chain_id, validate_gas_profile,
+        validate_not_blocked, validate_not_system_address, validate_risk, validate_selector,
+        validate_tx_request, validate_tx_size, validate_value, validate_verification,
+        validate_whitelisted, AttestationVerifier, BalanceThreshold, BatchExecution,
+        BudgetAlertSource, BudgetAlerts, Call, CallDepthProbe, ConfigError, ConflictingDelegations,
+        DelegationCapability, DelegationWhitelist, Divergence, DuplicateWhitelistEntries,
+        FileStateStore, GlobalRateLimitConfig, InMemoryRiskOracle, MethodFlags, MetricsSnapshot,
+        NoopAttestationVerifier, NoopVerificationOracle, OdysseyWallet,
+        OdysseyWalletAdminApiServer, OdysseyWalletConfig, OdysseyWalletError, PriorityFee,
+        RateLimitBackoff, RateLimitConfig, ResolvedDestination, SaturatedEstimations,
+        SpendingCapStatus, SponsoredTxKind, SponsorshipBudgetConfig, SystemAddresses,
+        VerificationOracle, WalletCapabilities, WalletMethod, DEFAULT_GAS_CEILING,
+        DEFAULT_MAX_CALLDATA_BYTES, DEFAULT_PRIORITY_FEE, WALLET_PROTOCOL_VERSION,
+    };
+    use alloy_consensus::Transaction;
+    use alloy_eips::{
+        eip2930::{AccessList, AccessListItem},
+        eip7702::{Authorization, SignedAuthorization},
+    };
+    use alloy_network::{Ethereum, EthereumWallet, NetworkWallet, TransactionBuilder};
+    use alloy_primitives::{address, Address, Bytes, B256, U256};
+    use alloy_rpc_types::TransactionRequest;
+    use jsonrpsee::{core::async_trait, types::error::ErrorObject, ConnectionId};
+    use metrics::{
+        Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit,
+    };
+    use std::{
+        collections::{HashMap, HashSet},
+        ops::RangeInclusive,
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+
+    #[test]
+    fn signer_address_is_cached() {
+        let wallet = EthereumWallet::default();
+        let expected = NetworkWallet::<Ethereum>::default_signer_address(&wallet);
+        let wallet = OdysseyWallet::new((), wallet, (), 1, DelegationCapability::default());
+
+        assert_eq!(wallet.signer_address(), expected);
+        assert_eq!(wallet.signer_address(), wallet.inner.signer_address);
+    }
+
+    #[test]
+    fn sponsor_address_is_signer() {
+        let signer = EthereumWallet::default();
+        let wallet = OdysseyWallet::new((), signer.clone(), (), 1, DelegationCapability::default());
+
+        assert_eq!(
+            wallet.sponsor_address(),
+            NetworkWallet::<Ethereum>::default_signer_address(&signer)
+        );
+    }
+
+    #[test]
+    fn config_updates_are_atomic() {
+        let config = |max| {
+            OdysseyWalletConfig::default()
+                .with_max_tx_bytes(max)
+                .with_max_batch_calldata_bytes(max)
+                .with_sponsorship_budget(SponsorshipBudgetConfig {
+                    capacity: U256::from(1_000),
+                    refill_per_second: U256::ZERO,
+                })
+        };
+        let wallet =
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config(0)).unwrap();
+        let budget = wallet.active_config().budget.clone().unwrap();
+
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..1_000 {
+                        // a request never observes a mix of two configurations
+                        let active = wallet.active_config();
+                        assert_eq!(
+                            active.config.max_tx_bytes,
+                            active.config.max_batch_calldata_bytes
+                        );
+                    }
+                });
+            }
+            scope.spawn(|| {
+                for max in 1..=1_000 {
+                    wallet.update_config(config(max)).unwrap();
+                }
+            });
+        });
+        assert_eq!(wallet.active_config().config.max_tx_bytes, Some(1_000));
+
+        // the unchanged budget is carried over
+        assert!(Arc::ptr_eq(&budget, wallet.active_config().budget.as_ref().unwrap()));
+
+        // invalid updates are not applied
+        assert_eq!(
+            wallet.update_config(config(1).with_delegation_cohort(RangeInclusive::new(10, 5))),
+            Err(ConfigError::EmptyCohort)
+        );
+        assert_eq!(wallet.active_config().config.max_tx_bytes, Some(1_000));
+    }
+
+    #[test]
+    fn duplicate_whitelist_entries() {
+        let a = Address::with_last_byte(1);
+        let config = OdysseyWalletConfig::default()
+            .with_delegation_whitelist(DelegationCapability { addresses: vec![a, a] });
+        let wallet = OdysseyWallet::with_config(
+            (),
+            EthereumWallet::default(),
+            (),
+            1,
+            OdysseyWalletConfig::default(),
+        )
+        .unwrap();
+
+        // rejected if configured
+        assert_eq!(
+            wallet.update_config(
+                config.clone().with_duplicate_whitelist_entries(DuplicateWhitelistEntries::Reject)
+            ),
+            Err(ConfigError::DuplicateWhitelistEntries(vec![a]))
+        );
+        assert!(wallet.active_config().whitelist.is_none());
+
+        // deduplicated otherwise
+        wallet.update_config(config).unwrap();
+        assert!(wallet.active_rules().contains(&"whitelist=1 addresses".to_string()));
+    }
+
+    #[test]
+    fn constructs_without_metrics_recorder() {
+        // no recorder is installed in tests
+        let wallet = OdysseyWallet::new(
+            (),
+            EthereumWallet::default(),
+            (),
+            1,
+            DelegationCapability::default(),
+        );
+        assert!(wallet.metrics_active());
+        wallet.inner.metrics.valid_send_transaction_calls.increment(1);
+        assert_eq!(wallet.metrics_snapshot().valid_send_transaction_calls, 1);
+
+        // the counters are observable with metrics disabled
+        let config = OdysseyWalletConfig::default().with_metrics(false);
+        let wallet =
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config).unwrap();
+        assert!(!wallet.metrics_active());
+        wallet.inner.metrics.invalid_send_transaction_calls.increment(1);
+        wallet.inner.metrics.submitted_transactions(SponsoredTxKind::DelegationSetup).increment(1);
+        assert_eq!(
+            wallet.metrics_snapshot(),
+            MetricsSnapshot {
+                invalid_send_transaction_calls: 1,
+                delegation_setup_transactions: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn constructs_if_metrics_registration_fails() {
+        struct PanickingRecorder;
+
+        impl Recorder for PanickingRecorder {
+            fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+            fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+            fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+            fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+                panic!("conflicting registration of {key}")
+            }
+            fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+                panic!("conflicting registration of {key}")
+            }
+            fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+                panic!("conflicting registration of {key}")
+            }
+        }
+
+        let wallet = metrics::with_local_recorder(&PanickingRecorder, || {
+            OdysseyWallet::new(
+                (),
+                EthereumWallet::default(),
+                (),
+                1,
+                DelegationCapability::default(),
+            )
+        });
+        assert!(!wallet.metrics_active());
+        wallet.inner.metrics.invalid_send_transaction_calls.increment(1);
+    }
+
+    #[tokio::test]
+    async fn permit_acquire_times_out() {
+        let config =
+            OdysseyWalletConfig::default().with_permit_acquire_timeout(Duration::from_millis(10));
+        let wallet =
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config).unwrap();
+        let active = wallet.active_config();
+
+        // another request holds the permit
+        let permit = wallet.inner.nonces.lock(&wallet.signer_address()).await;
+        assert_eq!(
+            wallet.acquire_permit(&active).await.map(drop),
+            Err(OdysseyWalletError::Overloaded)
+        );
+
+        drop(permit);
+        assert!(wallet.acquire_permit(&active).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn bounds_estimations_across_requests() {
+        let config =
+            OdysseyWalletConfig::default().with_estimation_limit(1, SaturatedEstimations::Reject);
+        let wallet =
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config).unwrap();
+        let active = wallet.active_config();
+
+        let permit = wallet.acquire_estimation_permit(&active).await.unwrap();
+        assert!(permit.is_some());
+        assert_eq!(
+            wallet.acquire_estimation_permit(&active).await.map(drop),
+            Err(OdysseyWalletError::Overloaded)
+        );
+        drop(permit);
+        assert!(wallet.acquire_estimation_permit(&active).await.unwrap().is_some());
+
+        assert_eq!(
+            wallet.update_config(
+                OdysseyWalletConfig::default()
+                    .with_estimation_limit(0, SaturatedEstimations::Queue)
+            ),
+            Err(ConfigError::EmptyEstimationLimit)
+        );
+        wallet.update_config(OdysseyWalletConfig::default()).unwrap();
+        assert!(wallet.acquire_estimation_permit(&wallet.active_config()).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn updates_whitelist_at_runtime() {
+        let initial = Address::with_last_byte(1);
+        let added = Address::with_last_byte(2);
+        let config = OdysseyWalletConfig::default()
+            .with_delegation_whitelist(DelegationCapability { addresses: vec![initial] });
+        let wallet =
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config).unwrap();
+
+        // the admin namespace is disabled by default
+        assert_eq!(
+            OdysseyWalletAdminApiServer::add_delegation_targets(&wallet, vec![added]),
+            Err(OdysseyWalletError::MethodDisabled { method: "admin_addDelegationTargets" }.into())
+        );
+        let whitelist = wallet.active_config().current_whitelist();
+        assert_eq!(
+            validate_whitelisted(&[added], whitelist.as_deref()),
+            Err(OdysseyWalletError::IllegalDestination)
+        );
+
+        let config = wallet.active_config().config.clone().with_admin_api();
+        wallet.update_config(config).unwrap();
+        assert_eq!(
+            OdysseyWalletAdminApiServer::add_delegation_targets(&wallet, vec![added, initial]),
+            Ok(DelegationCapability { addresses: vec![initial, added] })
+        );
+        let whitelist = wallet.active_config().current_whitelist();
+        assert_eq!(validate_whitelisted(&[added], whitelist.as_deref()), Ok(()));
+        assert_eq!(
+            serde_json::to_value(wallet.capabilities()).unwrap()["0x1"]["delegation"],
+            serde_json::json!({ "addresses": [initial, added] })
+        );
+
+        // replacing drops the previous targets
+        assert_eq!(
+            OdysseyWalletAdminApiServer::set_delegation_whitelist(&wallet, vec![added]),
+            Ok(DelegationCapability { addresses: vec![added] })
+        );
+        let whitelist = wallet.active_config().current_whitelist();
+        assert_eq!(
+            validate_whitelisted(&[initial], whitelist.as_deref()),
+            Err(OdysseyWalletError::IllegalDestination)
+        );
+    }
+
+    #[test]
+    fn inherits_whitelist_from_registry() {
+        let registry = Address::with_last_byte(0x42);
+        let target = Address::with_last_byte(1);
+        let bootstrap = Address::with_last_byte(2);
+        let config = OdysseyWalletConfig::default()
+            .with_delegation_whitelist(DelegationCapability { addresses: vec![bootstrap] })
+            .with_whitelist_registry(registry, Duration::from_secs(60));
+        let wallet =
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config).unwrap();
+
+        // the configured whitelist applies until the registry was read
+        let whitelist = wallet.active_config().current_whitelist();
+        assert_eq!(
+            validate_whitelisted(&[target], whitelist.as_deref()),
+            Err(OdysseyWalletError::IllegalDestination)
+        );
+        assert_eq!(validate_whitelisted(&[bootstrap], whitelist.as_deref()), Ok(()));
+        let delegation = |wallet: &OdysseyWallet<(), ()>| {
+            serde_json::to_value(wallet.capabilities()).unwrap()["0x1"]["delegation"].clone()
+        };
+        assert_eq!(delegation(&wallet), serde_json::json!({ "addresses": [bootstrap] }));
+
+        let mut output = U256::from(32).to_be_bytes::<32>().to_vec();
+        output.extend(U256::from(1).to_be_bytes::<32>());
+        output.extend(target.into_word());
+        let active = wallet.active_config();
+        assert!(active.registry_whitelist.as_ref().unwrap().update(Ok::<_, ()>(output.into())));
+        let whitelist = active.current_whitelist();
+        assert_eq!(validate_whitelisted(&[target], whitelist.as_deref()), Ok(()));
+        // and then takes precedence
+        assert_eq!(
+            validate_whitelisted(&[bootstrap], whitelist.as_deref()),
+            Err(OdysseyWalletError::IllegalDestination)
+        );
+
+        // the list read is kept across reconfigurations with the same registry
+        wallet
+            .update_config(
+                OdysseyWalletConfig::default()
+                    .with_whitelist_registry(registry, Duration::from_secs(30)),
+            )
+            .unwrap();
+        assert_eq!(delegation(&wallet), serde_json::json!({ "addresses": [target] }));
+
+        assert_eq!(
+            wallet.update_config(
+                OdysseyWalletConfig::default().with_whitelist_registry(registry, Duration::ZERO)
+            ),
+            Err(ConfigError::ZeroRegistryRefreshInterval)
+        );
+    }
+
+    #[test]
+    fn lists_recent_transactions() {
+        let wallet = OdysseyWallet::new(
+            (),
+            EthereumWallet::default(),
+            (),
+            1,
+            DelegationCapability::default(),
+        );
+        let account = Address::with_last_byte(1);
+        assert!(wallet.recent_transactions(account, 10).is_empty());
+
+        let hashes: Vec<_> = (1..=3).map(B256::with_last_byte).collect();
+        for hash in &hashes {
+            wallet.inner.recent_transactions.record([account], *hash);
+        }
+        assert_eq!(
+            wallet.recent_transactions(account, 10),
+            hashes.iter().rev().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(wallet.recent_transactions(account, 1), vec![hashes[2]]);
+    }
+
+    #[test]
+    fn rejected_transactions_keep_destination_quotas() {
+        let claim = Address::with_last_byte(1);
+        let budget =
+            SponsorshipBudgetConfig { capacity: U256::from(1_000), refill_per_second: U256::ZERO };
+        let config = OdysseyWalletConfig::default()
+            .with_destination_quota(claim, 1)
+            .with_sponsorship_budget(budget);
+        let wallet =
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config).unwrap();
+        let active = wallet.active_config();
+        let destination = ResolvedDestination {
+            to: Some(Address::with_last_byte(2)),
+            delegates: vec![claim],
+            delegations: Vec::new(),
+            kind: SponsoredTxKind::DelegatedCall,
+            proxied: false,
+        };
+
+        // the budget rejects the transaction
+        assert_eq!(
+            wallet.charge(&active, &destination, U256::from(1_001)).err(),
+            Some(OdysseyWalletError::BudgetExhausted)
+        );
+        assert_eq!(wallet.remaining_destination_quota(claim), Some(1));
+
+        // the submission fails
+        drop(wallet.charge(&active, &destination, U256::from(1)).unwrap());
+        assert_eq!(wallet.remaining_destination_quota(claim), Some(1));
+
+        // the transaction is submitted
+        wallet.charge(&active, &destination, U256::from(1)).unwrap().commit();
+        assert_eq!(wallet.remaining_destination_quota(claim), Some(0));
+    }
+
+    #[test]
+    fn charges_value_against_budget() {
+        let budget =
+            SponsorshipBudgetConfig { capacity: U256::from(1_000), refill_per_second: U256::ZERO };
+        let config = OdysseyWalletConfig::default().with_sponsorship_budget(budget);
+        let wallet =
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config).unwrap();
+        let active = wallet.active_config();
+        let destination = ResolvedDestination {
+            to: Some(Address::with_last_byte(2)),
+            delegates: Vec::new(),
+            delegations: Vec::new(),
+            kind: SponsoredTxKind::DelegatedCall,
+            proxied: false,
+        };
+        let request = TransactionRequest::default().gas_limit(100).max_fee_per_gas(2);
+        assert_eq!(max_cost(&request), U256::from(200));
+
+        // the value alone exceeds the budget
+        let request = request.value(U256::from(1_001));
+        assert_eq!(max_cost(&request), U256::from(1_201));
+        assert_eq!(
+            wallet.charge(&active, &destination, max_cost(&request)).err(),
+            Some(OdysseyWalletError::BudgetExhausted)
+        );
+        assert_eq!(wallet.snapshot_state().budget, Some(U256::from(1_000)));
+    }
+
+    #[test]
+    fn unsubmitted_transactions_are_refunded() {
+        let budget =
+            SponsorshipBudgetConfig { capacity: U256::from(1_000), refill_per_second: U256::ZERO };
+        let config = OdysseyWalletConfig::default()
+            .with_sponsorship_budget(budget)
+            .with_spending_cap(U256::from(500), Duration::from_secs(3600));
+        let wallet =
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config).unwrap();
+        let active = wallet.active_config();
+        let destination = ResolvedDestination {
+            to: Some(Address::with_last_byte(2)),
+            delegates: Vec::new(),
+            delegations: Vec::new(),
+            kind: SponsoredTxKind::DelegatedCall,
+            proxied: false,
+        };
+
+        // the submission fails
+        drop(wallet.charge(&active, &destination, U256::from(400)).unwrap());
+        assert_eq!(wallet.snapshot_state().budget, Some(U256::from(1_000)));
+        assert_eq!(wallet.spending_cap().unwrap().remaining, U256::from(500));
+
+        // the transaction is submitted
+        wallet.charge(&active, &destination, U256::from(400)).unwrap().commit();
+        assert_eq!(wallet.snapshot_state().budget, Some(U256::from(600)));
+        assert_eq!(wallet.spending_cap().unwrap().remaining, U256::from(100));
+    }
+
+    #[test]
+    fn unsubmitted_delegations_are_not_pending() {
+        let config = OdysseyWalletConfig::default().with_pending_delegation_tracking(
+            Duration::from_secs(60),
+            16,
+            ConflictingDelegations::Reject,
+        );
+        let wallet =
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config).unwrap();
+        let active = wallet.active_config();
+        let authority = Address::with_last_byte(0xa);
+        let delegation = |target: u8| ResolvedDestination {
+            to: None,
+            delegates: vec![Address::with_last_byte(target)],
+            delegations: vec![(authority, Address::with_last_byte(target))],
+            kind: SponsoredTxKind::DelegationSetup,
+            proxied: false,
+        };
+
+        // the submission of the first delegation fails, so the retry to another target is
+        // sponsored
+        drop(wallet.charge(&active, &delegation(1), U256::from(1)).unwrap());
+        wallet.charge(&active, &delegation(2), U256::from(1)).unwrap().commit();
+
+        // while a submitted delegation is pending, a conflicting one is rejected
+        assert_eq!(
+            wallet.charge(&active, &delegation(3), U256::from(1)).err(),
+            Some(OdysseyWalletError::ConflictingPendingDelegation {
+                authority,
+                pending: Address::with_last_byte(2)
+            })
+        );
+    }
+
+    #[test]
+    fn tracks_destination_quotas() {
+        let claim = Address::with_last_byte(1);
+        let config = OdysseyWalletConfig::default().with_destination_quota(claim, 1);
+        let wallet =
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config).unwrap();
+        assert_eq!(wallet.remaining_destination_quota(claim), Some(1));
+        assert_eq!(wallet.remaining_destination_quota(Address::ZERO), None);
+
+        let active = wallet.active_config();
+        consume_destination_quotas(&active, &[claim]).unwrap().unwrap().commit();
+        assert_eq!(
+            consume_destination_quotas(&active, &[claim]).err(),
+            Some(OdysseyWalletError::DestinationQuotaExceeded { target: claim })
+        );
+        assert_eq!(wallet.remaining_destination_quota(claim), Some(0));
+
+        // the quotas survive unrelated reconfigurations
+        wallet
+            .update_config(
+                OdysseyWalletConfig::default()
+                    .with_destination_quota(claim, 1)
+                    .with_max_tx_bytes(1_000),
+            )
+            .unwrap();
+        assert_eq!(wallet.remaining_destination_quota(claim), Some(0));
+    }
+
+    #[test]
+    fn blocks_sponsorship_once_spending_cap_reached() {
+        let config = OdysseyWalletConfig::default()
+            .with_spending_cap(U256::from(1_000), Duration::from_secs(86_400))
+            .with_sponsorship_budget(SponsorshipBudgetConfig {
+                capacity: U256::from(700),
+                refill_per_second: U256::ZERO,
+            });
+        let wallet =
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config).unwrap();
+        let active = wallet.active_config();
+
+        assert_eq!(wallet.consume_budget(&active, U256::from(600)), Ok(()));
+        // rejected by the budget, so the cost does not count towards the cap
+        assert_eq!(
+            wallet.consume_budget(&active, U256::from(300)),
+            Err(OdysseyWalletError::BudgetExhausted)
+        );
+        assert_eq!(
+            wallet.spending_cap(),
+            Some(SpendingCapStatus { limit: U256::from(1_000), remaining: U256::from(400) })
+        );
+
+        // the cap is reached, so even a refilled budget does not help until the window ends
+        active.budget.as_ref().unwrap().restore(U256::from(700), Instant::now());
+        assert_eq!(wallet.consume_budget(&active, U256::from(100)), Ok(()));
+        assert_eq!(
+            wallet.consume_budget(&active, U256::from(400)),
+            Err(OdysseyWalletError::BudgetExhausted)
+        );
+        assert_eq!(wallet.spending_cap().unwrap().remaining, U256::from(300));
+
+        let wallet =
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, Default::default())
+                .unwrap();
+        assert_eq!(wallet.spending_cap(), None);
+
+        // an empty window is rejected up front
+        let config =
+            OdysseyWalletConfig::default().with_spending_cap(U256::from(1_000), Duration::ZERO);
+        assert_eq!(
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config).err(),
+            Some(ConfigError::ZeroSpendingCapWindow)
+        );
+    }
+
+    #[test]
+    fn pushes_budget_alerts() {
+        let alerts = BudgetAlerts::new(vec![BalanceThreshold::warn(U256::from(500))]);
+        let mut receiver = alerts.subscribe();
+        let config = OdysseyWalletConfig::default()
+            .with_sponsorship_budget(SponsorshipBudgetConfig {
+                capacity: U256::from(1_000),
+                refill_per_second: U256::ZERO,
+            })
+            .with_budget_alerts(alerts);
+        let wallet =
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config).unwrap();
+        let active = wallet.active_config();
+
+        assert_eq!(wallet.consume_budget(&active, U256::from(400)), Ok(()));
+        assert_eq!(wallet.consume_budget(&active, U256::from(200)), Ok(()));
+        let depletion = receiver.try_recv().unwrap();
+        assert_eq!(depletion.source, BudgetAlertSource::SponsorshipBudget);
+        assert_eq!((depletion.remaining, depletion.recovered), (U256::from(400), false));
+
+        // the budget is topped up, which is noticed on the next request
+        active.budget.as_ref().unwrap().restore(U256::from(1_000), Instant::now());
+        assert_eq!(wallet.consume_budget(&active, U256::from(100)), Ok(()));
+        let recovery = receiver.try_recv().unwrap();
+        assert_eq!((recovery.remaining, recovery.recovered), (U256::from(900), true));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn restores_state_after_restart() {
+        let claim = Address::with_last_byte(1);
+        let path = std::env::temp_dir().join(format!("odyssey-wallet-{}.json", std::process::id()));
+        let budget =
+            SponsorshipBudgetConfig { capacity: U256::from(1_000), refill_per_second: U256::ZERO };
+        let config = OdysseyWalletConfig::default()
+            .with_destination_quota(claim, 2)
+            .with_sponsorship_budget(budget)
+            .with_spending_cap(U256::from(500), Duration::from_secs(3600))
+            .with_account_rate_limit(RateLimitConfig { max_requests: 1, ..Default::default() })
+            .with_state_store(Arc::new(FileStateStore::new(&path)));
+        let alice = Address::with_last_byte(0xa);
+        let call = ResolvedDestination {
+            to: Some(alice),
+            delegates: vec![claim],
+            delegations: Vec::new(),
+            kind: SponsoredTxKind::DelegatedCall,
+            proxied: false,
+        };
+
+        let wallet =
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config.clone())
+                .unwrap();
+        let active = wallet.active_config();
+        consume_destination_quotas(&active, &[claim]).unwrap().unwrap().commit();
+        assert!(active.budget.as_ref().unwrap().try_consume(U256::from(600), Instant::now()));
+        assert!(active.spending_cap.as_ref().unwrap().try_spend(U256::from(200), Instant::now()));
+        assert_eq!(wallet.check_account_rate_limit(&active, &call), Ok(()));
+        wallet.persist_state().unwrap();
+
+        // a new wallet restores the state from the store
+        let wallet =
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config).unwrap();
+        assert_eq!(wallet.remaining_destination_quota(claim), Some(1));
+        let active = wallet.active_config();
+        assert_eq!(active.budget.as_ref().unwrap().level(Instant::now()), U256::from(400));
+        assert_eq!(wallet.spending_cap().unwrap().remaining, U256::from(300));
+        assert!(matches!(
+            wallet.check_account_rate_limit(&active, &call),
+            Err(OdysseyWalletError::RateLimited { escalation: 1, .. })
+        ));
+
+        // without a store, the state resets
+        let config = OdysseyWalletConfig::default().with_destination_quota(claim, 2);
+        let wallet =
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config).unwrap();
+        assert_eq!(wallet.remaining_destination_quota(claim), Some(2));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn saturates_global_rate_limit() {
+        let config =
+            OdysseyWalletConfig::default().with_global_rate_limit(Some(GlobalRateLimitConfig {
+                requests_per_second: 1,
+                burst: 3,
+            }));
+        let wallet =
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config).unwrap();
+        let active = wallet.active_config();
+
+        for _ in 0..3 {
+            assert_eq!(wallet.check_global_rate_limit(&active), Ok(()));
+        }
+        assert_eq!(
+            wallet.check_global_rate_limit(&active),
+            Err(OdysseyWalletError::GlobalRateLimited)
+        );
+
+        // the limit can be disabled, but not set to admit nothing
+        assert_eq!(
+            wallet.update_config(OdysseyWalletConfig::default().with_global_rate_limit(Some(
+                GlobalRateLimitConfig { requests_per_second: 0, burst: 3 }
+            ))),
+            Err(ConfigError::EmptyGlobalRateLimit)
+        );
+        wallet.update_config(OdysseyWalletConfig::default().with_global_rate_limit(None)).unwrap();
+        assert_eq!(wallet.check_global_rate_limit(&wallet.active_config()), Ok(()));
+    }
+
+    #[test]
+    fn saturates_signer_nonce_rate() {
+        let config = OdysseyWalletConfig::default().with_max_signer_nonces_per_minute(2);
+        let wallet =
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config).unwrap();
+        let active = wallet.active_config();
+        let signer = wallet.signer_address();
+
+        assert_eq!(wallet.check_signer_nonce_rate(&active, signer), Ok(()));
+        assert_eq!(wallet.check_signer_nonce_rate(&active, signer), Ok(()));
+        assert_eq!(
+            wallet.check_signer_nonce_rate(&active, signer),
+            Err(OdysseyWalletError::SignerNonceRateLimited)
+        );
+
+        // disabled by default
+        let wallet = OdysseyWallet::with_config(
+            (),
+            EthereumWallet::default(),
+            (),
+            1,
+            OdysseyWalletConfig::default(),
+        )
+        .unwrap();
+        let active = wallet.active_config();
+        for _ in 0..100 {
+            assert_eq!(wallet.check_signer_nonce_rate(&active, signer), Ok(()));
+        }
+    }
+
+    #[test]
+    fn rate_limits_accounts() {
+        let config = OdysseyWalletConfig::default()
+            .with_account_rate_limit(RateLimitConfig { max_requests: 2, ..Default::default() });
+        let wallet =
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config).unwrap();
+        let active = wallet.active_config();
+        let (alice, bob) = (Address::with_last_byte(0xa), Address::with_last_byte(0xb));
+        let call = |to| ResolvedDestination {
+            to: Some(to),
+            delegates: vec![Address::with_last_byte(1)],
+            delegations: Vec::new(),
+            kind: SponsoredTxKind::DelegatedCall,
+            proxied: false,
+        };
+
+        assert_eq!(wallet.check_account_rate_limit(&active, &call(alice)), Ok(()));
+        assert_eq!(wallet.check_account_rate_limit(&active, &call(alice)), Ok(()));
+        assert_eq!(
+            wallet.check_account_rate_limit(&active, &call(alice)),
+            Err(OdysseyWalletError::RateLimited { retry_after: 60, escalation: 1 })
+        );
+        // other accounts are limited separately
+        assert_eq!(wallet.check_account_rate_limit(&active, &call(bob)), Ok(()));
+
+        // eip-7702 transactions count against each authority
+        let delegation = ResolvedDestination {
+            to: None,
+            delegates: vec![Address::with_last_byte(1)],
+            delegations: vec![(bob, Address::with_last_byte(1))],
+            kind: SponsoredTxKind::DelegationSetup,
+            proxied: false,
+        };
+        assert_eq!(wallet.check_account_rate_limit(&active, &delegation), Ok(()));
+        assert!(matches!(
+            wallet.check_account_rate_limit(&active, &call(bob)),
+            Err(OdysseyWalletError::RateLimited { .. })
+        ));
+    }
+
+    #[test]
+    fn advises_backoff_when_rate_limited() {
+        let config = OdysseyWalletConfig::default().with_connection_rate_limit(RateLimitConfig {
+            max_requests: 1,
+            backoff: Some(RateLimitBackoff::default()),
+            ..Default::default()
+        });
+        let wallet =
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config).unwrap();
+        let active = wallet.active_config();
+        let connection = Some(ConnectionId(1));
+
+        assert_eq!(wallet.check_connection_rate_limit(&active, connection), Ok(()));
+        assert_eq!(
+            wallet.check_connection_rate_limit(&active, connection),
+            Err(OdysseyWalletError::RateLimited { retry_after: 60, escalation: 1 })
+        );
+        let err = wallet.check_connection_rate_limit(&active, connection).unwrap_err();
+        assert!(matches!(err, OdysseyWalletError::RateLimited { escalation: 2, .. }));
+
+        // the advice is returned as the error data
+        let err = ErrorObject::from(err);
+        let data: serde_json::Value = serde_json::from_str(err.data().unwrap().get()).unwrap();
+        assert_eq!(data["escalation"], 2);
+        assert!(data["retryAfter"].as_u64().unwrap() >= 59);
+    }
+
+    #[test]
+    fn lists_active_rules() {
+        let wallet = OdysseyWallet::with_config(
+            (),
+            EthereumWallet::default(),
+            (),
+            1,
+            OdysseyWalletConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            wallet.active_rules(),
+            [
+                "value=zero",
+                "gas_ceiling=350000",
+                "gas_buffer=10%",
+                "max_calldata_bytes=131072",
+                "system_addresses",
+                "global_rate_limit=100/s (burst 200)"
+            ]
+        );
+
+        let config = OdysseyWalletConfig::default()
+            .with_connection_rate_limit(RateLimitConfig::default())
+            .with_max_tx_bytes(4_096)
+            .with_reject_universal_authorizations()
+            .with_methods(MethodFlags::all().with_method(WalletMethod::EstimateSponsorship, false));
+        wallet.update_config(config).unwrap();
+        let rules = wallet.active_rules();
+        for rule in [
+            "gas_ceiling=350000",
+            "connection_rate_limit=10/60s",
+            "max_tx_bytes=4096",
+            "reject_universal_authorizations",
+            "disabled_method=wallet_estimateSponsorship",
+        ] {
+            assert!(rules.iter().any(|active| active == rule), "missing {rule}");
+        }
+        assert!(!rules.iter().any(|rule| rule.starts_with("sponsorship_budget")));
+    }
+
+    #[test]
+    fn configurable_gas_ceiling() {
+        let request = TransactionRequest::default().to(Address::with_last_byte(1));
+        let wallet = OdysseyWallet::with_config(
+            (),
+            EthereumWallet::default(),
+            (),
+            1,
+            OdysseyWalletConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(wallet.gas_ceiling(&wallet.active_config(), &request), DEFAULT_GAS_CEILING);
+
+        wallet.update_config(OdysseyWalletConfig::default().with_gas_ceiling(1_000_000)).unwrap();
+        assert_eq!(wallet.gas_ceiling(&wallet.active_config(), &request), 1_000_000);
+        assert!(wallet.active_rules().contains(&"gas_ceiling=1000000".to_string()));
+
+        assert_eq!(
+            OdysseyWalletError::GasEstimateTooHigh { estimate: 1_200_000, limit: 1_000_000 }
+                .to_string(),
+            "request would use too much gas: estimated 1200000, limit is 1000000"
+        );
+    }
+
+    #[test]
+    fn capabilities_include_version() {
+        let capabilities =
+            serde_json::to_value(WalletCapabilities::new(911867, DelegationCapability::default()))
+                .unwrap();
+        assert_eq!(capabilities["0xde9fb"]["version"], WALLET_PROTOCOL_VERSION);
+    }
+
+    #[test]
+    fn capabilities_include_whitelisted_delegations() {
+        let a = Address::with_last_byte(1);
+        let b = Address::with_last_byte(2);
+        let wallet = OdysseyWallet::new(
+            (),
+            EthereumWallet::default(),
+            (),
+            911867,
+            DelegationCapability { addresses: vec![a, b, a] },
+        );
+
+        let capabilities = serde_json::to_value(wallet.capabilities()).unwrap();
+        assert_eq!(
+            capabilities,
+            serde_json::json!({
+                "0xde9fb": {
+                    "version": WALLET_PROTOCOL_VERSION,
+                    "delegation": { "addresses": [a, b] },
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn calldata_limit() {
+        let request = |size| TransactionRequest::default().input(vec![0u8; size].into());
+
+        assert_eq!(validate_calldata_size(&request(100), 100), Ok(()));
+        assert_eq!(
+            validate_calldata_size(&request(101), 100),
+            Err(OdysseyWalletError::CalldataTooLarge { size: 101, max: 100 })
+        );
+        assert_eq!(validate_calldata_size(&TransactionRequest::default(), 0), Ok(()));
+        assert_eq!(
+            validate_calldata_size(
+                &request(DEFAULT_MAX_CALLDATA_BYTES),
+                DEFAULT_MAX_CALLDATA_BYTES
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn selector_lists() {
+        let execute = [0xe9, 0xae, 0x5c, 0x53];
+        let transfer = [0xa9, 0x05, 0x9c, 0xbb];
+        let request = |input: &[u8]| TransactionRequest::default().input(input.to_vec().into());
+        let rejected = |selector: &[u8]| {
+            Err(OdysseyWalletError::SelectorNotAllowed { selector: selector.to_vec().into() })
+        };
+
+        let unrestricted = OdysseyWalletConfig::default();
+        assert_eq!(validate_selector(&request(&transfer), &unrestricted), Ok(()));
+        assert_eq!(validate_selector(&request(&[]), &unrestricted), Ok(()));
+
+        let allowlist = OdysseyWalletConfig::default().with_allowed_selectors([execute]);
+        assert_eq!(
+            validate_selector(&request(&[execute.as_slice(), &[0; 32]].concat()), &allowlist),
+            Ok(())
+        );
+        assert_eq!(validate_selector(&request(&transfer), &allowlist), rejected(&transfer));
+        // calldata shorter than a selector does not match any listed selector
+        assert_eq!(validate_selector(&request(&execute[..3]), &allowlist), rejected(&execute[..3]));
+        assert_eq!(validate_selector(&TransactionRequest::default(), &allowlist), rejected(&[]));
+
+        let blocklist = OdysseyWalletConfig::default().with_blocked_selectors([transfer]);
+        assert_eq!(validate_selector(&request(&execute), &blocklist), Ok(()));
+        assert_eq!(validate_selector(&request(&transfer), &blocklist), rejected(&transfer));
+        assert_eq!(validate_selector(&request(&transfer[..2]), &blocklist), Ok(()));
+
+        // blocking takes precedence
+        let both = allowlist.with_blocked_selectors([execute]);
+        assert_eq!(validate_selector(&request(&execute), &both), rejected(&execute));
+    }
+
+    #[test]
+    fn batch_calldata_limit() {
+        let calls = [[0u8; 40].as_slice(), &[0; 30], &[0; 30]];
+
+        assert_eq!(validate_batch_calldata(calls, Some(100)), Ok(()));
+        assert_eq!(
+            validate_batch_calldata(calls, Some(99)),
+            Err(OdysseyWalletError::BatchCalldataTooLarge { size: 100, max: 99 })
+        );
+        assert_eq!(validate_batch_calldata(calls, None), Ok(()));
+    }
+
+    #[test]
+    fn batch_gas_limit() {
+        assert_eq!(validate_batch_gas(&[400_000, 599_999], 1_000_000), Ok(()));
+        assert_eq!(
+            validate_batch_gas(&[400_000, 600_000], 1_000_000),
+            Err(OdysseyWalletError::GasEstimateTooHigh { estimate: 1_000_000, limit: 1_000_000 })
+        );
+        assert_eq!(
+            validate_batch_gas(&[u64::MAX, 1], 1_000_000),
+            Err(OdysseyWalletError::GasEstimateTooHigh { estimate: u64::MAX, limit: 1_000_000 })
+        );
+    }
+
+    #[test]
+    fn resolves_gas_and_base_fee() {
+        fn failed<T>() -> Result<T, OdysseyWalletError> {
+            Err(OdysseyWalletError::InternalError)
+        }
+        let estimate = || Ok::<_, OdysseyWalletError>(U256::from(21_000));
+        let fees = || Ok::<_, OdysseyWalletError>((U256::from(7), U256::from(1)));
+
+        // both succeed
+        assert_eq!(resolve_gas_and_base_fee(estimate(), fees(), None), Ok((U256::from(21_000), 7)));
+
+        // the estimate fails
+        let err = ErrorObject::from(OdysseyWalletError::InternalError);
+        assert_eq!(resolve_gas_and_base_fee(failed(), fees(), Some(3)), Err(err.clone()));
+
+        // the fee lookup fails
+        assert_eq!(
+            resolve_gas_and_base_fee(estimate(), failed(), None),
+            Err(OdysseyWalletError::InvalidTransactionRequest.into())
+        );
+        assert_eq!(
+            resolve_gas_and_base_fee(estimate(), failed(), Some(3)),
+            Ok((U256::from(21_000), 3))
+        );
+
+        // both fail
+        assert_eq!(resolve_gas_and_base_fee(failed(), failed(), Some(3)), Err(err));
+    }
+
+    #[test]
+    fn configured_priority_fee_is_signed() {
+        let fees: Result<_, ()> = Ok((U256::from(7), U256::from(3_000_000_000u64)));
+        let priority_fee = |config: OdysseyWalletConfig| {
+            let wallet =
+                OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config).unwrap();
+            wallet.active_config().config.priority_fee.resolve(suggested_priority_fee(&fees))
+        };
+
+        assert_eq!(priority_fee(OdysseyWalletConfig::default()), DEFAULT_PRIORITY_FEE);
+        let fixed = OdysseyWalletConfig::default().with_priority_fee(PriorityFee::Fixed(2));
+        assert_eq!(priority_fee(fixed.clone()), 2);
+        assert!(fixed.active_rules().contains(&"priority_fee=2 wei".to_string()));
+        // the suggestion of the node is clamped
+        let dynamic = |min, max| {
+            OdysseyWalletConfig::default().with_priority_fee(PriorityFee::Dynamic { min, max })
+        };
+        assert_eq!(priority_fee(dynamic(1, 2_000_000_000)), 2_000_000_000);
+        assert_eq!(priority_fee(dynamic(1, 5_000_000_000)), 3_000_000_000);
+        assert_eq!(
+            dynamic(2, 1).validate(),
+            Err(ConfigError::PriorityFeeMinAboveMax { min: 2, max: 1 })
+        );
+
+        // the fee ends up in the transaction that is signed
+        let mut request =
+            TransactionRequest::default().to(Address::with_last_byte(1)).nonce(0).gas_limit(21_000);
+        request.chain_id = Some(1);
+        set_fees(&mut request, 7 + 2, 2);
+        let tx = request.build_typed_tx().unwrap();
+        assert_eq!(tx.max_priority_fee_per_gas(), Some(2));
+        assert_eq!(tx.max_fee_per_gas(), 9);
+    }
+
+    #[test]
+    fn access_list_is_signed() {
+        let access_list: AccessList = vec![AccessListItem {
+            address: Address::with_last_byte(1),
+            storage_keys: vec![B256::with_last_byte(1)],
+        }]
+        .into();
+        let mut request = TransactionRequest::default()
+            .to(Address::with_last_byte(1))
+            .access_list(access_list.clone());
+        assert_eq!(validate_tx_request(&request), Ok(()));
+
+        // the fields overwritten by `wallet_sendTransaction` keep the access list
+        request.from = Some(Address::with_last_byte(2));
+        request.nonce = Some(0);
+        request.gas = Some(30_000);
+        request.chain_id = Some(1);
+        set_fees(&mut request, 9, 2);
+        let tx = request.build_typed_tx().unwrap();
+        assert_eq!(tx.access_list(), Some(&access_list));
+    }
+
+    #[test]
+    fn rejects_anomalous_gas_profiles() {
+        let band = RangeInclusive::new(16, 2_000);
+
+        // a regular call
+        assert_eq!(validate_gas_profile(60_000, &[0; 100], Some(&band)), Ok(()));
+
+        // calldata-heavy, e.g. data availability abuse
+        assert_eq!(
+            validate_gas_profile(60_000, &[0; 10_000], Some(&band)),
+            Err(OdysseyWalletError::AnomalousGasProfile { gas_per_byte: 6 })
+        );
+
+        // execution-heavy, e.g. an execution bomb
+        assert_eq!(
+            validate_gas_profile(300_000, &[0; 4], Some(&band)),
+            Err(OdysseyWalletError::AnomalousGasProfile { gas_per_byte: 75_000 })
+        );
+
+        // without calldata, or if disabled, nothing is checked
+        assert_eq!(validate_gas_profile(300_000, &[], Some(&band)), Ok(()));
+        assert_eq!(validate_gas_profile(300_000, &[0; 4], None), Ok(()));
+    }
+
+    #[test]
+    fn honors_lower_gas_limit() {
+        // a lower limit is honored
+        assert_eq!(resolve_gas_limit(Some(30_000), 50_000, true), Ok(30_000));
+        assert_eq!(resolve_gas_limit(Some(50_000), 50_000, true), Ok(50_000));
+
+        // a higher limit is rejected
+        assert_eq!(
+            resolve_gas_limit(Some(50_001), 50_000, true),
+            Err(OdysseyWalletError::GasLimitAboveEstimate { gas_limit: 50_001, estimate: 50_000 })
+        );
+
+        // without a limit, or if disabled, the estimate is used
+        assert_eq!(resolve_gas_limit(None, 50_000, true), Ok(50_000));
+        assert_eq!(resolve_gas_limit(Some(30_000), 50_000, false), Ok(50_000));
+        assert_eq!(resolve_gas_limit(Some(50_001), 50_000, false), Ok(50_000));
+    }
+
+    #[test]
+    fn oversized_transaction_rejected() {
+        // e.g. an eip-7702 transaction carrying many authorizations
+        let tx = vec![0u8; 1_001];
+
+        assert_eq!(
+            validate_tx_size(&tx, Some(1_000)),
+            Err(OdysseyWalletError::TransactionTooLarge { size: 1_001, max: 1_000 })
+        );
+        assert_eq!(validate_tx_size(&tx[..1_000], Some(1_000)), Ok(()));
+        assert_eq!(validate_tx_size(&tx, None), Ok(()));
+    }
+
+    #[test]
+    fn oversized_authorization_list_rejected() {
+        let authorization = |nonce, signature| {
+            SignedAuthorization::new_unchecked(
+                Authorization { chain_id: 1, address: Address::ZERO, nonce },
+                0,
+                signature,
+                signature,
+            )
+        };
+        let request = |authorizations| TransactionRequest {
+            authorization_list: Some(authorizations),
+            ..Default::default()
+        };
+        // many small authorizations are within the limit
+        let small = request(vec![authorization(0, U256::from(1)); 3]);
+        let max = alloy_rlp::list_length(small.authorization_list.as_ref().unwrap());
+        assert_eq!(validate_authorization_list_size(&small, Some(max)), Ok(()));
+
+        // fewer, but larger authorizations are not
+        let large = request(vec![authorization(u64::MAX, U256::MAX); 2]);
+        let size = alloy_rlp::list_length(large.authorization_list.as_ref().unwrap());
+        assert!(size > max);
+        assert_eq!(
+            validate_authorization_list_size(&large, Some(max)),
+            Err(OdysseyWalletError::AuthorizationListTooLarge { size, max })
+        );
+        assert_eq!(validate_authorization_list_size(&large, None), Ok(()));
+    }
+
+    #[test]
+    fn authorization_list_rejected_without_7702() {
+        let request = TransactionRequest { authorization_list: Some(vec![]), ..Default::default() };
+
+        assert_eq!(
+            ensure_7702_supported(&request, false),
+            Err(OdysseyWalletError::Eip7702NotSupported)
+        );
+        assert_eq!(ensure_7702_supported(&request, true), Ok(()));
+        assert_eq!(ensure_7702_supported(&TransactionRequest::default(), false), Ok(()));
+    }
+
+    #[test]
+    fn revocations_exempt_if_allowed() {
+        let target = address!("3C44CdDdB6a900fa2b585dd299e03d12FA4293BC");
+        let authorization = |address| {
+            SignedAuthorization::new_unchecked(
+                Authorization { chain_id: 1, address, nonce: 0 },
+                0,
+                U256::from(1),
+                U256::from(1),
+            )
+        };
+        let authorizations = [authorization(target), authorization(Address::ZERO)];
+        let whitelist = DelegationWhitelist::new(&DelegationCapability { addresses: vec![target] });
 
-        // set chain id
-        request.chain_id = Some(self.chain_id());
+        // a revocation is checked like any other target by default
+        let delegates = authorization_delegates(&authorizations, false);
+        assert_eq!(delegates, [target, Address::ZERO]);
+        assert_eq!(
+            validate_whitelisted(&delegates, Some(&whitelist)),
+            Err(OdysseyWalletError::IllegalDestination)
+        );
 
-        // set gas limit
-        // note: we also set the `from` field here to correctly estimate for contracts that use e.g.
-        // `tx.origin`
-        request.from = Some(NetworkWallet::<Ethereum>::default_signer_address(&self.inner.wallet));
-        let (estimate, base_fee) = tokio::join!(
-            EthCall::estimate_gas_at(&self.inner.eth_api, request.clone(), BlockId::latest(), None),
-            LoadFee::eip1559_fees(&self.inner.eth_api, None, None)
+        let delegates = authorization_delegates(&authorizations, true);
+        assert_eq!(delegates, [target]);
+        assert_eq!(validate_whitelisted(&delegates, Some(&whitelist)), Ok(()));
+        assert_eq!(validate_not_system_address(delegates, &SystemAddresses::default()), Ok(()));
+    }
+
+    #[test]
+    fn universal_authorizations_rejected() {
+        let authorization = |chain_id| {
+            SignedAuthorization::new_unchecked(
+                Authorization { chain_id, address: Address::ZERO, nonce: 0 },
+                0,
+                U256::from(1),
+                U256::from(1),
+            )
+        };
+        let universal = [authorization(1), authorization(0)];
+        let chain_specific = [authorization(1), authorization(1)];
+
+        assert_eq!(
+            validate_authorization_chain_ids(&universal, 1, true),
+            Err(OdysseyWalletError::UniversalAuthorizationNotAllowed)
         );
-        let estimate = estimate.map_err(|err| {
-            self.inner.metrics.invalid_send_transaction_calls.increment(1);
-            err.into()
-        })?;
+        assert_eq!(validate_authorization_chain_ids(&chain_specific, 1, true), Ok(()));
+        assert_eq!(validate_authorization_chain_ids(&universal, 1, false), Ok(()));
+        assert_eq!(validate_authorization_chain_ids(&chain_specific, 1, false), Ok(()));
 
-        if estimate >= U256::from(350_000) {
-            self.inner.metrics.invalid_send_transaction_calls.increment(1);
-            return Err(OdysseyWalletError::GasEstimateTooHigh { estimate: estimate.to() }.into());
-        }
-        request.gas = Some(estimate.to());
+        // authorizations for other chains are rejected
+        assert_eq!(
+            validate_authorization_chain_ids(&[authorization(1), authorization(911867)], 1, false),
+            Err(OdysseyWalletError::AuthorizationChainIdMismatch { chain_id: 911867 })
+        );
+        // as are empty authorization lists
+        assert_eq!(
+            validate_authorization_chain_ids(&[], 1, false),
+            Err(OdysseyWalletError::EmptyAuthorizationList)
+        );
+    }
 
-        // set gas price
-        let (base_fee, _) = base_fee.map_err(|_| {
-            self.inner.metrics.invalid_send_transaction_calls.increment(1);
-            OdysseyWalletError::InvalidTransactionRequest
-        })?;
-        let max_priority_fee_per_gas = 1_000_000_000; // 1 gwei
-        request.max_fee_per_gas = Some(base_fee.to::<u128>() + max_priority_fee_per_gas);
-        request.max_priority_fee_per_gas = Some(max_priority_fee_per_gas);
-        request.gas_price = None;
+    #[test]
+    fn mismatching_chain_id_rejected() {
+        let request = |chain_id| TransactionRequest { chain_id, ..Default::default() };
 
-        // build and sign
-        let envelope =
-            <TransactionRequest as TransactionBuilder<Ethereum>>::build::<EthereumWallet>(
-                request,
-                &self.inner.wallet,
-            )
-            .await
-            .map_err(|_| {
-                self.inner.metrics.invalid_send_transaction_calls.increment(1);
-                OdysseyWalletError::InvalidTransactionRequest
-            })?;
+        assert_eq!(validate_chain_id(&request(Some(911867)), 911867), Ok(()));
+        assert_eq!(
+            validate_chain_id(&request(Some(1)), 911867),
+            Err(OdysseyWalletError::ChainIdMismatch { chain_id: 1 })
+        );
+        // absent chain ids are filled in on submission
+        assert_eq!(validate_chain_id(&request(None), 911867), Ok(()));
+    }
 
-        // all checks passed, increment the valid calls counter
-        self.inner.metrics.valid_send_transaction_calls.increment(1);
+    #[test]
+    fn not_synced_rejected() {
+        assert_eq!(ensure_synced(true, true), Err(OdysseyWalletError::NodeNotSynced));
+        assert_eq!(ensure_synced(true, false), Ok(()));
+        assert_eq!(ensure_synced(false, true), Ok(()));
+    }
 
-        // this uses the internal `OpEthApi` to either forward the tx to the sequencer, or add it to
-        // the txpool
-        //
-        // see: https://github.com/paradigmxyz/reth/blob/b67f004fbe8e1b7c05f84f314c4c9f2ed9be1891/crates/optimism/rpc/src/eth/transaction.rs#L35-L57
-        EthTransactions::send_raw_transaction(&self.inner.eth_api, envelope.encoded_2718().into())
-            .await
-            .inspect_err(|err| warn!(target: "rpc::wallet", ?err, "Error adding sequencer-sponsored tx to pool"))
-            .map_err(Into::into)
+    #[test]
+    fn non_whitelisted_delegate_rejected() {
+        let whitelisted = Address::with_last_byte(1);
+        let other = Address::with_last_byte(2);
+        let wallet = OdysseyWallet::new(
+            (),
+            EthereumWallet::default(),
+            (),
+            1,
+            DelegationCapability { addresses: vec![whitelisted] },
+        );
+        let whitelist = wallet.active_config().current_whitelist();
+        assert!(whitelist.is_some());
+
+        // an account delegated to a whitelisted address
+        assert_eq!(validate_whitelisted(&[whitelisted], whitelist.as_deref()), Ok(()));
+        // an account delegated to an address that is not whitelisted
+        assert_eq!(
+            validate_whitelisted(&[other], whitelist.as_deref()),
+            Err(OdysseyWalletError::IllegalDestination)
+        );
+        // authorizations must all be whitelisted
+        assert_eq!(
+            validate_whitelisted(&[whitelisted, other], whitelist.as_deref()),
+            Err(OdysseyWalletError::IllegalDestination)
+        );
+        // an empty whitelist sponsors nothing
+        let empty = DelegationWhitelist::new(&DelegationCapability::default());
+        assert_eq!(
+            validate_whitelisted(&[whitelisted], Some(&empty)),
+            Err(OdysseyWalletError::IllegalDestination)
+        );
+
+        // neither does a wallet without a whitelist
+        let wallet = OdysseyWallet::with_config(
+            (),
+            EthereumWallet::default(),
+            (),
+            1,
+            OdysseyWalletConfig::default(),
+        )
+        .unwrap();
+        let whitelist = wallet.active_config().current_whitelist();
+        assert_eq!(
+            validate_whitelisted(&[whitelisted], whitelist.as_deref()),
+            Err(OdysseyWalletError::IllegalDestination)
+        );
+        // revocations have no delegate to whitelist
+        assert_eq!(validate_whitelisted(&[], whitelist.as_deref()), Ok(()));
     }
-}
 
-/// Implementation of the Odyssey `wallet_` namespace.
-#[derive(Debug)]
-struct OdysseyWalletInner<Provider, Eth> {
-    provider: Provider,
-    eth_api: Eth,
-    wallet: EthereumWallet,
-    chain_id: ChainId,
-    /// Used to guard tx signing
-    permit: Mutex<()>,
-    /// Metrics for the `wallet_` RPC namespace.
-    metrics: WalletMetrics,
-}
+    #[test]
+    fn batch_execution_matches_send_calls() {
+        let call =
+            |to: u8| Call { to: Address::with_last_byte(to), data: Bytes::new(), value: None };
 
-fn validate_tx_request(request: &TransactionRequest) -> Result<(), OdysseyWalletError> {
-    // reject transactions that have a non-zero value to prevent draining the sequencer.
-    if request.value.is_some_and(|val| val > U256::ZERO) {
-        return Err(OdysseyWalletError::ValueNotZero);
+        // `wallet_sendCalls` submits a transaction per call, even to the same account
+        let batch = vec![call(1), call(1), call(1)];
+        let transactions = batch_requests(batch);
+        assert_eq!(transactions.len(), 3);
+        assert_eq!(BatchExecution::of(&transactions), BatchExecution::Sequential);
+
+        let transactions = batch_requests(vec![call(1)]);
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(BatchExecution::of(&transactions), BatchExecution::Atomic);
     }
 
-    // reject transactions that have from set, as this will be the sequencer.
-    if request.from.is_some() {
-        return Err(OdysseyWalletError::FromSet);
+    #[test]
+    fn parses_delegation_whitelist() {
+        let a = address!("70997970C51812dc3A010C7d01b50e0d17dc79C8");
+        let b = address!("3C44CdDdB6a900fa2b585dd299e03d12FA4293BC");
+        assert_eq!(
+            format!("{a}, {b},").parse(),
+            Ok(DelegationCapability { addresses: vec![a, b] })
+        );
+        assert_eq!("".parse(), Ok(DelegationCapability::default()));
+        assert!("0x1234".parse::<DelegationCapability>().is_err());
     }
 
-    // reject transaction requests that have nonce set, as this is managed by the sequencer.
-    if request.nonce.is_some() {
-        return Err(OdysseyWalletError::NonceSet);
+    #[test]
+    fn delegated_state_conflict_rejected() {
+        let account = Address::with_last_byte(1);
+
+        // the account has a transaction pending in the pool
+        assert_eq!(
+            detect_delegated_state_conflict(account, 3, 4),
+            Err(OdysseyWalletError::DelegatedStateConflict { account })
+        );
+        // nothing pending
+        assert_eq!(detect_delegated_state_conflict(account, 3, 3), Ok(()));
     }
 
-    Ok(())
-}
+    #[test]
+    fn sponsorship_not_started_rejected() {
+        assert_eq!(
+            ensure_sponsorship_started(99, 100),
+            Err(OdysseyWalletError::SponsorshipNotStarted { current: 99, start: 100 })
+        );
+        assert_eq!(ensure_sponsorship_started(100, 100), Ok(()));
+        assert_eq!(ensure_sponsorship_started(101, 100), Ok(()));
+    }
 
-/// Metrics for the `wallet_` RPC namespace.
-#[derive(Metrics)]
-#[metrics(scope = "wallet")]
-struct WalletMetrics {
-    /// Number of invalid calls to `odyssey_sendTransaction`
-    invalid_send_transaction_calls: Counter,
-    /// Number of valid calls to `odyssey_sendTransaction`
-    valid_send_transaction_calls: Counter,
-}
+    #[test]
+    fn low_sponsor_balance_rejected() {
+        let minimum = Some(U256::from(1_000));
+        assert_eq!(
+            ensure_sponsor_funded(U256::from(999), minimum),
+            Err(OdysseyWalletError::SponsorInsufficientFunds {
+                balance: U256::from(999),
+                minimum: U256::from(1_000)
+            })
+        );
+        assert_eq!(ensure_sponsor_funded(U256::from(1_000), minimum), Ok(()));
+        // without a minimum, even an empty sponsor is not rejected here
+        assert_eq!(ensure_sponsor_funded(U256::ZERO, None), Ok(()));
+    }
 
-#[cfg(test)]
-mod tests {
-    use crate::{validate_tx_request, OdysseyWalletError};
-    use alloy_primitives::{Address, U256};
-    use alloy_rpc_types::TransactionRequest;
     #[test]
     fn no_value_allowed() {
+        let delegate = Address::with_last_byte(1);
         assert_eq!(
-            validate_tx_request(&TransactionRequest::default().value(U256::from(1))),
+            validate_value(&TransactionRequest::default().value(U256::from(1)), &[delegate], None),
             Err(OdysseyWalletError::ValueNotZero)
         );
 
         assert_eq!(
-            validate_tx_request(&TransactionRequest::default().value(U256::from(0))),
+            validate_value(&TransactionRequest::default().value(U256::from(0)), &[delegate], None),
             Ok(())
         );
     }
 
+    #[test]
+    fn value_within_allowance_allowed() {
+        let listed = address!("70997970C51812dc3A010C7d01b50e0d17dc79C8");
+        let unlisted = address!("3C44CdDdB6a900fa2b585dd299e03d12FA4293BC");
+        let allowances = HashMap::from([(listed, U256::from(100))]);
+        // the value is sent to the delegated account, not to its delegate
+        let request = |value| {
+            TransactionRequest::default().to(Address::with_last_byte(0xa)).value(U256::from(value))
+        };
+
+        assert_eq!(validate_value(&request(100), &[listed], Some(&allowances)), Ok(()));
+        assert_eq!(
+            validate_value(&request(101), &[listed], Some(&allowances)),
+            Err(OdysseyWalletError::ValueNotZero)
+        );
+        assert_eq!(
+            validate_value(&request(1), &[unlisted], Some(&allowances)),
+            Err(OdysseyWalletError::ValueNotZero)
+        );
+        // every delegate must allow the value
+        assert_eq!(
+            validate_value(&request(1), &[listed, unlisted], Some(&allowances)),
+            Err(OdysseyWalletError::ValueNotZero)
+        );
+        // requests without delegates have no allowance
+        assert_eq!(
+            validate_value(&request(1), &[], Some(&allowances)),
+            Err(OdysseyWalletError::ValueNotZero)
+        );
+    }
+
     #[test]
     fn no_from_allowed() {
         assert_eq!(
@@ -359,4 +5123,375 @@ mod tests {
 
         assert_eq!(validate_tx_request(&TransactionRequest::default()), Ok(()));
     }
+
+    #[test]
+    fn only_eip1559_shapes_allowed() {
+        let access_list: AccessList = vec![AccessListItem::default()].into();
+
+        // legacy
+        assert_eq!(
+            validate_tx_request(&TransactionRequest { gas_price: Some(1), ..Default::default() }),
+            Err(OdysseyWalletError::UnsupportedTransactionType { tx_type: 0 })
+        );
+        assert_eq!(
+            validate_tx_request(&TransactionRequest::default().transaction_type(0)),
+            Err(OdysseyWalletError::UnsupportedTransactionType { tx_type: 0 })
+        );
+
+        // eip-2930
+        assert_eq!(
+            validate_tx_request(&TransactionRequest {
+                gas_price: Some(1),
+                access_list: Some(access_list.clone()),
+                ..Default::default()
+            }),
+            Err(OdysseyWalletError::UnsupportedTransactionType { tx_type: 1 })
+        );
+        assert_eq!(
+            validate_tx_request(&TransactionRequest::default().transaction_type(1)),
+            Err(OdysseyWalletError::UnsupportedTransactionType { tx_type: 1 })
+        );
+
+        // eip-1559 and eip-7702, with or without an access list
+        assert_eq!(
+            validate_tx_request(&TransactionRequest::default().access_list(access_list)),
+            Ok(())
+        );
+        assert_eq!(validate_tx_request(&TransactionRequest::default().transaction_type(2)), Ok(()));
+        assert_eq!(validate_tx_request(&TransactionRequest::default().transaction_type(4)), Ok(()));
+    }
+
+    #[test]
+    fn inconsistent_blob_fields_rejected() {
+        // blob fee without blobs
+        assert_eq!(
+            validate_tx_request(&TransactionRequest {
+                max_fee_per_blob_gas: Some(1),
+                ..Default::default()
+            }),
+            Err(OdysseyWalletError::InconsistentBlobFields)
+        );
+        assert_eq!(
+            validate_tx_request(&TransactionRequest {
+                max_fee_per_blob_gas: Some(1),
+                blob_versioned_hashes: Some(vec![]),
+                ..Default::default()
+            }),
+            Err(OdysseyWalletError::InconsistentBlobFields)
+        );
+
+        // blobs without blob fee
+        assert_eq!(
+            validate_tx_request(&TransactionRequest {
+                blob_versioned_hashes: Some(vec![B256::ZERO]),
+                ..Default::default()
+            }),
+            Err(OdysseyWalletError::InconsistentBlobFields)
+        );
+        assert_eq!(
+            validate_tx_request(&TransactionRequest {
+                sidecar: Some(Default::default()),
+                ..Default::default()
+            }),
+            Err(OdysseyWalletError::InconsistentBlobFields)
+        );
+
+        // consistent blob fields are still not sponsored
+        assert_eq!(
+            validate_tx_request(&TransactionRequest {
+                max_fee_per_blob_gas: Some(1),
+                blob_versioned_hashes: Some(vec![B256::ZERO]),
+                ..Default::default()
+            }),
+            Err(OdysseyWalletError::BlobsNotSponsored)
+        );
+        assert_eq!(
+            validate_tx_request(&TransactionRequest {
+                blob_versioned_hashes: Some(vec![]),
+                ..Default::default()
+            }),
+            Err(OdysseyWalletError::BlobsNotSponsored)
+        );
+        assert_eq!(
+            validate_tx_request(&TransactionRequest::default().transaction_type(3)),
+            Err(OdysseyWalletError::UnsupportedTransactionType { tx_type: 3 })
+        );
+        assert_eq!(validate_tx_request(&TransactionRequest::default()), Ok(()));
+    }
+
+    #[test]
+    fn no_system_address_allowed() {
+        let system = SystemAddresses::default();
+        let precompile = address!("0000000000000000000000000000000000000001");
+        let delegated = address!("70997970C51812dc3A010C7d01b50e0d17dc79C8");
+        let delegate = address!("3C44CdDdB6a900fa2b585dd299e03d12FA4293BC");
+
+        assert_eq!(
+            validate_not_system_address([precompile], &system),
+            Err(OdysseyWalletError::SystemAddressNotAllowed { address: precompile })
+        );
+        assert_eq!(
+            validate_not_system_address([delegated, precompile], &system),
+            Err(OdysseyWalletError::SystemAddressNotAllowed { address: precompile })
+        );
+        assert_eq!(validate_not_system_address([delegated, delegate], &system), Ok(()));
+        assert_eq!(validate_not_system_address([precompile], &SystemAddresses::none()), Ok(()));
+    }
+
+    #[test]
+    fn blocked_address_rejected() {
+        let blocked = address!("70997970C51812dc3A010C7d01b50e0d17dc79C8");
+        let allowed = address!("3C44CdDdB6a900fa2b585dd299e03d12FA4293BC");
+        let config = OdysseyWalletConfig::default().with_blocked_addresses([blocked]);
+
+        assert_eq!(
+            validate_not_blocked([blocked], &config.blocked_addresses),
+            Err(OdysseyWalletError::AddressBlocked { address: blocked })
+        );
+        // e.g. a blocked authority of a request to an allowed account
+        assert_eq!(
+            validate_not_blocked([allowed, blocked], &config.blocked_addresses),
+            Err(OdysseyWalletError::AddressBlocked { address: blocked })
+        );
+        assert_eq!(validate_not_blocked([allowed], &config.blocked_addresses), Ok(()));
+        assert_eq!(validate_not_blocked([blocked], &HashSet::new()), Ok(()));
+    }
+
+    #[test]
+    fn authorization_nonce_window() {
+        let authority = address!("70997970C51812dc3A010C7d01b50e0d17dc79C8");
+
+        // in range
+        assert_eq!(validate_authorization_nonce(authority, 5, 5, 2), Ok(()));
+        assert_eq!(validate_authorization_nonce(authority, 7, 5, 2), Ok(()));
+
+        // far-future
+        assert_eq!(
+            validate_authorization_nonce(authority, 8, 5, 2),
+            Err(OdysseyWalletError::AuthorizationNonceOutOfRange {
+                authority,
+                nonce: 8,
+                current: 5
+            })
+        );
+
+        // stale
+        assert_eq!(
+            validate_authorization_nonce(authority, 4, 5, 2),
+            Err(OdysseyWalletError::AuthorizationNonceOutOfRange {
+                authority,
+                nonce: 4,
+                current: 5
+            })
+        );
+    }
+
+    #[test]
+    fn disabled_method_rejected() {
+        assert_eq!(ensure_enabled(&MethodFlags::all(), WalletMethod::SendTransaction), Ok(()));
+
+        let methods = MethodFlags::all().with_method(WalletMethod::SendTransaction, false);
+        let err = ensure_enabled(&methods, WalletMethod::SendTransaction).unwrap_err();
+        assert_eq!(err, OdysseyWalletError::MethodDisabled { method: "wallet_sendTransaction" });
+
+        let err = jsonrpsee::types::error::ErrorObject::from(err);
+        assert_eq!(err.code(), jsonrpsee::types::error::METHOD_NOT_FOUND_CODE);
+    }
+
+    #[test]
+    fn shadow_rejects_accepted_request() {
+        let live = OdysseyWalletConfig::default();
+        let shadow = OdysseyWalletConfig::default().with_gas_ceiling(200_000);
+        let wallet = OdysseyWallet::with_config(
+            (),
+            EthereumWallet::default(),
+            (),
+            1,
+            live.clone().with_shadow_config(shadow),
+        )
+        .unwrap();
+        let active = wallet.active_config();
+        let shadow = active.shadow.as_ref().unwrap();
+        assert_eq!(shadow.config.gas_ceiling, 200_000);
+
+        // the buffered estimate is within the live ceiling, but not the stricter one
+        let decide = |config: &OdysseyWalletConfig| {
+            validate_buffered_gas(190_000, config.gas_buffer_percent, config.gas_ceiling)
+                .map(|_| ())
+        };
+        assert_eq!(decide(&live), Ok(()));
+        let decision = decide(&shadow.config);
+        assert_eq!(
+            decision,
+            Err(OdysseyWalletError::GasEstimateTooHigh { estimate: 209_000, limit: 200_000 })
+        );
+        assert_eq!(Divergence::of(true, &decision), Some(Divergence::Rejected));
+        wallet.record_divergence(true, &decision);
+    }
+
+    #[test]
+    fn partial_bundle_in_error_data() {
+        let (id, hash) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let err = ErrorObject::from(OdysseyWalletError::BundlePartiallySubmitted {
+            id,
+            hashes: vec![hash],
+            reason: "tx value not zero".to_owned(),
+        });
+        assert_eq!(err.code(), -32066);
+        assert!(err.message().ends_with("some of its calls: tx value not zero"));
+        let data: serde_json::Value = serde_json::from_str(err.data().unwrap().get()).unwrap();
+        assert_eq!(data, serde_json::json!({ "id": id, "hashes": [hash] }));
+    }
+
+    #[test]
+    fn gas_estimate_in_error_data() {
+        let err = ErrorObject::from(OdysseyWalletError::GasEstimateTooHigh {
+            estimate: 400_000,
+            limit: 350_000,
+        });
+        assert_eq!(err.code(), -32041);
+        let data: serde_json::Value = serde_json::from_str(err.data().unwrap().get()).unwrap();
+        assert_eq!(data, serde_json::json!({ "estimate": 400_000, "limit": 350_000 }));
+    }
+
+    #[tokio::test]
+    async fn flagged_delegate_rejected() {
+        let flagged = address!("3C44CdDdB6a900fa2b585dd299e03d12FA4293BC");
+        let unflagged = address!("90F79bf6EB2c4f870365E785982E1f101E93b906");
+        let oracle = InMemoryRiskOracle::new([flagged]);
+
+        assert_eq!(validate_risk(&oracle, &[unflagged]).await, Ok(()));
+        assert_eq!(
+            validate_risk(&oracle, &[unflagged, flagged]).await,
+            Err(OdysseyWalletError::DestinationFlagged { target: flagged })
+        );
+    }
+
+    #[tokio::test]
+    async fn build_failures_are_detailed() {
+        // a call without fees and gas limit cannot be built
+        let request = TransactionRequest::default().to(Address::with_last_byte(1)).nonce(0);
+        assert_eq!(
+            ensure_buildable(&request),
+            Err(OdysseyWalletError::MissingTransactionFields {
+                tx_type: "EIP-1559".into(),
+                fields: vec!["gas_limit", "max_fee_per_gas", "max_priority_fee_per_gas"]
+            })
+        );
+
+        // the wallet has no signer for the sender
+        let request = request
+            .from(Address::with_last_byte(2))
+            .gas_limit(21_000)
+            .max_fee_per_gas(2)
+            .max_priority_fee_per_gas(1);
+        assert_eq!(ensure_buildable(&request), Ok(()));
+        let err = <TransactionRequest as TransactionBuilder<Ethereum>>::build::<EthereumWallet>(
+            request,
+            &EthereumWallet::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(build_error(err), OdysseyWalletError::SigningFailed { .. }));
+    }
+
+    #[tokio::test]
+    async fn attestation_required() {
+        /// Accepts the token `valid` for a single account.
+        #[derive(Debug)]
+        struct MockAttestationVerifier(Address);
+
+        #[async_trait]
+        impl AttestationVerifier for MockAttestationVerifier {
+            async fn verify(&self, token: &str, eoa: Address) -> bool {
+                token == "valid" && eoa == self.0
+            }
+        }
+
+        let attested = address!("3C44CdDdB6a900fa2b585dd299e03d12FA4293BC");
+        let other = address!("90F79bf6EB2c4f870365E785982E1f101E93b906");
+        let verifier = MockAttestationVerifier(attested);
+
+        assert_eq!(
+            validate_attestation(&verifier, Some("valid"), [attested].into_iter()).await,
+            Ok(())
+        );
+        for (token, accounts) in [
+            (Some("invalid"), vec![attested]),
+            (None, vec![attested]),
+            (Some("valid"), vec![attested, other]),
+        ] {
+            assert_eq!(
+                validate_attestation(&verifier, token, accounts.into_iter()).await,
+                Err(OdysseyWalletError::AttestationRequired)
+            );
+        }
+        assert_eq!(
+            validate_attestation(&NoopAttestationVerifier, None, [other].into_iter()).await,
+            Ok(())
+        );
+    }
+
+    #[tokio::test]
+    async fn unverified_delegate_rejected() {
+        #[derive(Debug)]
+        struct MockVerificationOracle(Address);
+
+        #[async_trait]
+        impl VerificationOracle for MockVerificationOracle {
+            async fn is_verified(&self, target: Address) -> bool {
+                target == self.0
+            }
+        }
+
+        let verified = address!("3C44CdDdB6a900fa2b585dd299e03d12FA4293BC");
+        let unverified = address!("90F79bf6EB2c4f870365E785982E1f101E93b906");
+        let oracle = MockVerificationOracle(verified);
+
+        assert_eq!(validate_verification(&oracle, &[verified]).await, Ok(()));
+        assert_eq!(
+            validate_verification(&oracle, &[verified, unverified]).await,
+            Err(OdysseyWalletError::DestinationUnverified { target: unverified })
+        );
+        assert_eq!(validate_verification(&NoopVerificationOracle, &[unverified]).await, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn deep_calls_get_tighter_ceiling() {
+        /// Reports the depth encoded in the first byte of the calldata.
+        #[derive(Debug)]
+        struct MockCallDepthProbe;
+
+        #[async_trait]
+        impl CallDepthProbe for MockCallDepthProbe {
+            async fn max_call_depth(&self, request: &TransactionRequest) -> Option<u64> {
+                request.input.input().and_then(|input| input.first()).map(|depth| *depth as u64)
+            }
+        }
+
+        let config = OdysseyWalletConfig::default().with_deep_call_gas_ceiling(
+            Arc::new(MockCallDepthProbe),
+            8,
+            100_000,
+        );
+        let wallet =
+            OdysseyWallet::with_config((), EthereumWallet::default(), (), 1, config).unwrap();
+        let active = wallet.active_config();
+        let request = |depth: Option<u8>| {
+            TransactionRequest::default()
+                .to(Address::with_last_byte(1))
+                .input(depth.map(|depth| vec![depth]).unwrap_or_default().into())
+        };
+
+        // shallow calls, and calls of unknown depth, get the regular ceiling
+        for depth in [Some(1), Some(8), None] {
+            assert_eq!(
+                wallet.effective_gas_ceiling(&active, &request(depth)).await,
+                DEFAULT_GAS_CEILING
+            );
+        }
+
+        // deep calls get the tighter ceiling
+        assert_eq!(wallet.effective_gas_ceiling(&active, &request(Some(9))).await, 100_000);
+    }
 }
@@ -0,0 +1,145 @@
+//! Scheduled maintenance windows, during which sponsorship is paused.
+//!
+//! Unlike disabling methods, windows are planned ahead: clients are rejected with the time the
+//! window ends, and operators are warned as a window approaches.
+
+use crate::OdysseyWalletError;
+use std::time::Duration;
+
+/// A period during which sponsorship is paused, in unix seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaintenanceWindow {
+    /// A single window from `start` until `end`.
+    Once {
+        /// When the window starts.
+        start: u64,
+        /// When the window ends.
+        end: u64,
+    },
+    /// A window of `duration` that recurs every `period`, the first starting at `start`.
+    Recurring {
+        /// When the first window starts.
+        start: u64,
+        /// How long each window lasts.
+        duration: Duration,
+        /// The time between the starts of consecutive windows.
+        period: Duration,
+    },
+}
+
+impl MaintenanceWindow {
+    /// Returns whether the window is well-formed, i.e. non-empty and, if recurring, lasting at
+    /// least a second and not overlapping with its next occurrence.
+    pub(crate) fn is_valid(&self) -> bool {
+        match *self {
+            Self::Once { start, end } => start < end,
+            Self::Recurring { duration, period, .. } => {
+                duration.as_secs() > 0 && duration <= period
+            }
+        }
+    }
+
+    /// Returns when the window ends, if it is active at `now`.
+    fn ends_at(&self, now: u64) -> Option<u64> {
+        match *self {
+            Self::Once { start, end } => (start..end).contains(&now).then_some(end),
+            Self::Recurring { start, duration, period } => {
+                let into_period = now.checked_sub(start)? % period.as_secs().max(1);
+                (into_period < duration.as_secs()).then(|| now - into_period + duration.as_secs())
+            }
+        }
+    }
+
+    /// Returns when the window next starts after `now`, if ever.
+    fn next_start(&self, now: u64) -> Option<u64> {
+        match *self {
+            Self::Once { start, .. } => (start > now).then_some(start),
+            Self::Recurring { start, period, .. } => {
+                let Some(elapsed) = now.checked_sub(start) else {
+                    return Some(start);
+                };
+                let period = period.as_secs().max(1);
+                start.checked_add((elapsed / period + 1).checked_mul(period)?)
+            }
+        }
+    }
+}
+
+/// The planned maintenance windows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaintenanceSchedule {
+    /// The windows during which sponsorship is paused.
+    pub windows: Vec<MaintenanceWindow>,
+    /// How long before a window starts a warning is logged on each request.
+    pub notice: Duration,
+}
+
+impl MaintenanceSchedule {
+    /// Ensures no window is active at `now`, the current unix time in seconds.
+    ///
+    /// Returns the number of seconds until the next window starts, if any.
+    pub(crate) fn check(&self, now: u64) -> Result<Option<u64>, OdysseyWalletError> {
+        // if windows overlap, sponsorship resumes once all of them ended
+        if let Some(ends_at) = self.windows.iter().filter_map(|window| window.ends_at(now)).max() {
+            return Err(OdysseyWalletError::MaintenanceWindow { ends_at });
+        }
+        Ok(self
+            .windows
+            .iter()
+            .filter_map(|window| window.next_start(now))
+            .min()
+            .map(|start| start - now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_inside_windows() {
+        let schedule = MaintenanceSchedule {
+            windows: vec![
+                MaintenanceWindow::Once { start: 1_000, end: 2_000 },
+                // for 10 seconds every minute, from 10_000
+                MaintenanceWindow::Recurring {
+                    start: 10_000,
+                    duration: Duration::from_secs(10),
+                    period: Duration::from_secs(60),
+                },
+            ],
+            notice: Duration::from_secs(300),
+        };
+
+        assert_eq!(schedule.check(999), Ok(Some(1)));
+        assert_eq!(
+            schedule.check(1_000),
+            Err(OdysseyWalletError::MaintenanceWindow { ends_at: 2_000 })
+        );
+        assert_eq!(
+            schedule.check(1_999),
+            Err(OdysseyWalletError::MaintenanceWindow { ends_at: 2_000 })
+        );
+        assert_eq!(schedule.check(2_000), Ok(Some(8_000)));
+
+        assert_eq!(
+            schedule.check(10_125),
+            Err(OdysseyWalletError::MaintenanceWindow { ends_at: 10_130 })
+        );
+        assert_eq!(schedule.check(10_130), Ok(Some(50)));
+    }
+
+    #[test]
+    fn validates_windows() {
+        assert!(MaintenanceWindow::Once { start: 1, end: 2 }.is_valid());
+        assert!(!MaintenanceWindow::Once { start: 2, end: 2 }.is_valid());
+        let recurring = |duration, period| MaintenanceWindow::Recurring {
+            start: 0,
+            duration: Duration::from_secs(duration),
+            period: Duration::from_secs(period),
+        };
+        assert!(recurring(10, 60).is_valid());
+        assert!(!recurring(0, 60).is_valid());
+        assert!(!recurring(61, 60).is_valid());
+    }
+}
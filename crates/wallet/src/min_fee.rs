@@ -0,0 +1,76 @@
+//! Enforcement of the minimum fee accepted by the pool.
+//!
+//! The fee of a sponsored transaction is derived from the base fee when the request is estimated.
+//! If the base fee rises quickly, the fee may fall below what the pool accepts by the time the
+//! transaction is submitted, and the pool drops it.
+
+use crate::OdysseyWalletError;
+
+/// How a fee below the minimum is handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BelowMinimumFee {
+    /// Requests are rejected.
+    Reject,
+    /// The max fee per gas is raised to the minimum.
+    Bump,
+}
+
+/// Settings for checking the fee of sponsored transactions against the minimum accepted by the
+/// pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MinimumFeeCheck {
+    /// The minimum max fee per gas accepted by the pool regardless of the base fee, in wei.
+    pub floor: u128,
+    /// How a fee below the minimum is handled.
+    pub on_below: BelowMinimumFee,
+}
+
+impl MinimumFeeCheck {
+    /// Returns the minimum max fee per gas, given the current base fee.
+    pub(crate) fn minimum(&self, base_fee: u128) -> u128 {
+        self.floor.max(base_fee)
+    }
+
+    /// Returns the max fee per gas to use, given the computed fee and the minimum.
+    pub(crate) const fn apply(
+        &self,
+        max_fee_per_gas: u128,
+        minimum: u128,
+    ) -> Result<u128, OdysseyWalletError> {
+        if max_fee_per_gas >= minimum {
+            return Ok(max_fee_per_gas);
+        }
+        match self.on_below {
+            BelowMinimumFee::Reject => {
+                Err(OdysseyWalletError::FeeBelowMinimum { max_fee_per_gas, minimum })
+            }
+            BelowMinimumFee::Bump => Ok(minimum),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enforces_minimum_fee() {
+        let reject = MinimumFeeCheck { floor: 100, on_below: BelowMinimumFee::Reject };
+        let bump = MinimumFeeCheck { on_below: BelowMinimumFee::Bump, ..reject };
+
+        // the minimum is the higher of the floor and the base fee
+        assert_eq!(reject.minimum(50), 100);
+        assert_eq!(reject.minimum(150), 150);
+
+        // competitive fees are kept
+        assert_eq!(reject.apply(200, 150), Ok(200));
+        assert_eq!(bump.apply(150, 150), Ok(150));
+
+        // the base fee rose above the computed fee
+        assert_eq!(
+            reject.apply(120, 150),
+            Err(OdysseyWalletError::FeeBelowMinimum { max_fee_per_gas: 120, minimum: 150 })
+        );
+        assert_eq!(bump.apply(120, 150), Ok(150));
+    }
+}
@@ -0,0 +1,352 @@
+//! Coordination of the nonces assigned to each signer.
+//!
+//! The next available nonce reported by the pool does not include transactions that were signed
+//! but not yet submitted. Each signer therefore tracks the next nonce it assigns, which is
+//! initialized from the pool on first use, so the pool is not queried on every request. Nonces
+//! are assigned while holding the permit of the signer, which is released right after, so
+//! requests are estimated, signed and submitted concurrently. A nonce whose transaction is not
+//! submitted is released, and assigned again before any new nonce, so the nonces stay contiguous
+//! and are never assigned twice.
+//!
+//! If a submission is rejected because its nonce is too low, e.g. because the signer was used
+//! outside of the wallet, the counter is [resynced](SignerNonces::resync) from the pool.
+
+use alloy_primitives::Address;
+use jsonrpsee::types::error::ErrorObject;
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+use tokio::sync::{Mutex, MutexGuard};
+
+/// The window over which the nonces assigned to a signer are counted.
+pub(crate) const NONCE_RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// The permits and nonce counters of the signers of the wallet.
+#[derive(Debug)]
+pub(crate) struct SignerNonces {
+    signers: HashMap<Address, SignerNonce>,
+}
+
+/// The permit and nonce counter of a single signer.
+#[derive(Debug, Default)]
+struct SignerNonce {
+    /// Guards the assignment of nonces.
+    permit: Mutex<()>,
+    /// The nonces reserved by requests in flight.
+    reservations: parking_lot::Mutex<Reservations>,
+}
+
+/// The nonces of a signer that are reserved or available for reuse.
+#[derive(Debug, Default)]
+struct Reservations {
+    /// The next nonce to assign, if no released nonce is available.
+    next: u64,
+    /// Nonces below `next` that were released, and are assigned first.
+    released: BTreeSet<u64>,
+    /// Whether `next` is known to be at least the next available nonce according to the pool.
+    synced: bool,
+}
+
+impl Reservations {
+    /// Reserves the lowest available nonce, given the next available nonce according to the pool.
+    fn reserve(&mut self, pending: u64) -> u64 {
+        // nonces below the pending nonce were used by transactions not sent by the wallet
+        self.released = self.released.split_off(&pending);
+        self.next = self.next.max(pending);
+        self.synced = true;
+        self.take()
+    }
+
+    /// Reserves the lowest available nonce without consulting the pool.
+    ///
+    /// Returns `None` if the counter is not synced with the pool.
+    fn reserve_cached(&mut self) -> Option<u64> {
+        self.synced.then(|| self.take())
+    }
+
+    /// Takes the lowest released nonce, or the next new one.
+    fn take(&mut self) -> u64 {
+        self.released.pop_first().unwrap_or_else(|| {
+            let nonce = self.next;
+            self.next += 1;
+            nonce
+        })
+    }
+
+    /// Makes the nonce available again.
+    fn release(&mut self, nonce: u64) {
+        if nonce >= self.next {
+            return;
+        }
+        self.released.insert(nonce);
+        // released nonces at the end are not gaps
+        while self.next > 0 && self.released.remove(&(self.next - 1)) {
+            self.next -= 1;
+        }
+    }
+}
+
+impl SignerNonces {
+    /// Creates the counters for the given signers.
+    pub(crate) fn new(signers: impl IntoIterator<Item = Address>) -> Self {
+        Self { signers: signers.into_iter().map(|signer| (signer, Default::default())).collect() }
+    }
+
+    /// Waits for the permit of the signer.
+    ///
+    /// Returns `None` if the signer is unknown.
+    pub(crate) async fn lock(&self, signer: &Address) -> Option<SignerPermit<'_>> {
+        let signer = self.signers.get(signer)?;
+        let guard = signer.permit.lock().await;
+        Some(SignerPermit { _guard: guard, signer })
+    }
+
+    /// Syncs the counter of the signer with the pool before the next nonce is assigned.
+    pub(crate) fn resync(&self, signer: &Address) {
+        if let Some(signer) = self.signers.get(signer) {
+            signer.reservations.lock().synced = false;
+        }
+    }
+}
+
+/// Returns `true` if the submission was rejected because the nonce of the transaction is too low.
+pub(crate) fn is_nonce_too_low(err: &ErrorObject<'_>) -> bool {
+    err.message().to_ascii_lowercase().contains("nonce too low")
+}
+
+/// Limits how fast the nonce of each signer advances.
+///
+/// This protects the signers against bugs or abuse that rapidly consume nonces, regardless of who
+/// the requests are from.
+#[derive(Debug)]
+pub(crate) struct SignerNonceRate {
+    /// The maximum number of nonces assigned to a signer within the window.
+    max_per_window: u32,
+    /// When nonces were assigned to each signer within the window, oldest first.
+    assigned: parking_lot::Mutex<HashMap<Address, VecDeque<Instant>>>,
+}
+
+impl SignerNonceRate {
+    /// Creates a new limiter allowing `max_per_window` nonces per signer within the window.
+    pub(crate) fn new(max_per_window: u32) -> Self {
+        Self { max_per_window, assigned: Default::default() }
+    }
+
+    /// Records the assignment of a nonce to the signer.
+    ///
+    /// Returns the number of nonces assigned to the signer within the window, including this one,
+    /// or `None` if the signer reached the limit, in which case nothing is recorded. Nonces that
+    /// are released after being assigned still count.
+    pub(crate) fn try_record(&self, signer: Address, now: Instant) -> Option<u32> {
+        let mut assigned = self.assigned.lock();
+        let assigned = assigned.entry(signer).or_default();
+        while assigned
+            .front()
+            .is_some_and(|at| now.saturating_duration_since(*at) >= NONCE_RATE_WINDOW)
+        {
+            assigned.pop_front();
+        }
+        if assigned.len() >= self.max_per_window as usize {
+            return None;
+        }
+        assigned.push_back(now);
+        Some(assigned.len() as u32)
+    }
+}
+
+/// Exclusive access to the assignment of nonces of a signer.
+#[derive(Debug)]
+pub(crate) struct SignerPermit<'a> {
+    _guard: MutexGuard<'a, ()>,
+    signer: &'a SignerNonce,
+}
+
+impl<'a> SignerPermit<'a> {
+    /// Reserves the next nonce, given the next available nonce according to the pool.
+    ///
+    /// The reservation outlives the permit, so the permit can be released right after.
+    pub(crate) fn assign(&self, pending: u64) -> NonceReservation<'a> {
+        let nonce = self.signer.reservations.lock().reserve(pending);
+        NonceReservation { signer: self.signer, nonce, committed: false }
+    }
+
+    /// Reserves the next nonce from the counter of the signer.
+    ///
+    /// Returns `None` if the counter must first be synced with the pool, see
+    /// [`assign`](Self::assign).
+    pub(crate) fn assign_cached(&self) -> Option<NonceReservation<'a>> {
+        let nonce = self.signer.reservations.lock().reserve_cached()?;
+        Some(NonceReservation { signer: self.signer, nonce, committed: false })
+    }
+}
+
+/// A nonce reserved for a request.
+///
+/// If the reservation is dropped without being [committed](Self::commit), the nonce is released,
+/// e.g. because the transaction was rejected or its submission failed.
+#[derive(Debug)]
+pub(crate) struct NonceReservation<'a> {
+    signer: &'a SignerNonce,
+    nonce: u64,
+    committed: bool,
+}
+
+impl NonceReservation<'_> {
+    /// Returns the reserved nonce.
+    pub(crate) const fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    /// Keeps the reserved nonce, as its transaction was submitted.
+    pub(crate) fn commit(mut self) {
+        self.committed = true;
+    }
+}
+
+impl Drop for NonceReservation<'_> {
+    fn drop(&mut self) {
+        if !self.committed {
+            self.signer.reservations.lock().release(self.nonce);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+    use std::{collections::HashSet, sync::Arc};
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn assigns_contiguous_nonces_under_contention() {
+        let signers: Vec<_> = (1..=4).map(Address::with_last_byte).collect();
+        let nonces = Arc::new(SignerNonces::new(signers.clone()));
+        let in_flight = Arc::new(Mutex::new(HashMap::<Address, HashSet<u64>>::new()));
+        let submitted = Arc::new(Mutex::new(HashMap::<Address, Vec<u64>>::new()));
+
+        let tasks: Vec<_> = (0..800)
+            .map(|i| {
+                let signer = signers[i % signers.len()];
+                let nonces = nonces.clone();
+                let in_flight = in_flight.clone();
+                let submitted = submitted.clone();
+                tokio::spawn(async move {
+                    // the permit is only held to reserve the nonce
+                    let reservation = nonces.lock(&signer).await.unwrap().assign(0);
+                    let nonce = reservation.nonce();
+                    assert!(in_flight.lock().entry(signer).or_default().insert(nonce));
+
+                    // the pool has not seen any of the transactions yet, and every third request
+                    // is rejected after its nonce was reserved
+                    tokio::time::sleep(Duration::from_micros(100)).await;
+                    in_flight.lock().get_mut(&signer).unwrap().remove(&nonce);
+                    if i % 3 == 0 {
+                        return;
+                    }
+                    submitted.lock().entry(signer).or_default().push(nonce);
+                    reservation.commit();
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        for signer in &signers {
+            let submitted = submitted.lock().remove(signer).unwrap();
+            let reservations = nonces.signers[signer].reservations.lock();
+            // no nonce was submitted twice, and each gap is assigned before any new nonce
+            let mut nonces: Vec<_> =
+                submitted.iter().chain(&reservations.released).copied().collect();
+            nonces.sort_unstable();
+            assert_eq!(nonces, (0..reservations.next).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn limits_signer_nonce_rate() {
+        let signer = Address::with_last_byte(1);
+        let rate = SignerNonceRate::new(3);
+        let now = Instant::now();
+
+        let recorded: Vec<_> = (0..5).map(|_| rate.try_record(signer, now)).collect();
+        assert_eq!(recorded, [Some(1), Some(2), Some(3), None, None]);
+
+        // other signers have their own limit
+        assert_eq!(rate.try_record(Address::ZERO, now), Some(1));
+
+        // the rate drops as assignments leave the window
+        let later = now + NONCE_RATE_WINDOW;
+        assert_eq!(rate.try_record(signer, later), Some(1));
+    }
+
+    #[tokio::test]
+    async fn releases_unused_nonces() {
+        let signer = Address::with_last_byte(1);
+        let nonces = SignerNonces::new([signer]);
+        assert!(nonces.lock(&Address::ZERO).await.is_none());
+        let nonces = &nonces;
+        let reserve = |pending| async move { nonces.lock(&signer).await.unwrap().assign(pending) };
+
+        // the pool is ahead of the counter
+        let first = reserve(5).await;
+        assert_eq!(first.nonce(), 5);
+        first.commit();
+
+        // requests in flight hold distinct nonces
+        let (second, third, fourth) = (reserve(5).await, reserve(5).await, reserve(5).await);
+        assert_eq!((second.nonce(), third.nonce(), fourth.nonce()), (6, 7, 8));
+
+        // a nonce that was not committed is assigned again before new nonces
+        drop(third);
+        let again = reserve(5).await;
+        assert_eq!(again.nonce(), 7);
+        again.commit();
+        second.commit();
+        assert_eq!(reserve(5).await.nonce(), 9);
+
+        // released nonces at the end are not gaps
+        drop(fourth);
+        assert_eq!(reserve(5).await.nonce(), 8);
+
+        // released nonces the pool moved past are not assigned again
+        let (a, b) = (reserve(5).await, reserve(5).await);
+        assert_eq!((a.nonce(), b.nonce()), (8, 9));
+        b.commit();
+        drop(a);
+        assert_eq!(reserve(10).await.nonce(), 10);
+    }
+
+    #[tokio::test]
+    async fn caches_nonces_until_resync() {
+        let signer = Address::with_last_byte(1);
+        let nonces = SignerNonces::new([signer]);
+
+        // the counter is initialized from the pool
+        let permit = nonces.lock(&signer).await.unwrap();
+        assert!(permit.assign_cached().is_none());
+        permit.assign(5).commit();
+        let next = permit.assign_cached().unwrap();
+        assert_eq!(next.nonce(), 6);
+        next.commit();
+
+        // released nonces are reused without consulting the pool
+        let (a, b) = (permit.assign_cached().unwrap(), permit.assign_cached().unwrap());
+        assert_eq!((a.nonce(), b.nonce()), (7, 8));
+        b.commit();
+        drop(a);
+        assert_eq!(permit.assign_cached().unwrap().nonce(), 7);
+        drop(permit);
+
+        // the signer was used outside of the wallet
+        let err =
+            ErrorObject::owned(-32003, "nonce too low: next nonce 12, tx nonce 9", None::<()>);
+        assert!(is_nonce_too_low(&err));
+        assert!(!is_nonce_too_low(&ErrorObject::owned(-32003, "insufficient funds", None::<()>)));
+        nonces.resync(&signer);
+        let permit = nonces.lock(&signer).await.unwrap();
+        assert!(permit.assign_cached().is_none());
+        assert_eq!(permit.assign(12).nonce(), 12);
+    }
+}
@@ -0,0 +1,206 @@
+//! Pluggable oracles consulted when validating sponsored transactions.
+
+use alloy_primitives::Address;
+use jsonrpsee::core::async_trait;
+use parking_lot::{Mutex, RwLock};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    time::{Duration, Instant},
+};
+
+/// An oracle that flags delegation targets that have shown malicious behaviour.
+///
+/// The oracle is consulted for every delegation target after the destination of a request has been
+/// resolved. Requests that would call into, or delegate to, a flagged target are rejected.
+#[async_trait]
+pub trait RiskOracle: Debug + Send + Sync {
+    /// Returns `true` if the target is flagged.
+    async fn is_flagged(&self, target: Address) -> bool;
+}
+
+/// A [`RiskOracle`] that never flags any target.
+#[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
+pub struct NoopRiskOracle;
+
+#[async_trait]
+impl RiskOracle for NoopRiskOracle {
+    async fn is_flagged(&self, _target: Address) -> bool {
+        false
+    }
+}
+
+/// A [`RiskOracle`] backed by an in-memory set of flagged targets that can be updated at runtime.
+#[derive(Debug, Default)]
+pub struct InMemoryRiskOracle {
+    flagged: RwLock<HashSet<Address>>,
+}
+
+impl InMemoryRiskOracle {
+    /// Creates a new oracle with the given flagged targets.
+    pub fn new(flagged: impl IntoIterator<Item = Address>) -> Self {
+        Self { flagged: RwLock::new(flagged.into_iter().collect()) }
+    }
+
+    /// Flags a target.
+    pub fn flag(&self, target: Address) {
+        self.flagged.write().insert(target);
+    }
+
+    /// Removes a target from the flagged set.
+    pub fn unflag(&self, target: Address) {
+        self.flagged.write().remove(&target);
+    }
+}
+
+#[async_trait]
+impl RiskOracle for InMemoryRiskOracle {
+    async fn is_flagged(&self, target: Address) -> bool {
+        self.flagged.read().contains(&target)
+    }
+}
+
+/// An oracle that reports whether the source of a delegation target is verified, e.g. on a block
+/// explorer.
+///
+/// The oracle is consulted for every delegation target after the destination of a request has been
+/// resolved. Requests that would call into, or delegate to, an unverified target are rejected.
+#[async_trait]
+pub trait VerificationOracle: Debug + Send + Sync {
+    /// Returns `true` if the source of the target is verified.
+    async fn is_verified(&self, target: Address) -> bool;
+}
+
+/// A [`VerificationOracle`] that considers every target verified.
+#[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
+pub struct NoopVerificationOracle;
+
+#[async_trait]
+impl VerificationOracle for NoopVerificationOracle {
+    async fn is_verified(&self, _target: Address) -> bool {
+        true
+    }
+}
+
+/// A verifier of attestation tokens, e.g. proofs of humanity, that clients attach to requests to
+/// show they are not sybils.
+///
+/// The verifier is consulted for every account a request is sponsored for, after the destination
+/// of the request has been resolved. Requests without a token are verified with an empty token.
+/// Requests with a token that is not valid for every account are rejected.
+#[async_trait]
+pub trait AttestationVerifier: Debug + Send + Sync {
+    /// Returns `true` if the token attests the account.
+    async fn verify(&self, token: &str, eoa: Address) -> bool;
+}
+
+/// An [`AttestationVerifier`] that accepts every request, with or without a token.
+#[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
+pub struct NoopAttestationVerifier;
+
+#[async_trait]
+impl AttestationVerifier for NoopAttestationVerifier {
+    async fn verify(&self, _token: &str, _eoa: Address) -> bool {
+        true
+    }
+}
+
+/// The maximum number of cached verification results before expired entries are evicted.
+const MAX_CACHED_VERIFICATIONS: usize = 1_024;
+
+/// A [`VerificationOracle`] that caches the results of another oracle for a fixed duration.
+#[derive(Debug)]
+pub struct CachedVerificationOracle<O> {
+    oracle: O,
+    ttl: Duration,
+    cache: Mutex<HashMap<Address, (bool, Instant)>>,
+}
+
+impl<O> CachedVerificationOracle<O> {
+    /// Creates a new oracle that caches the results of `oracle` for `ttl`.
+    pub fn new(oracle: O, ttl: Duration) -> Self {
+        Self { oracle, ttl, cache: Default::default() }
+    }
+}
+
+#[async_trait]
+impl<O: VerificationOracle> VerificationOracle for CachedVerificationOracle<O> {
+    async fn is_verified(&self, target: Address) -> bool {
+        if let Some((verified, checked_at)) = self.cache.lock().get(&target).copied() {
+            if checked_at.elapsed() < self.ttl {
+                return verified;
+            }
+        }
+
+        let verified = self.oracle.is_verified(target).await;
+
+        let mut cache = self.cache.lock();
+        if cache.len() >= MAX_CACHED_VERIFICATIONS {
+            cache.retain(|_, (_, checked_at)| checked_at.elapsed() < self.ttl);
+        }
+        cache.insert(target, (verified, Instant::now()));
+
+        verified
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn in_memory_oracle() {
+        let flagged = Address::with_last_byte(1);
+        let unflagged = Address::with_last_byte(2);
+        let oracle = InMemoryRiskOracle::new([flagged]);
+
+        assert!(oracle.is_flagged(flagged).await);
+        assert!(!oracle.is_flagged(unflagged).await);
+
+        oracle.flag(unflagged);
+        oracle.unflag(flagged);
+        assert!(!oracle.is_flagged(flagged).await);
+        assert!(oracle.is_flagged(unflagged).await);
+
+        assert!(!NoopRiskOracle.is_flagged(flagged).await);
+    }
+
+    /// Verifies a fixed set of targets, and counts the lookups.
+    #[derive(Debug, Default)]
+    struct MockVerificationOracle {
+        verified: HashSet<Address>,
+        lookups: Mutex<usize>,
+    }
+
+    #[async_trait]
+    impl VerificationOracle for MockVerificationOracle {
+        async fn is_verified(&self, target: Address) -> bool {
+            *self.lookups.lock() += 1;
+            self.verified.contains(&target)
+        }
+    }
+
+    #[tokio::test]
+    async fn cached_verification_oracle() {
+        let verified = Address::with_last_byte(1);
+        let unverified = Address::with_last_byte(2);
+        let oracle = CachedVerificationOracle::new(
+            MockVerificationOracle { verified: HashSet::from([verified]), ..Default::default() },
+            Duration::from_secs(60),
+        );
+
+        assert!(oracle.is_verified(verified).await);
+        assert!(!oracle.is_verified(unverified).await);
+        assert_eq!(*oracle.oracle.lookups.lock(), 2);
+
+        // results are cached
+        assert!(oracle.is_verified(verified).await);
+        assert!(!oracle.is_verified(unverified).await);
+        assert_eq!(*oracle.oracle.lookups.lock(), 2);
+
+        assert!(NoopVerificationOracle.is_verified(unverified).await);
+    }
+}
@@ -0,0 +1,152 @@
+//! Detection of suspicious request patterns, e.g. scripted sybil farms.
+
+use alloy_primitives::{keccak256, Address, Bytes, B256};
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// The maximum number of distinct calldata tracked by the [`RepeatedCalldataDetector`].
+const MAX_TRACKED_CALLDATA: usize = 1024;
+
+/// The metadata of a request seen by a [`PatternDetector`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestMetadata {
+    /// The account the request calls, i.e. the delegated EOA.
+    pub account: Option<Address>,
+    /// The calldata of the request.
+    pub input: Bytes,
+}
+
+/// A detector that scores requests by how closely they match suspicious patterns.
+///
+/// The detector observes every `wallet_sendTransaction` request that passed validation, and
+/// returns a risk score in `0.0..=1.0`. Requests scoring at or above the configured threshold are
+/// rejected.
+pub trait PatternDetector: Debug + Send + Sync {
+    /// Records the request and returns its risk score.
+    fn score(&self, request: &RequestMetadata, now: Instant) -> f64;
+}
+
+/// Settings for pattern detection.
+#[derive(Debug, Clone)]
+pub struct PatternDetection {
+    /// The detector scoring requests.
+    pub detector: Arc<dyn PatternDetector>,
+    /// The risk score at or above which requests are rejected.
+    pub threshold: f64,
+}
+
+/// A [`PatternDetector`] that flags identical calldata sent to many distinct accounts.
+///
+/// A scripted farm of fresh EOAs typically makes each account perform the exact same call. The
+/// score is the number of distinct accounts that received the same calldata within the window,
+/// relative to `max_accounts`. Requests without calldata are not scored.
+#[derive(Debug)]
+pub struct RepeatedCalldataDetector {
+    window: Duration,
+    max_accounts: usize,
+    /// The accounts that received each calldata, and when they last did.
+    seen: Mutex<HashMap<B256, Vec<(Address, Instant)>>>,
+}
+
+impl RepeatedCalldataDetector {
+    /// Creates a new detector that scores `1.0` once `max_accounts` distinct accounts received the
+    /// same calldata within `window`.
+    pub fn new(window: Duration, max_accounts: usize) -> Self {
+        Self { window, max_accounts: max_accounts.max(1), seen: Default::default() }
+    }
+}
+
+impl PatternDetector for RepeatedCalldataDetector {
+    fn score(&self, request: &RequestMetadata, now: Instant) -> f64 {
+        let Some(account) = request.account else {
+            return 0.0;
+        };
+        if request.input.is_empty() {
+            return 0.0;
+        }
+
+        let key = keccak256(&request.input);
+        let mut seen = self.seen.lock();
+
+        if !seen.contains_key(&key) && seen.len() >= MAX_TRACKED_CALLDATA {
+            let window = self.window;
+            seen.retain(|_, accounts| {
+                accounts.iter().any(|(_, at)| now.saturating_duration_since(*at) < window)
+            });
+
+            if seen.len() >= MAX_TRACKED_CALLDATA {
+                let oldest = seen
+                    .iter()
+                    .min_by_key(|(_, accounts)| accounts.iter().map(|(_, at)| *at).max())
+                    .map(|(key, _)| *key);
+                if let Some(oldest) = oldest {
+                    seen.remove(&oldest);
+                }
+            }
+        }
+
+        let accounts = seen.entry(key).or_default();
+        accounts.retain(|(seen, at)| {
+            *seen != account && now.saturating_duration_since(*at) < self.window
+        });
+        accounts.push((account, now));
+        // the score saturates at `max_accounts`, so there is no need to track more
+        if accounts.len() > self.max_accounts {
+            accounts.remove(0);
+        }
+
+        (accounts.len() as f64 / self.max_accounts as f64).min(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_calldata_farm() {
+        let detector = RepeatedCalldataDetector::new(Duration::from_secs(60), 10);
+        let now = Instant::now();
+        let claim = Bytes::from_static(&[0x4e, 0x71, 0xd9, 0x2d]);
+        let request = |account: u8, input: &Bytes| RequestMetadata {
+            account: Some(Address::repeat_byte(account)),
+            input: input.clone(),
+        };
+
+        // fresh accounts performing the same call
+        for account in 1..10 {
+            assert!(detector.score(&request(account, &claim), now) < 1.0);
+        }
+        assert_eq!(detector.score(&request(10, &claim), now), 1.0);
+
+        // the same account repeating the call does not count twice
+        let detector = RepeatedCalldataDetector::new(Duration::from_secs(60), 10);
+        for _ in 0..20 {
+            assert_eq!(detector.score(&request(1, &claim), now), 0.1);
+        }
+
+        // other calldata is scored separately
+        assert_eq!(detector.score(&request(2, &Bytes::from_static(&[1])), now), 0.1);
+
+        // calls without calldata are not scored
+        assert_eq!(detector.score(&request(3, &Bytes::new()), now), 0.0);
+    }
+
+    #[test]
+    fn forgets_after_window() {
+        let detector = RepeatedCalldataDetector::new(Duration::from_secs(60), 2);
+        let now = Instant::now();
+        let request = |account: u8| RequestMetadata {
+            account: Some(Address::repeat_byte(account)),
+            input: Bytes::from_static(&[1, 2, 3, 4]),
+        };
+
+        assert_eq!(detector.score(&request(1), now), 0.5);
+        assert_eq!(detector.score(&request(2), now + Duration::from_secs(61)), 0.5);
+    }
+}
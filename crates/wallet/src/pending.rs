@@ -0,0 +1,263 @@
+//! Detection of conflicting EIP-7702 delegations of the same authority.
+//!
+//! If an authority delegates to a target, and shortly after to another target, the second
+//! delegation supersedes the first, which was sponsored in vain. The most recent delegation of
+//! each authority is tracked for a while after its submission, as it is likely still pending.
+
+use crate::OdysseyWalletError;
+use alloy_primitives::Address;
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+use tracing::debug;
+
+/// How a delegation conflicting with a pending delegation of the same authority is handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictingDelegations {
+    /// The delegation is rejected with
+    /// [`OdysseyWalletError::ConflictingPendingDelegation`].
+    Reject,
+    /// The delegation is sponsored, and replaces the pending delegation as the one tracked.
+    ///
+    /// The transaction of the pending delegation is not cancelled.
+    Replace,
+}
+
+/// Settings for detecting conflicting delegations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PendingDelegationTracking {
+    /// How long a delegation is considered pending after its submission.
+    pub expiry: Duration,
+    /// The maximum number of tracked authorities, the oldest delegations are forgotten first.
+    pub max_tracked: usize,
+    /// How conflicting delegations are handled.
+    pub on_conflict: ConflictingDelegations,
+}
+
+/// A submitted delegation.
+#[derive(Debug, Clone, Copy)]
+struct PendingDelegation {
+    target: Address,
+    submitted_at: Instant,
+}
+
+/// Tracks the most recent delegation of each authority.
+#[derive(Debug)]
+pub(crate) struct PendingDelegations {
+    settings: PendingDelegationTracking,
+    pending: Mutex<HashMap<Address, PendingDelegation>>,
+}
+
+impl PendingDelegations {
+    /// Creates a new tracker with the given settings.
+    pub(crate) fn new(settings: PendingDelegationTracking) -> Self {
+        Self { settings, pending: Default::default() }
+    }
+
+    /// Tracks the delegations, given as pairs of authority and target.
+    ///
+    /// If any delegation conflicts with a pending delegation of its authority and conflicts are
+    /// rejected, no delegation is tracked, and the error is returned. Otherwise, the delegations
+    /// are untracked again unless the returned reservation is committed.
+    pub(crate) fn try_track(
+        &self,
+        delegations: &[(Address, Address)],
+        now: Instant,
+    ) -> Result<PendingReservation<'_>, OdysseyWalletError> {
+        let mut pending = self.pending.lock();
+        pending.retain(|_, delegation| {
+            now.saturating_duration_since(delegation.submitted_at) < self.settings.expiry
+        });
+
+        for (authority, target) in delegations {
+            let Some(previous) =
+                pending.get(authority).filter(|previous| previous.target != *target)
+            else {
+                continue;
+            };
+            match self.settings.on_conflict {
+                ConflictingDelegations::Reject => {
+                    return Err(OdysseyWalletError::ConflictingPendingDelegation {
+                        authority: *authority,
+                        pending: previous.target,
+                    });
+                }
+                ConflictingDelegations::Replace => {
+                    debug!(
+                        target: "rpc::wallet",
+                        %authority,
+                        pending = %previous.target,
+                        %target,
+                        "Replacing pending delegation"
+                    );
+                }
+            }
+        }
+
+        let mut replaced = Vec::with_capacity(delegations.len());
+        for (authority, target) in delegations {
+            let delegation = PendingDelegation { target: *target, submitted_at: now };
+            let previous = pending.insert(*authority, delegation);
+            if !replaced.iter().any(|(replaced, _)| replaced == authority) {
+                replaced.push((*authority, previous));
+            }
+        }
+        while pending.len() > self.settings.max_tracked {
+            let Some(oldest) = pending
+                .iter()
+                .min_by_key(|(_, delegation)| delegation.submitted_at)
+                .map(|(authority, _)| *authority)
+            else {
+                break;
+            };
+            pending.remove(&oldest);
+        }
+        Ok(PendingReservation { delegations: self, replaced, at: now, committed: false })
+    }
+
+    /// Untracks the delegations tracked at `at`, tracking the delegations they replaced again.
+    fn release(&self, replaced: &[(Address, Option<PendingDelegation>)], at: Instant) {
+        let mut pending = self.pending.lock();
+        for (authority, previous) in replaced {
+            // the delegation may have been replaced or forgotten in the meantime
+            if pending.get(authority).is_none_or(|delegation| delegation.submitted_at != at) {
+                continue;
+            }
+            match previous {
+                Some(previous) => pending.insert(*authority, *previous),
+                None => pending.remove(authority),
+            };
+        }
+    }
+}
+
+/// Delegations tracked for a request.
+///
+/// If the reservation is dropped without being [committed](Self::commit), the delegations are
+/// untracked, e.g. because the transaction was rejected or its submission failed.
+#[derive(Debug)]
+pub(crate) struct PendingReservation<'a> {
+    delegations: &'a PendingDelegations,
+    /// The authorities, and the delegations tracked for them before.
+    replaced: Vec<(Address, Option<PendingDelegation>)>,
+    at: Instant,
+    committed: bool,
+}
+
+impl PendingReservation<'_> {
+    /// Keeps the delegations tracked, as the transaction was submitted.
+    pub(crate) fn commit(mut self) {
+        self.committed = true;
+    }
+}
+
+impl Drop for PendingReservation<'_> {
+    fn drop(&mut self) {
+        if !self.committed {
+            self.delegations.release(&self.replaced, self.at);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tracks the delegations of a submitted transaction.
+    fn track(
+        pending: &PendingDelegations,
+        delegations: &[(Address, Address)],
+        now: Instant,
+    ) -> Result<(), OdysseyWalletError> {
+        pending.try_track(delegations, now).map(PendingReservation::commit)
+    }
+
+    fn tracker(on_conflict: ConflictingDelegations) -> PendingDelegations {
+        PendingDelegations::new(PendingDelegationTracking {
+            expiry: Duration::from_secs(30),
+            max_tracked: 2,
+            on_conflict,
+        })
+    }
+
+    #[test]
+    fn rejects_conflicting_delegations() {
+        let (alice, bob) = (Address::with_last_byte(0xa), Address::with_last_byte(0xb));
+        let (first, second) = (Address::with_last_byte(1), Address::with_last_byte(2));
+        let pending = tracker(ConflictingDelegations::Reject);
+        let now = Instant::now();
+
+        assert_eq!(track(&pending, &[(alice, first)], now), Ok(()));
+        // delegating to the same target again is not a conflict
+        assert_eq!(track(&pending, &[(alice, first)], now), Ok(()));
+        assert_eq!(
+            track(&pending, &[(bob, first), (alice, second)], now),
+            Err(OdysseyWalletError::ConflictingPendingDelegation {
+                authority: alice,
+                pending: first
+            })
+        );
+        // nothing was tracked for the rejected transaction
+        assert_eq!(track(&pending, &[(bob, second)], now), Ok(()));
+
+        // the pending delegation expires
+        let later = now + Duration::from_secs(30);
+        assert_eq!(track(&pending, &[(alice, second)], later), Ok(()));
+    }
+
+    #[test]
+    fn replaces_conflicting_delegations() {
+        let alice = Address::with_last_byte(0xa);
+        let (first, second) = (Address::with_last_byte(1), Address::with_last_byte(2));
+        let pending = tracker(ConflictingDelegations::Replace);
+        let now = Instant::now();
+
+        assert_eq!(track(&pending, &[(alice, first)], now), Ok(()));
+        assert_eq!(track(&pending, &[(alice, second)], now), Ok(()));
+        assert_eq!(pending.pending.lock()[&alice].target, second);
+    }
+
+    #[test]
+    fn untracks_unsubmitted_delegations() {
+        let alice = Address::with_last_byte(0xa);
+        let (first, second, third) =
+            (Address::with_last_byte(1), Address::with_last_byte(2), Address::with_last_byte(3));
+        let now = Instant::now();
+
+        // the submission failed, so a retry to another target is no conflict
+        let pending = tracker(ConflictingDelegations::Reject);
+        drop(pending.try_track(&[(alice, first)], now).unwrap());
+        assert_eq!(track(&pending, &[(alice, second)], now), Ok(()));
+        assert_eq!(pending.pending.lock()[&alice].target, second);
+
+        // the replaced delegation is pending again
+        let pending = tracker(ConflictingDelegations::Replace);
+        assert_eq!(track(&pending, &[(alice, first)], now), Ok(()));
+        drop(pending.try_track(&[(alice, second)], now + Duration::from_secs(1)).unwrap());
+        assert_eq!(pending.pending.lock()[&alice].target, first);
+
+        // unless a later delegation replaced it in the meantime
+        let reservation =
+            pending.try_track(&[(alice, second)], now + Duration::from_secs(2)).unwrap();
+        assert_eq!(track(&pending, &[(alice, third)], now + Duration::from_secs(3)), Ok(()));
+        drop(reservation);
+        assert_eq!(pending.pending.lock()[&alice].target, third);
+    }
+
+    #[test]
+    fn bounds_tracked_authorities() {
+        let target = Address::with_last_byte(1);
+        let pending = tracker(ConflictingDelegations::Reject);
+        let now = Instant::now();
+
+        for (i, authority) in (0xa..0xd).map(Address::with_last_byte).enumerate() {
+            let at = now + Duration::from_secs(i as u64);
+            assert_eq!(track(&pending, &[(authority, target)], at), Ok(()));
+        }
+        let tracked = pending.pending.lock();
+        assert_eq!(tracked.len(), 2);
+        assert!(!tracked.contains_key(&Address::with_last_byte(0xa)));
+    }
+}
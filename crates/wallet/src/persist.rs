@@ -0,0 +1,176 @@
+//! Persistence of the anti-abuse state across restarts.
+//!
+//! The sponsorship budget, the spending cap, the destination quotas and the account rate limits are
+//! kept in memory, so without persistence they reset on every restart. A [`StateStore`] holds a
+//! snapshot of them, which the wallet restores when it is constructed.
+//!
+//! The connection rate limiter is not persisted, as connection ids are reassigned after a restart.
+
+use alloy_primitives::{Address, U256};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    fs, io,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+use tracing::warn;
+
+/// The version of the [`StateSnapshot`] schema.
+///
+/// Snapshots of other versions are ignored on restore.
+pub const STATE_SNAPSHOT_VERSION: u32 = 1;
+
+/// A snapshot of the anti-abuse state of the wallet.
+///
+/// Points in time are stored as seconds since the unix epoch, so the budget refills and the
+/// quota windows roll over while the wallet is down.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateSnapshot {
+    /// The version of the schema, see [`STATE_SNAPSHOT_VERSION`].
+    pub version: u32,
+    /// When the snapshot was taken.
+    pub taken_at: u64,
+    /// The remaining sponsorship budget in wei, if the budget is enabled.
+    pub budget: Option<U256>,
+    /// When each delegation target with a quota was called within the quota window.
+    pub destination_quotas: HashMap<Address, Vec<u64>>,
+    /// The spending within the current window of the spending cap, if the cap is enabled.
+    #[serde(default)]
+    pub spending_cap: Option<SpendingCapSnapshot>,
+    /// The requests of each account within its rate limit window.
+    #[serde(default)]
+    pub account_rate_limits: HashMap<Address, RateLimitWindowSnapshot>,
+}
+
+/// The spending within the current window of the spending cap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpendingCapSnapshot {
+    /// The amount of wei spent within the window.
+    pub spent: U256,
+    /// When the window started.
+    pub window_start: u64,
+}
+
+/// The requests of an account within its rate limit window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimitWindowSnapshot {
+    /// When the window started.
+    pub started_at: u64,
+    /// The number of requests within the window.
+    pub requests: u32,
+    /// The number of consecutive violations, and when the last one occurred.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub violations: Option<(u32, u64)>,
+}
+
+/// Storage for [`StateSnapshot`]s.
+pub trait StateStore: Debug + Send + Sync {
+    /// Loads the latest snapshot, if any.
+    fn load(&self) -> io::Result<Option<StateSnapshot>>;
+
+    /// Replaces the latest snapshot.
+    fn save(&self, snapshot: &StateSnapshot) -> io::Result<()>;
+}
+
+/// A [`StateStore`] backed by a JSON file.
+///
+/// Snapshots are written to a temporary file next to it, which then replaces the file, so a crash
+/// during a write never leaves a partial snapshot behind.
+#[derive(Debug, Clone)]
+pub struct FileStateStore {
+    path: PathBuf,
+}
+
+impl FileStateStore {
+    /// Creates a store backed by the file at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl StateStore for FileStateStore {
+    fn load(&self) -> io::Result<Option<StateSnapshot>> {
+        /// The part of the schema shared by all versions.
+        #[derive(Deserialize)]
+        struct Versioned {
+            version: u32,
+        }
+
+        let contents = match fs::read(&self.path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let Versioned { version } = serde_json::from_slice(&contents)?;
+        if version != STATE_SNAPSHOT_VERSION {
+            warn!(target: "rpc::wallet", version, path = %self.path.display(), "Ignoring state snapshot of unsupported version");
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_slice(&contents)?))
+    }
+
+    fn save(&self, snapshot: &StateSnapshot) -> io::Result<()> {
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        fs::write(&tmp, serde_json::to_vec(snapshot)?)?;
+        fs::rename(&tmp, &self.path)
+    }
+}
+
+/// Converts `at` to seconds since the unix epoch, given the current time in both clocks.
+pub(crate) fn to_unix(at: Instant, now: Instant, now_unix: u64) -> u64 {
+    now_unix.saturating_sub(now.saturating_duration_since(at).as_secs())
+}
+
+/// Converts seconds since the unix epoch to an [`Instant`], given the current time in both clocks.
+///
+/// Times that cannot be represented are clamped to `now`, which errs on the side of keeping them
+/// within any window.
+pub(crate) fn from_unix(at: u64, now: Instant, now_unix: u64) -> Instant {
+    now.checked_sub(Duration::from_secs(now_unix.saturating_sub(at))).unwrap_or(now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignores_other_versions() {
+        let path = std::env::temp_dir().join(format!("odyssey-state-{}.json", std::process::id()));
+        let store = FileStateStore::new(&path);
+        assert_eq!(store.load().unwrap(), None);
+
+        let snapshot = StateSnapshot {
+            version: STATE_SNAPSHOT_VERSION,
+            taken_at: 1_000,
+            budget: Some(U256::from(5)),
+            destination_quotas: HashMap::from([(Address::with_last_byte(1), vec![900, 950])]),
+            spending_cap: Some(SpendingCapSnapshot { spent: U256::from(3), window_start: 500 }),
+            account_rate_limits: HashMap::from([(
+                Address::with_last_byte(2),
+                RateLimitWindowSnapshot {
+                    started_at: 990,
+                    requests: 4,
+                    violations: Some((1, 995)),
+                },
+            )]),
+        };
+        store.save(&snapshot).unwrap();
+        assert_eq!(store.load().unwrap(), Some(snapshot));
+
+        // a future schema is not misread
+        fs::write(&path, r#"{"version":2,"budgets":[]}"#).unwrap();
+        assert_eq!(store.load().unwrap(), None);
+
+        // a corrupt snapshot is an error
+        fs::write(&path, "{").unwrap();
+        assert!(store.load().is_err());
+
+        fs::remove_file(path).unwrap();
+    }
+}
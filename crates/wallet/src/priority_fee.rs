@@ -0,0 +1,54 @@
+//! The priority fee of sponsored transactions.
+
+/// The default priority fee per gas of sponsored transactions, in wei.
+pub const DEFAULT_PRIORITY_FEE: u128 = 1_000_000_000; // 1 gwei
+
+/// How the priority fee per gas of sponsored transactions is determined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriorityFee {
+    /// A fixed priority fee per gas, in wei.
+    Fixed(u128),
+    /// The priority fee per gas suggested by the node from recent fee history, clamped to
+    /// `min..=max`.
+    ///
+    /// If the suggestion is not available, `min` is used.
+    Dynamic {
+        /// The minimum priority fee per gas, in wei.
+        min: u128,
+        /// The maximum priority fee per gas, in wei.
+        max: u128,
+    },
+}
+
+impl Default for PriorityFee {
+    fn default() -> Self {
+        Self::Fixed(DEFAULT_PRIORITY_FEE)
+    }
+}
+
+impl PriorityFee {
+    /// Returns the priority fee per gas, given the fee suggested by the node, if available.
+    pub(crate) fn resolve(&self, suggested: Option<u128>) -> u128 {
+        match *self {
+            Self::Fixed(fee) => fee,
+            Self::Dynamic { min, max } => suggested.map_or(min, |fee| fee.clamp(min, max)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_priority_fee() {
+        assert_eq!(PriorityFee::default().resolve(Some(5)), DEFAULT_PRIORITY_FEE);
+        assert_eq!(PriorityFee::Fixed(7).resolve(None), 7);
+
+        let dynamic = PriorityFee::Dynamic { min: 10, max: 100 };
+        assert_eq!(dynamic.resolve(Some(50)), 50);
+        assert_eq!(dynamic.resolve(Some(1)), 10);
+        assert_eq!(dynamic.resolve(Some(1_000)), 100);
+        assert_eq!(dynamic.resolve(None), 10);
+    }
+}
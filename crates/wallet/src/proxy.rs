@@ -0,0 +1,110 @@
+//! Stricter rules for delegation targets that are upgradeable proxies.
+//!
+//! A whitelisted proxy can be upgraded to arbitrary logic without its address changing, so
+//! whitelisting the proxy alone does not pin the code delegated accounts run. Proxies are detected
+//! by their [EIP-1967][eip-1967] implementation slot.
+//!
+//! [eip-1967]: https://eips.ethereum.org/EIPS/eip-1967
+
+use crate::{whitelist::DelegationWhitelist, OdysseyWalletError};
+use alloy_primitives::{b256, Address, B256, U256};
+
+/// The EIP-1967 implementation slot, i.e. `keccak256("eip1967.proxy.implementation") - 1`.
+pub const EIP1967_IMPLEMENTATION_SLOT: B256 =
+    b256!("360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc");
+
+/// Rules for requests to accounts delegating to an upgradeable proxy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProxyDelegationPolicy {
+    /// The gas ceiling for such requests, if tighter than the ceiling that applies otherwise.
+    pub gas_ceiling: Option<u64>,
+    /// Whether the current implementation of the proxy must be whitelisted too.
+    ///
+    /// Without a delegation whitelist, every implementation is allowed.
+    pub require_whitelisted_implementation: bool,
+}
+
+impl ProxyDelegationPolicy {
+    /// Returns the gas ceiling for a request to an account delegating to a proxy, given the
+    /// ceiling that applies otherwise.
+    pub(crate) fn resolve_ceiling(&self, ceiling: u64) -> u64 {
+        self.gas_ceiling.map_or(ceiling, |proxy_ceiling| ceiling.min(proxy_ceiling))
+    }
+
+    /// Validates a delegation target against the policy, given the value of its implementation
+    /// slot.
+    ///
+    /// Returns `true` if the target is a proxy.
+    pub(crate) fn validate(
+        &self,
+        target: Address,
+        implementation_slot: Option<U256>,
+        whitelist: Option<&DelegationWhitelist>,
+    ) -> Result<bool, OdysseyWalletError> {
+        let Some(implementation) = implementation(implementation_slot) else {
+            return Ok(false);
+        };
+        if self.require_whitelisted_implementation
+            && whitelist.is_some_and(|whitelist| !whitelist.contains(&implementation))
+        {
+            return Err(OdysseyWalletError::ProxyImplementationNotWhitelisted {
+                proxy: target,
+                implementation,
+            });
+        }
+        Ok(true)
+    }
+}
+
+/// Returns the implementation stored in an EIP-1967 implementation slot, if any.
+fn implementation(slot: Option<U256>) -> Option<Address> {
+    let slot = slot.filter(|slot| !slot.is_zero())?;
+    Some(Address::from_word(slot.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DelegationCapability;
+
+    #[test]
+    fn requires_whitelisted_implementation() {
+        let proxy = Address::with_last_byte(1);
+        let approved = Address::with_last_byte(2);
+        let upgraded = Address::with_last_byte(3);
+        let whitelist =
+            DelegationWhitelist::new(&DelegationCapability { addresses: vec![proxy, approved] });
+        let slot = |implementation: Address| Some(U256::from_be_slice(implementation.as_slice()));
+        let policy =
+            ProxyDelegationPolicy { gas_ceiling: None, require_whitelisted_implementation: true };
+
+        assert_eq!(policy.validate(proxy, slot(approved), Some(&whitelist)), Ok(true));
+        assert_eq!(
+            policy.validate(proxy, slot(upgraded), Some(&whitelist)),
+            Err(OdysseyWalletError::ProxyImplementationNotWhitelisted {
+                proxy,
+                implementation: upgraded
+            })
+        );
+        // targets with an empty implementation slot are not proxies
+        assert_eq!(policy.validate(proxy, Some(U256::ZERO), Some(&whitelist)), Ok(false));
+        assert_eq!(policy.validate(proxy, None, Some(&whitelist)), Ok(false));
+
+        // without a whitelist, or if not required, any implementation is allowed
+        assert_eq!(policy.validate(proxy, slot(upgraded), None), Ok(true));
+        let lenient = ProxyDelegationPolicy { require_whitelisted_implementation: false, ..policy };
+        assert_eq!(lenient.validate(proxy, slot(upgraded), Some(&whitelist)), Ok(true));
+    }
+
+    #[test]
+    fn applies_tighter_ceiling() {
+        let policy = ProxyDelegationPolicy {
+            gas_ceiling: Some(100_000),
+            require_whitelisted_implementation: false,
+        };
+        assert_eq!(policy.resolve_ceiling(350_000), 100_000);
+        assert_eq!(policy.resolve_ceiling(50_000), 50_000);
+        let unchanged = ProxyDelegationPolicy { gas_ceiling: None, ..policy };
+        assert_eq!(unchanged.resolve_ceiling(350_000), 350_000);
+    }
+}
@@ -0,0 +1,188 @@
+//! Daily limits on the number of sponsored calls to specific delegation targets.
+
+use alloy_primitives::Address;
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+/// The rolling window over which the calls to a delegation target are counted.
+pub(crate) const QUOTA_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Tracks the sponsored calls to delegation targets with a daily quota.
+#[derive(Debug)]
+pub(crate) struct DestinationQuotas {
+    /// The maximum number of calls per target within the window.
+    quotas: HashMap<Address, u32>,
+    /// When each target was called within the window, oldest first.
+    calls: Mutex<HashMap<Address, VecDeque<Instant>>>,
+}
+
+impl DestinationQuotas {
+    /// Creates a new tracker for the given quotas.
+    pub(crate) fn new(quotas: HashMap<Address, u32>) -> Self {
+        Self { quotas, calls: Default::default() }
+    }
+
+    /// Records a call to each of the targets.
+    ///
+    /// If any target exceeded its quota, no call is recorded, and the first such target is
+    /// returned. Otherwise, the calls are released again unless the returned reservation is
+    /// committed.
+    pub(crate) fn try_consume(
+        &self,
+        targets: &[Address],
+        now: Instant,
+    ) -> Result<QuotaReservation<'_>, Address> {
+        let mut calls = self.calls.lock();
+        let mut consumed = Vec::with_capacity(targets.len());
+        for target in targets {
+            let Some(quota) = self.quotas.get(target) else {
+                continue;
+            };
+            if consumed.contains(target) {
+                continue;
+            }
+
+            let target_calls = calls.entry(*target).or_default();
+            prune(target_calls, now);
+            if target_calls.len() >= *quota as usize {
+                return Err(*target);
+            }
+            consumed.push(*target);
+        }
+
+        for target in &consumed {
+            calls.entry(*target).or_default().push_back(now);
+        }
+        Ok(QuotaReservation { quotas: self, targets: consumed, at: now, committed: false })
+    }
+
+    /// Removes the calls to the targets recorded at `at`.
+    fn release(&self, targets: &[Address], at: Instant) {
+        let mut calls = self.calls.lock();
+        for target in targets {
+            let Some(target_calls) = calls.get_mut(target) else {
+                continue;
+            };
+            if let Some(position) = target_calls.iter().rposition(|call| *call == at) {
+                target_calls.remove(position);
+            }
+        }
+    }
+
+    /// Returns the number of calls left for the target within the window, or `None` if it has no
+    /// quota.
+    pub(crate) fn remaining(&self, target: &Address, now: Instant) -> Option<u32> {
+        let quota = *self.quotas.get(target)?;
+        let mut calls = self.calls.lock();
+        let used = calls.get_mut(target).map_or(0, |calls| {
+            prune(calls, now);
+            calls.len()
+        });
+        Some(quota.saturating_sub(used as u32))
+    }
+
+    /// Returns when each target was called within the window.
+    pub(crate) fn calls(&self, now: Instant) -> HashMap<Address, Vec<Instant>> {
+        let mut calls = self.calls.lock();
+        calls
+            .iter_mut()
+            .filter_map(|(target, target_calls)| {
+                prune(target_calls, now);
+                (!target_calls.is_empty())
+                    .then(|| (*target, target_calls.iter().copied().collect()))
+            })
+            .collect()
+    }
+
+    /// Replaces the recorded calls, e.g. with a snapshot taken before a restart.
+    ///
+    /// Calls to targets without a quota are ignored.
+    pub(crate) fn restore(&self, restored: HashMap<Address, Vec<Instant>>) {
+        let mut calls = self.calls.lock();
+        calls.clear();
+        for (target, mut target_calls) in restored {
+            if self.quotas.contains_key(&target) {
+                target_calls.sort_unstable();
+                calls.insert(target, target_calls.into());
+            }
+        }
+    }
+}
+
+/// Calls recorded for a request.
+///
+/// If the reservation is dropped without being [committed](Self::commit), the calls are released,
+/// e.g. because the transaction was rejected or its submission failed.
+#[derive(Debug)]
+pub(crate) struct QuotaReservation<'a> {
+    quotas: &'a DestinationQuotas,
+    targets: Vec<Address>,
+    at: Instant,
+    committed: bool,
+}
+
+impl QuotaReservation<'_> {
+    /// Keeps the recorded calls, as the transaction was submitted.
+    pub(crate) fn commit(mut self) {
+        self.committed = true;
+    }
+}
+
+impl Drop for QuotaReservation<'_> {
+    fn drop(&mut self) {
+        if !self.committed {
+            self.quotas.release(&self.targets, self.at);
+        }
+    }
+}
+
+/// Removes the calls that fell out of the window.
+fn prune(calls: &mut VecDeque<Instant>, now: Instant) {
+    while calls.front().is_some_and(|at| now.saturating_duration_since(*at) >= QUOTA_WINDOW) {
+        calls.pop_front();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enforces_daily_quota() {
+        let claim = Address::with_last_byte(1);
+        let other = Address::with_last_byte(2);
+        let quotas = DestinationQuotas::new(HashMap::from([(claim, 2)]));
+        let now = Instant::now();
+
+        let consume = |targets: &[Address], at| quotas.try_consume(targets, at).map(|r| r.commit());
+        assert_eq!(quotas.remaining(&claim, now), Some(2));
+        assert_eq!(quotas.remaining(&other, now), None);
+
+        // up to the quota
+        assert_eq!(consume(&[claim], now), Ok(()));
+        assert_eq!(consume(&[claim, claim], now), Ok(()));
+        assert_eq!(quotas.remaining(&claim, now), Some(0));
+        assert_eq!(consume(&[claim], now), Err(claim));
+
+        // targets without a quota are unlimited, but nothing is recorded if any target is over
+        assert_eq!(consume(&[other], now), Ok(()));
+        assert_eq!(consume(&[other, claim], now), Err(claim));
+
+        // the window is rolling
+        let later = now + QUOTA_WINDOW - Duration::from_secs(1);
+        assert_eq!(consume(&[claim], later), Err(claim));
+        let later = now + QUOTA_WINDOW;
+        assert_eq!(quotas.remaining(&claim, later), Some(2));
+        assert_eq!(consume(&[claim], later), Ok(()));
+        assert_eq!(quotas.remaining(&claim, later), Some(1));
+
+        // calls that are not committed are released
+        let reservation = quotas.try_consume(&[claim], later).unwrap();
+        assert_eq!(quotas.remaining(&claim, later), Some(0));
+        drop(reservation);
+        assert_eq!(quotas.remaining(&claim, later), Some(1));
+    }
+}
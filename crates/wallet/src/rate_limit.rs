@@ -0,0 +1,383 @@
+//! Rate limiting of requests to the `wallet_` namespace.
+
+use parking_lot::Mutex;
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    hash::Hash,
+    time::{Duration, Instant},
+};
+
+/// Settings for a [`RateLimiter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitConfig {
+    /// The maximum number of requests per key within a window.
+    pub max_requests: u32,
+    /// The length of a window.
+    pub window: Duration,
+    /// The maximum number of keys tracked at once.
+    ///
+    /// Once reached, expired windows are evicted, and if none have expired, the key with the
+    /// oldest window is evicted.
+    pub max_tracked: usize,
+    /// Escalating backoff advice for keys that repeatedly exceed their limit.
+    ///
+    /// If `None`, keys are advised to retry once their window ends.
+    pub backoff: Option<RateLimitBackoff>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            max_requests: 10,
+            window: Duration::from_secs(60),
+            max_tracked: 10_000,
+            backoff: None,
+        }
+    }
+}
+
+/// Settings for exponential backoff advice for keys that repeatedly exceed their limit.
+///
+/// The advised delay doubles with each consecutive violation, starting at `base` and capped at
+/// `max`, but never ends before the current window. The escalation resets once a key did not
+/// exceed its limit for `quiet_period`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitBackoff {
+    /// The delay advised after the first violation.
+    pub base: Duration,
+    /// The maximum advised delay.
+    pub max: Duration,
+    /// The time without violations after which the escalation resets.
+    pub quiet_period: Duration,
+}
+
+impl Default for RateLimitBackoff {
+    fn default() -> Self {
+        Self {
+            base: Duration::from_secs(1),
+            max: Duration::from_secs(300),
+            quiet_period: Duration::from_secs(600),
+        }
+    }
+}
+
+/// Settings for the [`GlobalRateLimiter`].
+///
+/// The limiter is a token bucket: it holds up to `burst` requests, and refills at
+/// `requests_per_second`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlobalRateLimitConfig {
+    /// The sustained number of requests per second across all callers.
+    pub requests_per_second: u32,
+    /// The maximum number of requests admitted at once.
+    pub burst: u32,
+}
+
+impl Default for GlobalRateLimitConfig {
+    fn default() -> Self {
+        Self { requests_per_second: 100, burst: 200 }
+    }
+}
+
+/// The state of the [`GlobalRateLimiter`].
+#[derive(Debug)]
+struct GlobalBucket {
+    /// The requests that may currently be admitted.
+    tokens: f64,
+    /// When the bucket was last refilled.
+    last_refill: Instant,
+    /// When the current one second window started.
+    window_started_at: Instant,
+    /// The requests admitted within the current one second window.
+    window_requests: u32,
+}
+
+/// A single token bucket limiting requests across all callers.
+#[derive(Debug)]
+pub(crate) struct GlobalRateLimiter {
+    config: GlobalRateLimitConfig,
+    bucket: Mutex<GlobalBucket>,
+}
+
+impl GlobalRateLimiter {
+    /// Creates a new, full limiter.
+    pub(crate) const fn new(config: GlobalRateLimitConfig, now: Instant) -> Self {
+        Self {
+            config,
+            bucket: Mutex::new(GlobalBucket {
+                tokens: config.burst as f64,
+                last_refill: now,
+                window_started_at: now,
+                window_requests: 0,
+            }),
+        }
+    }
+
+    /// Admits a request if the bucket has a token left.
+    ///
+    /// Returns the number of requests admitted within the current second, or `None` if the
+    /// request was not admitted.
+    pub(crate) fn try_acquire(&self, now: Instant) -> Option<u32> {
+        let mut bucket = self.bucket.lock();
+
+        let elapsed = now.saturating_duration_since(bucket.last_refill);
+        bucket.tokens = elapsed
+            .as_secs_f64()
+            .mul_add(self.config.requests_per_second as f64, bucket.tokens)
+            .min(self.config.burst as f64);
+        bucket.last_refill = now.max(bucket.last_refill);
+        if now.saturating_duration_since(bucket.window_started_at) >= Duration::from_secs(1) {
+            bucket.window_started_at = now;
+            bucket.window_requests = 0;
+        }
+
+        if bucket.tokens < 1.0 {
+            return None;
+        }
+        bucket.tokens -= 1.0;
+        bucket.window_requests += 1;
+        Some(bucket.window_requests)
+    }
+}
+
+/// A request that exceeded the limit of its key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RateLimitExceeded {
+    /// How long the key is advised to wait before retrying.
+    pub(crate) retry_after: Duration,
+    /// The number of consecutive violations of the key, starting at 1.
+    pub(crate) escalation: u32,
+}
+
+/// The requests counted for a key within its current window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Window {
+    /// When the window started.
+    pub(crate) started_at: Instant,
+    /// The number of requests within the window.
+    pub(crate) requests: u32,
+    /// The number of consecutive violations, and when the last one occurred.
+    pub(crate) violations: Option<(u32, Instant)>,
+}
+
+/// A fixed window rate limiter with a bounded number of tracked keys.
+#[derive(Debug)]
+pub(crate) struct RateLimiter<K> {
+    config: RateLimitConfig,
+    windows: Mutex<HashMap<K, Window>>,
+}
+
+impl<K> RateLimiter<K>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Creates a new rate limiter.
+    pub(crate) fn new(config: RateLimitConfig) -> Self {
+        Self { config, windows: Default::default() }
+    }
+
+    /// Counts a request for the key, returning when to retry if the key exceeded its limit.
+    pub(crate) fn check(&self, key: K, now: Instant) -> Result<(), RateLimitExceeded> {
+        let mut windows = self.windows.lock();
+
+        if !windows.contains_key(&key) && windows.len() >= self.config.max_tracked {
+            windows.retain(|_, entry| !self.is_expired(entry, now));
+
+            if windows.len() >= self.config.max_tracked {
+                let oldest = windows
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.started_at)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    windows.remove(&oldest);
+                }
+            }
+        }
+
+        let entry =
+            windows.entry(key).or_insert(Window { started_at: now, requests: 0, violations: None });
+        if now.saturating_duration_since(entry.started_at) >= self.config.window {
+            entry.started_at = now;
+            entry.requests = 0;
+        }
+
+        if entry.requests < self.config.max_requests {
+            entry.requests += 1;
+            return Ok(());
+        }
+
+        let escalation = match (entry.violations, self.config.backoff) {
+            (Some((violations, at)), Some(backoff))
+                if now.saturating_duration_since(at) < backoff.quiet_period =>
+            {
+                violations.saturating_add(1)
+            }
+            _ => 1,
+        };
+        entry.violations = Some((escalation, now));
+
+        let window_end =
+            self.config.window.saturating_sub(now.saturating_duration_since(entry.started_at));
+        let retry_after = match self.config.backoff {
+            Some(backoff) => {
+                let factor = 1u32.checked_shl(escalation - 1).unwrap_or(u32::MAX);
+                backoff.base.saturating_mul(factor).min(backoff.max).max(window_end)
+            }
+            None => window_end,
+        };
+        Err(RateLimitExceeded { retry_after, escalation })
+    }
+
+    /// Returns the windows of the keys that still affect their limit.
+    pub(crate) fn windows(&self, now: Instant) -> HashMap<K, Window> {
+        self.windows
+            .lock()
+            .iter()
+            .filter(|(_, window)| !self.is_expired(window, now))
+            .map(|(key, window)| (key.clone(), *window))
+            .collect()
+    }
+
+    /// Replaces the tracked windows, e.g. with a snapshot taken before a restart.
+    ///
+    /// Beyond the maximum number of tracked keys, the keys with the oldest windows are dropped.
+    pub(crate) fn restore(&self, restored: HashMap<K, Window>) {
+        let mut restored: Vec<_> = restored.into_iter().collect();
+        restored.sort_unstable_by_key(|(_, window)| Reverse(window.started_at));
+        restored.truncate(self.config.max_tracked);
+        *self.windows.lock() = restored.into_iter().collect();
+    }
+
+    /// Returns `true` if the window has ended and its violations no longer escalate the backoff.
+    fn is_expired(&self, window: &Window, now: Instant) -> bool {
+        let quiet_period = self.config.backoff.map(|backoff| backoff.quiet_period);
+        now.saturating_duration_since(window.started_at) >= self.config.window
+            && !window.violations.zip(quiet_period).is_some_and(|((_, at), quiet_period)| {
+                now.saturating_duration_since(at) < quiet_period
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_per_key() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            max_requests: 2,
+            window: Duration::from_secs(10),
+            max_tracked: 2,
+            backoff: None,
+        });
+        let now = Instant::now();
+
+        assert!(limiter.check(1, now).is_ok());
+        assert!(limiter.check(1, now).is_ok());
+        assert_eq!(
+            limiter.check(1, now + Duration::from_secs(4)),
+            Err(RateLimitExceeded { retry_after: Duration::from_secs(6), escalation: 1 })
+        );
+
+        // other keys have their own limit
+        assert!(limiter.check(2, now).is_ok());
+
+        // the limit resets with the next window
+        assert!(limiter.check(1, now + Duration::from_secs(10)).is_ok());
+
+        // tracking a new key evicts expired windows
+        assert!(limiter.check(3, now + Duration::from_secs(11)).is_ok());
+        assert_eq!(limiter.windows.lock().len(), 2);
+        assert!(!limiter.windows.lock().contains_key(&2));
+    }
+
+    #[test]
+    fn restores_windows() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            max_requests: 1,
+            window: Duration::from_secs(10),
+            max_tracked: 2,
+            backoff: None,
+        });
+        let now = Instant::now();
+        assert!(limiter.check(1, now).is_ok());
+        assert!(limiter.check(2, now + Duration::from_secs(5)).is_ok());
+
+        // ended windows are not returned
+        let windows = limiter.windows(now + Duration::from_secs(12));
+        assert_eq!(windows.keys().collect::<Vec<_>>(), [&2]);
+
+        // the limit is kept after a restore, and only the newest windows are tracked
+        let restored = RateLimiter::new(limiter.config);
+        let window = |started_at| Window { started_at, requests: 1, violations: None };
+        restored.restore(HashMap::from([
+            (1, window(now)),
+            (2, window(now + Duration::from_secs(5))),
+            (3, window(now + Duration::from_secs(6))),
+        ]));
+        assert_eq!(restored.windows.lock().len(), 2);
+        assert!(restored.check(2, now + Duration::from_secs(6)).is_err());
+        assert!(restored.check(1, now + Duration::from_secs(6)).is_ok());
+    }
+
+    #[test]
+    fn limits_global_rate() {
+        let now = Instant::now();
+        let limiter = GlobalRateLimiter::new(
+            GlobalRateLimitConfig { requests_per_second: 10, burst: 5 },
+            now,
+        );
+
+        // the burst is admitted at once, then the limit is saturated
+        let admitted: Vec<_> = (0..8).map(|_| limiter.try_acquire(now)).collect();
+        assert_eq!(admitted, [Some(1), Some(2), Some(3), Some(4), Some(5), None, None, None]);
+
+        // refills at the sustained rate
+        let now = now + Duration::from_millis(200);
+        assert_eq!(limiter.try_acquire(now), Some(6));
+        assert_eq!(limiter.try_acquire(now), Some(7));
+        assert_eq!(limiter.try_acquire(now), None);
+
+        // up to the burst, and the rate is counted per second
+        let now = now + Duration::from_secs(10);
+        assert_eq!((0..6).filter_map(|_| limiter.try_acquire(now)).last(), Some(5));
+    }
+
+    #[test]
+    fn escalates_backoff_for_repeat_offenders() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            max_requests: 1,
+            window: Duration::from_secs(10),
+            max_tracked: 10,
+            backoff: Some(RateLimitBackoff {
+                base: Duration::from_secs(20),
+                max: Duration::from_secs(100),
+                quiet_period: Duration::from_secs(300),
+            }),
+        });
+        let now = Instant::now();
+        assert!(limiter.check(1, now).is_ok());
+
+        // the advised delay doubles with each violation, up to the maximum
+        let mut advised = Vec::new();
+        for _ in 0..4 {
+            let exceeded = limiter.check(1, now).unwrap_err();
+            advised.push((exceeded.retry_after.as_secs(), exceeded.escalation));
+        }
+        assert_eq!(advised, [(20, 1), (40, 2), (80, 3), (100, 4)]);
+
+        // violations in later windows keep escalating
+        let later = now + Duration::from_secs(60);
+        assert!(limiter.check(1, later).is_ok());
+        assert_eq!(limiter.check(1, later).unwrap_err().escalation, 5);
+
+        // the escalation resets after a quiet period
+        let quiet = later + Duration::from_secs(300);
+        assert!(limiter.check(1, quiet).is_ok());
+        assert_eq!(
+            limiter.check(1, quiet),
+            Err(RateLimitExceeded { retry_after: Duration::from_secs(20), escalation: 1 })
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! A delegation whitelist governed by an on-chain registry.
+//!
+//! The registry is a contract listing the approved delegation targets. The list is read
+//! periodically, and requests are validated against the last list that was read successfully.
+
+use crate::{whitelist::DelegationWhitelist, DelegationCapability};
+use alloy_primitives::{Address, Bytes, U256};
+use parking_lot::RwLock;
+use std::{fmt::Debug, sync::Arc, time::Duration};
+use tracing::warn;
+
+/// The selector of `getApprovedTargets()`, which returns the approved targets as `address[]`.
+pub const GET_APPROVED_TARGETS_SELECTOR: [u8; 4] = [0xae, 0x52, 0xc5, 0x69];
+
+/// Settings for loading the delegation whitelist from an on-chain registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WhitelistRegistryConfig {
+    /// The address of the registry contract.
+    pub address: Address,
+    /// How often the registry is read.
+    pub refresh_interval: Duration,
+}
+
+/// The last list of approved targets read from the registry.
+#[derive(Debug, Default)]
+pub(crate) struct RegistryWhitelist {
+    current: RwLock<RegistryList>,
+}
+
+/// A list of approved targets, in the order of the registry.
+#[derive(Debug, Default)]
+struct RegistryList {
+    capability: DelegationCapability,
+    whitelist: Arc<DelegationWhitelist>,
+    /// Whether the registry was read successfully.
+    loaded: bool,
+}
+
+impl RegistryWhitelist {
+    /// Returns the last list read from the registry, which is empty until the first successful
+    /// read.
+    pub(crate) fn whitelist(&self) -> Arc<DelegationWhitelist> {
+        self.current.read().whitelist.clone()
+    }
+
+    /// Returns the approved targets, in the order of the registry.
+    pub(crate) fn capability(&self) -> DelegationCapability {
+        self.current.read().capability.clone()
+    }
+
+    /// Returns the approved targets, or `None` if the registry was not read successfully yet.
+    pub(crate) fn loaded_capability(&self) -> Option<DelegationCapability> {
+        let current = self.current.read();
+        current.loaded.then(|| current.capability.clone())
+    }
+
+    /// Updates the list with the output of `getApprovedTargets()`.
+    ///
+    /// If the read failed or its output is malformed, the last list is kept. Returns `true` if
+    /// the list was updated.
+    pub(crate) fn update<E: Debug>(&self, output: Result<Bytes, E>) -> bool {
+        let addresses = match output {
+            Ok(output) => decode_address_array(&output),
+            Err(err) => {
+                warn!(target: "rpc::wallet", ?err, "Error reading the whitelist registry");
+                return false;
+            }
+        };
+        let Some(addresses) = addresses else {
+            warn!(target: "rpc::wallet", "Malformed output of the whitelist registry");
+            return false;
+        };
+
+        let capability = DelegationCapability { addresses };
+        let whitelist = Arc::new(DelegationWhitelist::new(&capability));
+        *self.current.write() = RegistryList { capability, whitelist, loaded: true };
+        true
+    }
+}
+
+/// Decodes an ABI encoded `address[]`.
+fn decode_address_array(output: &[u8]) -> Option<Vec<Address>> {
+    let word = |at: usize| output.get(at..at.checked_add(32)?);
+    let to_usize = |word: &[u8]| usize::try_from(U256::from_be_slice(word)).ok();
+
+    let offset = to_usize(word(0)?)?;
+    let len = to_usize(word(offset)?)?;
+    (0..len)
+        .map(|i| {
+            let word = word(offset.checked_add(32)?.checked_add(i.checked_mul(32)?)?)?;
+            // addresses are left-padded with zeros
+            word[..12].iter().all(|byte| *byte == 0).then(|| Address::from_slice(&word[12..]))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::keccak256;
+
+    /// The output of a mock registry returning the given targets.
+    fn registry_output(targets: &[Address]) -> Bytes {
+        let mut output = Vec::new();
+        output.extend(U256::from(32).to_be_bytes::<32>());
+        output.extend(U256::from(targets.len()).to_be_bytes::<32>());
+        for target in targets {
+            output.extend([0; 12]);
+            output.extend(target.as_slice());
+        }
+        output.into()
+    }
+
+    #[test]
+    fn selector_matches_signature() {
+        assert_eq!(GET_APPROVED_TARGETS_SELECTOR, keccak256("getApprovedTargets()")[..4]);
+    }
+
+    #[test]
+    fn keeps_last_known_good_list() {
+        let a = Address::with_last_byte(1);
+        let b = Address::with_last_byte(2);
+        let registry = RegistryWhitelist::default();
+
+        // nothing is approved until the registry was read
+        assert!(!registry.whitelist().contains(&a));
+        assert_eq!(registry.loaded_capability(), None);
+
+        assert!(registry.update(Ok::<_, ()>(registry_output(&[a, b]))));
+        assert!(registry.whitelist().contains(&a));
+        assert_eq!(registry.capability().addresses, vec![a, b]);
+
+        // failed reads and malformed outputs keep the last list
+        assert!(!registry.update(Err("connection refused")));
+        assert!(!registry.update(Ok::<_, ()>(registry_output(&[a]).slice(..40))));
+        let mut dirty = registry_output(&[a]).to_vec();
+        dirty[64] = 1;
+        assert!(!registry.update(Ok::<_, ()>(dirty.into())));
+        assert!(registry.whitelist().contains(&b));
+
+        // targets removed from the registry are no longer approved
+        assert!(registry.update(Ok::<_, ()>(registry_output(&[b]))));
+        assert!(!registry.whitelist().contains(&a));
+        assert!(registry.whitelist().contains(&b));
+        assert!(registry.update(Ok::<_, ()>(registry_output(&[]))));
+        assert!(!registry.whitelist().contains(&b));
+    }
+}
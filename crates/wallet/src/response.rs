@@ -0,0 +1,300 @@
+//! Responses of the `wallet_` namespace.
+
+use alloy_primitives::{keccak256, Bytes, TxHash};
+use alloy_rpc_types::TransactionRequest;
+use revm_primitives::Bytecode;
+use serde::{Deserialize, Serialize};
+
+/// The response of `wallet_sendTransactionWithRaw`, also served as
+/// `wallet_sendTransactionVerbose`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SendTransactionResponse {
+    /// The hash of the submitted transaction.
+    pub hash: TxHash,
+    /// The EIP-2718 encoded signed transaction, exactly as it was submitted.
+    pub raw: Bytes,
+    /// The nonce the sequencer signed the transaction with.
+    pub nonce: u64,
+    /// The gas limit the sequencer signed the transaction with.
+    pub gas_limit: u64,
+    /// The max fee per gas the sequencer signed the transaction with.
+    pub max_fee_per_gas: u128,
+    /// A best-effort estimate of the number of blocks until the transaction is included, based
+    /// on recent fee history.
+    ///
+    /// This is a heuristic, and `None` if the fee history is not available.
+    pub estimated_inclusion_blocks: Option<u64>,
+    /// Where the transaction was submitted to.
+    pub submission: SubmissionOutcome,
+    /// Why the transaction was submitted there.
+    pub routing_reason: RoutingReason,
+    /// What the transaction does.
+    pub kind: SponsoredTxKind,
+    /// The correlation id of the request, as provided by the client or generated by the wallet.
+    ///
+    /// It is attached to all log lines of the request.
+    pub correlation_id: Option<String>,
+}
+
+impl SendTransactionResponse {
+    /// Creates a new response for the given EIP-2718 encoded transaction, signed with the given
+    /// nonce, gas limit and max fee per gas.
+    pub fn new(
+        raw: Bytes,
+        nonce: u64,
+        gas_limit: u64,
+        max_fee_per_gas: u128,
+        submission: SubmissionOutcome,
+        kind: SponsoredTxKind,
+    ) -> Self {
+        Self {
+            hash: keccak256(&raw),
+            raw,
+            nonce,
+            gas_limit,
+            max_fee_per_gas,
+            estimated_inclusion_blocks: None,
+            submission,
+            routing_reason: submission.reason(),
+            kind,
+            correlation_id: None,
+        }
+    }
+}
+
+/// What a sponsored transaction does, beyond its transaction type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SponsoredTxKind {
+    /// An [EIP-7702][eip-7702] transaction delegating accounts that are not delegated yet.
+    ///
+    /// [eip-7702]: https://eips.ethereum.org/EIPS/eip-7702
+    DelegationSetup,
+    /// An EIP-1559 transaction calling a delegated account.
+    DelegatedCall,
+    /// An [EIP-7702][eip-7702] transaction changing the delegation of an account that is
+    /// already delegated.
+    ///
+    /// [eip-7702]: https://eips.ethereum.org/EIPS/eip-7702
+    DelegationChange,
+}
+
+impl SponsoredTxKind {
+    /// Classifies an EIP-7702 transaction, given the current code of the authorities of its
+    /// authorizations.
+    ///
+    /// The transaction changes a delegation if any of the authorities is already delegated.
+    pub(crate) fn of_authorities(codes: impl IntoIterator<Item = Option<Bytecode>>) -> Self {
+        if codes.into_iter().flatten().any(|code| matches!(code, Bytecode::Eip7702(_))) {
+            Self::DelegationChange
+        } else {
+            Self::DelegationSetup
+        }
+    }
+}
+
+/// Where a sponsored transaction was submitted to.
+///
+/// This determines who includes the transaction: transactions in the local pool depend on local
+/// block production, while forwarded transactions depend on the remote sequencer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SubmissionOutcome {
+    /// The transaction was accepted into the local transaction pool.
+    LocalPool,
+    /// The transaction was forwarded to the sequencer.
+    ForwardedToSequencer {
+        /// The hash of the transaction, as acknowledged by the submission.
+        sequencer_response: TxHash,
+    },
+}
+
+impl SubmissionOutcome {
+    /// Returns the outcome of a submission that returned `hash`.
+    ///
+    /// The `eth_` API does not report whether it forwarded a transaction, so the path is derived
+    /// from whether the node is configured to forward transactions to a sequencer.
+    pub const fn new(forwards_to_sequencer: bool, hash: TxHash) -> Self {
+        if forwards_to_sequencer {
+            Self::ForwardedToSequencer { sequencer_response: hash }
+        } else {
+            Self::LocalPool
+        }
+    }
+
+    /// Returns why the transaction was submitted where it was.
+    pub const fn reason(&self) -> RoutingReason {
+        match self {
+            Self::LocalPool => RoutingReason::NoSequencerConfigured,
+            Self::ForwardedToSequencer { .. } => RoutingReason::ConfiguredToForward,
+        }
+    }
+}
+
+/// Why a sponsored transaction was submitted where it was.
+///
+/// The `eth_` API never falls back to the local pool if the sequencer is unreachable, but fails
+/// the submission instead, so the route only depends on the configuration of the node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RoutingReason {
+    /// No sequencer endpoint is configured, so the transaction was added to the local pool.
+    NoSequencerConfigured,
+    /// The node is configured to forward transactions to the sequencer.
+    ConfiguredToForward,
+}
+
+impl RoutingReason {
+    /// Returns a human readable description of the reason.
+    pub const fn description(&self) -> &'static str {
+        match self {
+            Self::NoSequencerConfigured => "no sequencer configured",
+            Self::ConfiguredToForward => "configured to forward to the sequencer",
+        }
+    }
+}
+
+/// How the calls of a batch are executed, as reported by `wallet_batchExecution`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BatchExecution {
+    /// The calls are executed in a single transaction, so either all or none of them succeed.
+    Atomic,
+    /// The calls are executed in separate transactions, so some may succeed while others fail.
+    Sequential,
+}
+
+impl BatchExecution {
+    /// Returns how a batch submitted as the given transactions is executed.
+    ///
+    /// `wallet_sendCalls` submits the transactions one by one, and stops at the first that fails,
+    /// so only a batch of a single transaction is atomic. The calls are still validated when they
+    /// are sent.
+    pub const fn of(transactions: &[TransactionRequest]) -> Self {
+        if transactions.len() == 1 {
+            Self::Atomic
+        } else {
+            Self::Sequential
+        }
+    }
+}
+
+/// The response of `wallet_health`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletHealth {
+    /// Whether the node is fully synced.
+    pub synced: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::{Transaction, TxEnvelope};
+    use alloy_network::eip2718::Decodable2718;
+    use alloy_primitives::{b256, hex, Address};
+    use revm_primitives::Eip7702Bytecode;
+
+    #[test]
+    fn raw_decodes_to_submitted_transaction() {
+        // an eip-1559 transaction with nonce 7, max fee 2 gwei and priority fee 1 gwei
+        let raw = Bytes::from(hex!("02f86e830de9fb07843b9aca008477359400830186a09400000000000000000000000000000000000000018080c001a0f56fe9ba3936adacf2706b951dffffef7c58a7ea9cdf44dbada9da82415c4ea9a034de3ce9df77616662f42f3545777adcb0f9469fdd047497493a83ff772afcb5"));
+        let response = SendTransactionResponse::new(
+            raw,
+            7,
+            100_000,
+            2_000_000_000,
+            SubmissionOutcome::LocalPool,
+            SponsoredTxKind::DelegatedCall,
+        );
+        assert_eq!(
+            response.hash,
+            b256!("932806f607bfeb7ed5847081642bb69392b8e02c4b8bd97f02f6033fd6684523")
+        );
+
+        let envelope = TxEnvelope::decode_2718(&mut response.raw.as_ref()).unwrap();
+        assert_eq!(*envelope.tx_hash(), response.hash);
+        assert_eq!(envelope.nonce(), response.nonce);
+        assert_eq!(envelope.gas_limit(), response.gas_limit);
+        assert_eq!(envelope.max_fee_per_gas(), response.max_fee_per_gas);
+        assert_eq!(envelope.max_priority_fee_per_gas(), Some(1_000_000_000));
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "hash": response.hash,
+                "raw": response.raw,
+                "nonce": 7,
+                "gas_limit": 100_000,
+                "max_fee_per_gas": 2_000_000_000u64,
+                "estimated_inclusion_blocks": null,
+                "submission": "LocalPool",
+                "routing_reason": "NoSequencerConfigured",
+                "kind": "DelegatedCall",
+                "correlation_id": null,
+            })
+        );
+        assert_eq!(serde_json::from_value::<SendTransactionResponse>(json).unwrap(), response);
+    }
+
+    #[test]
+    fn reports_submission_path() {
+        let hash = b256!("932806f607bfeb7ed5847081642bb69392b8e02c4b8bd97f02f6033fd6684523");
+
+        let local = SubmissionOutcome::new(false, hash);
+        assert_eq!(local, SubmissionOutcome::LocalPool);
+        assert_eq!(local.reason(), RoutingReason::NoSequencerConfigured);
+
+        let forwarded = SubmissionOutcome::new(true, hash);
+        assert_eq!(forwarded, SubmissionOutcome::ForwardedToSequencer { sequencer_response: hash });
+        assert_eq!(forwarded.reason(), RoutingReason::ConfiguredToForward);
+
+        // the reason is reported alongside the outcome
+        let response = SendTransactionResponse::new(
+            Bytes::new(),
+            0,
+            21_000,
+            1,
+            forwarded,
+            SponsoredTxKind::DelegatedCall,
+        );
+        assert_eq!(response.routing_reason, RoutingReason::ConfiguredToForward);
+    }
+
+    #[test]
+    fn classifies_authorizations() {
+        let delegated = |target: u8| {
+            Some(Bytecode::Eip7702(Eip7702Bytecode::new(Address::with_last_byte(target))))
+        };
+        let contract = Some(Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00])));
+
+        // authorities without code, or with regular code, are delegated for the first time
+        assert_eq!(SponsoredTxKind::of_authorities([None]), SponsoredTxKind::DelegationSetup);
+        assert_eq!(
+            SponsoredTxKind::of_authorities([None, contract]),
+            SponsoredTxKind::DelegationSetup
+        );
+
+        // re-delegating any authority changes its delegation
+        assert_eq!(
+            SponsoredTxKind::of_authorities([delegated(1)]),
+            SponsoredTxKind::DelegationChange
+        );
+        assert_eq!(
+            SponsoredTxKind::of_authorities([None, delegated(2)]),
+            SponsoredTxKind::DelegationChange
+        );
+
+        let kind = serde_json::to_value(SponsoredTxKind::DelegationChange).unwrap();
+        assert_eq!(kind, "DelegationChange");
+    }
+
+    #[test]
+    fn reports_batch_execution() {
+        let transaction = TransactionRequest::default;
+
+        // a single transaction is atomic
+        assert_eq!(BatchExecution::of(&[transaction()]), BatchExecution::Atomic);
+
+        // several transactions are not, nor is an empty batch
+        assert_eq!(BatchExecution::of(&[transaction(), transaction()]), BatchExecution::Sequential);
+        assert_eq!(BatchExecution::of(&[]), BatchExecution::Sequential);
+    }
+}
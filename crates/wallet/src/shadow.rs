@@ -0,0 +1,48 @@
+//! Evaluation of a candidate configuration against live traffic.
+//!
+//! If a [shadow configuration](crate::OdysseyWalletConfig::shadow_config) is set, each sponsored
+//! request is also evaluated against it, without affecting the live decision. Where the decisions
+//! diverge, this is logged and counted, so e.g. a stricter gas ceiling can be tried on real
+//! traffic before it is activated.
+
+use crate::OdysseyWalletError;
+
+/// The maximum number of shadow evaluations in flight. Requests beyond this are not evaluated.
+pub(crate) const MAX_SHADOW_EVALUATIONS: usize = 16;
+
+/// How the decision of the shadow configuration diverges from the live decision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Divergence {
+    /// The request was accepted, but the shadow configuration rejects it.
+    Rejected,
+    /// The request was rejected, but the shadow configuration accepts it.
+    Accepted,
+}
+
+impl Divergence {
+    /// Compares the live decision to the decision of the shadow configuration.
+    pub(crate) const fn of(
+        live_accepted: bool,
+        shadow: &Result<(), OdysseyWalletError>,
+    ) -> Option<Self> {
+        match (live_accepted, shadow) {
+            (true, Err(_)) => Some(Self::Rejected),
+            (false, Ok(())) => Some(Self::Accepted),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_divergence() {
+        let rejected = Err(OdysseyWalletError::BudgetExhausted);
+        assert_eq!(Divergence::of(true, &Ok(())), None);
+        assert_eq!(Divergence::of(false, &rejected), None);
+        assert_eq!(Divergence::of(true, &rejected), Some(Divergence::Rejected));
+        assert_eq!(Divergence::of(false, &Ok(())), Some(Divergence::Accepted));
+    }
+}
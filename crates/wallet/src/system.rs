@@ -0,0 +1,102 @@
+//! Built-in protection against sponsoring calls to precompiles and system contracts.
+
+use alloy_primitives::{address, Address};
+use std::ops::RangeInclusive;
+
+/// The range of addresses reserved for precompiles.
+///
+/// This covers the Ethereum precompiles, as well as any additional precompiles Odyssey installs,
+/// such as P256 verification.
+pub const PRECOMPILE_ADDRESSES: RangeInclusive<Address> =
+    address!("0000000000000000000000000000000000000000")
+        ..=address!("00000000000000000000000000000000000000ff");
+
+/// The range of addresses reserved for OP stack predeploys, e.g. the `L2CrossDomainMessenger` or
+/// the fee vaults.
+///
+/// See also <https://specs.optimism.io/protocol/predeploys.html>
+pub const OP_PREDEPLOY_ADDRESSES: RangeInclusive<Address> =
+    address!("4200000000000000000000000000000000000000")
+        ..=address!("42000000000000000000000000000000000007ff");
+
+/// The OP stack depositor account that submits L1 attributes transactions.
+pub const OP_L1_ATTRIBUTES_DEPOSITOR: Address =
+    address!("deaddeaddeaddeaddeaddeaddeaddeaddead0001");
+
+/// The [EIP-4788][eip-4788] beacon roots contract.
+///
+/// [eip-4788]: https://eips.ethereum.org/EIPS/eip-4788
+pub const BEACON_ROOTS_ADDRESS: Address = address!("000F3df6D732807Ef1319fB7B8bB8522d0Beac02");
+
+/// A set of precompile and system addresses that sponsored transactions may not target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemAddresses {
+    ranges: Vec<RangeInclusive<Address>>,
+}
+
+impl SystemAddresses {
+    /// Creates an empty set, i.e. no address is considered a system address.
+    pub const fn none() -> Self {
+        Self { ranges: Vec::new() }
+    }
+
+    /// Creates the default set for OP stack chains.
+    ///
+    /// This includes the precompiles, the predeploys and other known system accounts.
+    pub fn op_stack() -> Self {
+        Self::none()
+            .with_range(PRECOMPILE_ADDRESSES)
+            .with_range(OP_PREDEPLOY_ADDRESSES)
+            .with_address(OP_L1_ATTRIBUTES_DEPOSITOR)
+            .with_address(BEACON_ROOTS_ADDRESS)
+    }
+
+    /// Adds a range of addresses to the set.
+    pub fn with_range(mut self, range: RangeInclusive<Address>) -> Self {
+        self.ranges.push(range);
+        self
+    }
+
+    /// Adds a single address to the set.
+    pub fn with_address(self, address: Address) -> Self {
+        self.with_range(address..=address)
+    }
+
+    /// Returns `true` if the address is a system address.
+    pub fn contains(&self, address: &Address) -> bool {
+        self.ranges.iter().any(|range| range.contains(address))
+    }
+}
+
+impl Default for SystemAddresses {
+    fn default() -> Self {
+        Self::op_stack()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn op_stack_defaults() {
+        let system = SystemAddresses::op_stack();
+
+        // ecrecover and p256verify
+        assert!(system.contains(&address!("0000000000000000000000000000000000000001")));
+        assert!(system.contains(&address!("0000000000000000000000000000000000000014")));
+        // L2CrossDomainMessenger and the L1 fee vault
+        assert!(system.contains(&address!("4200000000000000000000000000000000000007")));
+        assert!(system.contains(&address!("420000000000000000000000000000000000001a")));
+        assert!(system.contains(&OP_L1_ATTRIBUTES_DEPOSITOR));
+
+        // a regular delegated account
+        assert!(!system.contains(&address!("70997970C51812dc3A010C7d01b50e0d17dc79C8")));
+        assert!(!system.contains(&address!("4200000000000000000000000000000000000800")));
+    }
+
+    #[test]
+    fn none_contains_nothing() {
+        assert!(!SystemAddresses::none().contains(&Address::with_last_byte(1)));
+    }
+}
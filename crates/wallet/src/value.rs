@@ -0,0 +1,122 @@
+//! Detection of calls that revert because they are sent without value.
+//!
+//! Sponsored transactions never carry value, so calls to contracts that require `msg.value` are
+//! guaranteed to revert. Such calls are detected by estimating a reverting request again with a
+//! probe value: if it then succeeds, the revert was caused by the missing value.
+
+use crate::OdysseyWalletError;
+use alloy_primitives::{Bytes, U256};
+use jsonrpsee::types::error::ErrorObject;
+use std::future::Future;
+use tracing::debug;
+
+/// The JSON-RPC error code of a reverted execution, as returned by the estimate.
+pub(crate) const EXECUTION_REVERTED_CODE: i32 = 3;
+
+/// The selector of the `Error(string)` revert reason.
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// Returns the revert data of the error, if it reports a reverted execution.
+pub(crate) fn revert_data(err: &ErrorObject<'_>) -> Option<Bytes> {
+    if err.code() != EXECUTION_REVERTED_CODE {
+        return None;
+    }
+    serde_json::from_str(err.data()?.get()).ok()
+}
+
+/// Decodes the reason of an `Error(string)` revert, if the data is one.
+pub(crate) fn decode_revert_reason(data: &[u8]) -> Option<String> {
+    let encoded = data.strip_prefix(ERROR_SELECTOR.as_slice())?;
+    let offset = usize::try_from(U256::from_be_slice(encoded.get(..32)?)).ok()?;
+    let len_end = offset.checked_add(32)?;
+    let len = usize::try_from(U256::from_be_slice(encoded.get(offset..len_end)?)).ok()?;
+    let reason = encoded.get(len_end..len_end.checked_add(len)?)?;
+    String::from_utf8(reason.to_vec()).ok()
+}
+
+/// Returns the error to report for a failed estimate.
+///
+/// If the estimate reverted, it is repeated with `probe` as value. If it then succeeds,
+/// [`OdysseyWalletError::DestinationRequiresValue`] is returned, otherwise the original error.
+pub(crate) async fn explain_estimate_error<F, Fut>(
+    err: ErrorObject<'static>,
+    probe: U256,
+    estimate_with_value: F,
+) -> ErrorObject<'static>
+where
+    F: FnOnce(U256) -> Fut,
+    Fut: Future<Output = bool>,
+{
+    let Some(data) = revert_data(&err) else {
+        return err;
+    };
+    if !estimate_with_value(probe).await {
+        return err;
+    }
+    debug!(
+        target: "rpc::wallet",
+        reason = decode_revert_reason(&data),
+        %probe,
+        "Estimate reverts without value"
+    );
+    OdysseyWalletError::DestinationRequiresValue.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::hex;
+
+    /// The revert of `require(msg.value > 0, "value required")`.
+    fn value_required_revert() -> ErrorObject<'static> {
+        let data = hex!(
+            "08c379a0"
+            "0000000000000000000000000000000000000000000000000000000000000020"
+            "000000000000000000000000000000000000000000000000000000000000000e"
+            "76616c7565207265717569726564000000000000000000000000000000000000"
+        );
+        ErrorObject::owned(
+            EXECUTION_REVERTED_CODE,
+            "execution reverted: value required",
+            Some(Bytes::from(data)),
+        )
+    }
+
+    #[test]
+    fn decodes_revert_reasons() {
+        let data = revert_data(&value_required_revert()).unwrap();
+        assert_eq!(decode_revert_reason(&data).as_deref(), Some("value required"));
+
+        // custom errors and truncated data have no reason
+        assert_eq!(decode_revert_reason(&hex!("deadbeef")), None);
+        assert_eq!(decode_revert_reason(&data[..data.len() - 40]), None);
+        // other errors are not reverts
+        let err = ErrorObject::owned(-32000, "out of gas", Some(Bytes::new()));
+        assert_eq!(revert_data(&err), None);
+    }
+
+    #[tokio::test]
+    async fn detects_value_requirement() {
+        // a stub contract that only succeeds when called with value
+        let requires_value = |value: U256| async move { !value.is_zero() };
+        let probe = U256::from(1);
+
+        assert_eq!(
+            explain_estimate_error(value_required_revert(), probe, requires_value).await,
+            OdysseyWalletError::DestinationRequiresValue.into()
+        );
+
+        // the call reverts regardless of the value
+        assert_eq!(
+            explain_estimate_error(value_required_revert(), probe, |_| async { false }).await,
+            value_required_revert()
+        );
+
+        // errors other than reverts are not probed
+        let err = ErrorObject::from(OdysseyWalletError::InternalError);
+        assert_eq!(
+            explain_estimate_error(err.clone(), probe, |_| async { unreachable!() }).await,
+            err
+        );
+    }
+}
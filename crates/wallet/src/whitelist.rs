@@ -0,0 +1,155 @@
+//! The whitelist of delegation targets.
+
+use crate::DelegationCapability;
+use alloy_primitives::Address;
+use std::collections::HashSet;
+use tracing::warn;
+
+/// How duplicate entries in the delegation whitelist are handled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateWhitelistEntries {
+    /// Duplicates are removed, and a warning is logged.
+    #[default]
+    Warn,
+    /// Configurations with duplicates are rejected.
+    Reject,
+}
+
+/// How the configured delegation whitelist is combined with the list read from the whitelist
+/// registry, if both are set.
+///
+/// Until the registry was read successfully, its list is considered empty.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WhitelistSources {
+    /// Targets listed by either source are sponsored.
+    ///
+    /// Either source alone can approve a target, so whoever controls the registry, or the
+    /// configuration, can get any target sponsored.
+    Union,
+    /// Only targets listed by both sources are sponsored.
+    ///
+    /// Neither source alone can approve a target, so a compromised registry cannot get an
+    /// unlisted target sponsored, but either source can revoke a target. Nothing is sponsored
+    /// until the registry was read.
+    Intersection,
+    /// The registry is authoritative once it was read, and the configured whitelist only applies
+    /// until then.
+    ///
+    /// Whoever controls the registry controls which targets are sponsored, including targets
+    /// that were never configured.
+    #[default]
+    RegistryOverride,
+}
+
+impl WhitelistSources {
+    /// Combines the configured whitelist with the list read from the registry, or `None` if the
+    /// registry was not read yet, preserving the order of the entries.
+    pub(crate) fn merge(
+        &self,
+        configured: &DelegationCapability,
+        registry: Option<&DelegationCapability>,
+    ) -> DelegationCapability {
+        let registry = registry.map(|registry| registry.addresses.as_slice());
+        let addresses = match (self, registry) {
+            (Self::Union, registry) => {
+                configured.addresses.iter().chain(registry.unwrap_or_default()).copied().collect()
+            }
+            (Self::Intersection, registry) => {
+                let registry = registry.unwrap_or_default();
+                configured
+                    .addresses
+                    .iter()
+                    .filter(|address| registry.contains(address))
+                    .copied()
+                    .collect()
+            }
+            (Self::RegistryOverride, Some(registry)) => registry.to_vec(),
+            (Self::RegistryOverride, None) => configured.addresses.clone(),
+        };
+        DelegationCapability { addresses }
+    }
+}
+
+/// The set of delegation targets the wallet sponsors.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct DelegationWhitelist {
+    addresses: HashSet<Address>,
+}
+
+impl DelegationWhitelist {
+    /// Creates the whitelist from the addresses of the capability, removing duplicates.
+    pub(crate) fn new(capability: &DelegationCapability) -> Self {
+        let duplicates = duplicates(&capability.addresses);
+        if !duplicates.is_empty() {
+            warn!(target: "rpc::wallet", ?duplicates, "Duplicate entries in delegation whitelist");
+        }
+        Self { addresses: capability.addresses.iter().copied().collect() }
+    }
+
+    /// Creates the whitelist from merged sources, which may list a target more than once.
+    pub(crate) fn merged(capability: &DelegationCapability) -> Self {
+        Self { addresses: capability.addresses.iter().copied().collect() }
+    }
+
+    /// Returns `true` if the target is whitelisted.
+    pub(crate) fn contains(&self, target: &Address) -> bool {
+        self.addresses.contains(target)
+    }
+}
+
+/// Returns the addresses that appear more than once, in the order of their first repetition.
+pub(crate) fn duplicates(addresses: &[Address]) -> Vec<Address> {
+    let mut seen = HashSet::with_capacity(addresses.len());
+    let mut duplicates = Vec::new();
+    for address in addresses {
+        if !seen.insert(address) && !duplicates.contains(address) {
+            duplicates.push(*address);
+        }
+    }
+    duplicates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deduplicates_entries() {
+        let a = Address::with_last_byte(1);
+        let b = Address::with_last_byte(2);
+        let capability = DelegationCapability { addresses: vec![a, b, a, a, b] };
+
+        assert_eq!(duplicates(&capability.addresses), vec![a, b]);
+        assert!(duplicates(&[a, b]).is_empty());
+
+        let whitelist = DelegationWhitelist::new(&capability);
+        assert_eq!(whitelist.addresses.len(), 2);
+        assert!(whitelist.contains(&a));
+        assert!(whitelist.contains(&b));
+        assert!(!whitelist.contains(&Address::ZERO));
+    }
+
+    #[test]
+    fn merges_sources() {
+        let (only_config, only_registry, both) =
+            (Address::with_last_byte(1), Address::with_last_byte(2), Address::with_last_byte(3));
+        let configured = DelegationCapability { addresses: vec![only_config, both] };
+        let registry = DelegationCapability { addresses: vec![both, only_registry] };
+        let merge = |sources: WhitelistSources, registry| sources.merge(&configured, registry);
+
+        assert_eq!(
+            merge(WhitelistSources::Union, Some(&registry)).addresses,
+            vec![only_config, both, both, only_registry]
+        );
+        assert_eq!(merge(WhitelistSources::Intersection, Some(&registry)).addresses, vec![both]);
+        assert_eq!(
+            merge(WhitelistSources::RegistryOverride, Some(&registry)).addresses,
+            vec![both, only_registry]
+        );
+
+        // until the registry was read
+        assert_eq!(merge(WhitelistSources::Union, None), configured);
+        assert!(merge(WhitelistSources::Intersection, None).addresses.is_empty());
+        assert_eq!(merge(WhitelistSources::RegistryOverride, None), configured);
+    }
+}